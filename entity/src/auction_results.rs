@@ -13,10 +13,17 @@ pub struct Model {
     pub deleted_at: Option<DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
+    pub quantity: i32,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub unit_price: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((12, 2)))")]
+    pub hammer_price: Decimal,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::auction_result_fees::Entity")]
+    AuctionResultFees,
     #[sea_orm(
         belongs_to = "super::bids::Entity",
         from = "Column::WinningBidId",
@@ -25,6 +32,8 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Bids,
+    #[sea_orm(has_many = "super::invoice_lines::Entity")]
+    InvoiceLines,
     #[sea_orm(
         belongs_to = "super::listings::Entity",
         from = "Column::ListingId",
@@ -33,6 +42,8 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Listings,
+    #[sea_orm(has_many = "super::payout_items::Entity")]
+    PayoutItems,
     #[sea_orm(
         belongs_to = "super::users::Entity",
         from = "Column::WinningUserId",
@@ -43,18 +54,36 @@ pub enum Relation {
     Users,
 }
 
+impl Related<super::auction_result_fees::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AuctionResultFees.def()
+    }
+}
+
 impl Related<super::bids::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Bids.def()
     }
 }
 
+impl Related<super::invoice_lines::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::InvoiceLines.def()
+    }
+}
+
 impl Related<super::listings::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Listings.def()
     }
 }
 
+impl Related<super::payout_items::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PayoutItems.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
@@ -1,5 +1,8 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.15
 
+use super::sea_orm_active_enums::AuctionDirection;
+use super::sea_orm_active_enums::AuctionFormat;
+use super::sea_orm_active_enums::VerificationLevel;
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -14,14 +17,62 @@ pub struct Model {
     pub deleted_at: Option<DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))", nullable)]
+    pub deposit_amount: Option<Decimal>,
+    pub requires_credit_approval: bool,
+    pub format: AuctionFormat,
+    pub reveal_end_time: Option<DateTime>,
+    pub direction: AuctionDirection,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))", nullable)]
+    pub bid_increment: Option<Decimal>,
+    pub candle_end_time: Option<DateTime>,
+    pub candle_seed: Option<String>,
+    pub candle_seed_hash: Option<String>,
+    pub live: bool,
+    pub requires_registration: bool,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub terms: Option<String>,
+    pub min_verification_level: VerificationLevel,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::auction_registrations::Entity")]
+    AuctionRegistrations,
+    #[sea_orm(has_many = "super::bidder_limits::Entity")]
+    BidderLimits,
+    #[sea_orm(has_many = "super::fee_rules::Entity")]
+    FeeRules,
+    #[sea_orm(has_many = "super::invoices::Entity")]
+    Invoices,
     #[sea_orm(has_many = "super::listings::Entity")]
     Listings,
 }
 
+impl Related<super::auction_registrations::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AuctionRegistrations.def()
+    }
+}
+
+impl Related<super::bidder_limits::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BidderLimits.def()
+    }
+}
+
+impl Related<super::fee_rules::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::FeeRules.def()
+    }
+}
+
+impl Related<super::invoices::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Invoices.def()
+    }
+}
+
 impl Related<super::listings::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Listings.def()
@@ -1,5 +1,6 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.15
 
+use super::sea_orm_active_enums::BidChannel;
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -14,12 +15,23 @@ pub struct Model {
     pub deleted_at: Option<DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
+    pub quantity: i32,
+    pub channel: BidChannel,
+    pub placed_by_id: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub note: Option<String>,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))", nullable)]
+    pub max_amount: Option<Decimal>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::auction_results::Entity")]
     AuctionResults,
+    #[sea_orm(has_many = "super::bid_audits::Entity")]
+    BidAudits,
+    #[sea_orm(has_many = "super::bid_commitments::Entity")]
+    BidCommitments,
     #[sea_orm(
         belongs_to = "super::listings::Entity",
         from = "Column::ListingId",
@@ -44,6 +56,18 @@ impl Related<super::auction_results::Entity> for Entity {
     }
 }
 
+impl Related<super::bid_audits::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BidAudits.def()
+    }
+}
+
+impl Related<super::bid_commitments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BidCommitments.def()
+    }
+}
+
 impl Related<super::listings::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Listings.def()
@@ -1,5 +1,7 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.15
 
+use super::sea_orm_active_enums::ClearingRule;
+use super::sea_orm_active_enums::VerificationLevel;
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -17,6 +19,24 @@ pub struct Model {
     pub deleted_at: Option<DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
+    pub seller_id: Option<i32>,
+    pub clearing_rule: ClearingRule,
+    pub unit_of_measure: String,
+    pub min_quantity: i32,
+    pub max_quantity: Option<i32>,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))", nullable)]
+    pub start_price: Option<Decimal>,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))", nullable)]
+    pub price_step: Option<Decimal>,
+    pub price_step_seconds: Option<i32>,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))", nullable)]
+    pub buy_now_price: Option<Decimal>,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))", nullable)]
+    pub buy_now_threshold: Option<Decimal>,
+    pub closed_at: Option<DateTime>,
+    pub lot_number: Option<i32>,
+    pub opened_at: Option<DateTime>,
+    pub min_verification_level: Option<VerificationLevel>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -31,8 +51,22 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Auctions,
+    #[sea_orm(has_many = "super::bid_commitments::Entity")]
+    BidCommitments,
     #[sea_orm(has_many = "super::bids::Entity")]
     Bids,
+    #[sea_orm(has_many = "super::orders::Entity")]
+    Orders,
+    #[sea_orm(has_many = "super::trades::Entity")]
+    Trades,
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::SellerId",
+        to = "super::users::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Restrict"
+    )]
+    Users,
 }
 
 impl Related<super::auction_results::Entity> for Entity {
@@ -47,10 +81,34 @@ impl Related<super::auctions::Entity> for Entity {
     }
 }
 
+impl Related<super::bid_commitments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BidCommitments.def()
+    }
+}
+
 impl Related<super::bids::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Bids.def()
     }
 }
 
+impl Related<super::orders::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Orders.def()
+    }
+}
+
+impl Related<super::trades::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Trades.def()
+    }
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Users.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -2,8 +2,31 @@
 
 pub mod prelude;
 
+pub mod auction_registrations;
+pub mod auction_result_fees;
 pub mod auction_results;
 pub mod auctions;
+pub mod bid_audits;
+pub mod bid_commitments;
+pub mod bidder_limits;
 pub mod bids;
+pub mod fee_rules;
+pub mod invoice_lines;
+pub mod invoices;
+pub mod journal_entries;
+pub mod kyc_documents;
+pub mod kyc_reviews;
+pub mod ledger_accounts;
+pub mod ledger_postings;
 pub mod listings;
+pub mod notifications;
+pub mod one_time_codes;
+pub mod orders;
+pub mod payout_batches;
+pub mod payout_items;
+pub mod payouts;
+pub mod phone_number_changes;
+pub mod phone_number_history;
+pub mod sea_orm_active_enums;
+pub mod trades;
 pub mod users;
@@ -1,7 +1,29 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.15
 
+pub use super::auction_registrations::Entity as AuctionRegistrations;
+pub use super::auction_result_fees::Entity as AuctionResultFees;
 pub use super::auction_results::Entity as AuctionResults;
 pub use super::auctions::Entity as Auctions;
+pub use super::bid_audits::Entity as BidAudits;
+pub use super::bid_commitments::Entity as BidCommitments;
+pub use super::bidder_limits::Entity as BidderLimits;
 pub use super::bids::Entity as Bids;
+pub use super::fee_rules::Entity as FeeRules;
+pub use super::invoice_lines::Entity as InvoiceLines;
+pub use super::invoices::Entity as Invoices;
+pub use super::journal_entries::Entity as JournalEntries;
+pub use super::kyc_documents::Entity as KycDocuments;
+pub use super::kyc_reviews::Entity as KycReviews;
+pub use super::ledger_accounts::Entity as LedgerAccounts;
+pub use super::ledger_postings::Entity as LedgerPostings;
 pub use super::listings::Entity as Listings;
+pub use super::notifications::Entity as Notifications;
+pub use super::one_time_codes::Entity as OneTimeCodes;
+pub use super::orders::Entity as Orders;
+pub use super::payout_batches::Entity as PayoutBatches;
+pub use super::payout_items::Entity as PayoutItems;
+pub use super::payouts::Entity as Payouts;
+pub use super::phone_number_changes::Entity as PhoneNumberChanges;
+pub use super::phone_number_history::Entity as PhoneNumberHistory;
+pub use super::trades::Entity as Trades;
 pub use super::users::Entity as Users;
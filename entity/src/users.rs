@@ -1,5 +1,9 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.15
 
+use super::sea_orm_active_enums::KycStatus;
+use super::sea_orm_active_enums::PreferredLanguage;
+use super::sea_orm_active_enums::UserRole;
+use super::sea_orm_active_enums::VerificationLevel;
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -13,14 +17,71 @@ pub struct Model {
     pub deleted_at: Option<DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
+    pub role: UserRole,
+    #[sea_orm(unique)]
+    pub national_id_number: Option<String>,
+    pub kyc_status: KycStatus,
+    pub verification_level: VerificationLevel,
+    pub kyc_submitted_at: Option<DateTime>,
+    #[sea_orm(unique)]
+    pub email: Option<String>,
+    pub county: Option<String>,
+    pub location: Option<String>,
+    pub preferred_language: PreferredLanguage,
+    pub notify_whatsapp: bool,
+    pub notify_email: bool,
+    pub quiet_hours_start: Option<Time>,
+    pub quiet_hours_end: Option<Time>,
+    pub utc_offset_minutes: i32,
+    pub marketing_consent: bool,
+    pub marketing_consent_at: Option<DateTime>,
+    pub erased_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::auction_registrations::Entity")]
+    AuctionRegistrations,
     #[sea_orm(has_many = "super::auction_results::Entity")]
     AuctionResults,
+    #[sea_orm(has_many = "super::bid_audits::Entity")]
+    BidAudits,
+    #[sea_orm(has_many = "super::bid_commitments::Entity")]
+    BidCommitments,
+    #[sea_orm(has_many = "super::bidder_limits::Entity")]
+    BidderLimits,
     #[sea_orm(has_many = "super::bids::Entity")]
     Bids,
+    #[sea_orm(has_many = "super::invoices::Entity")]
+    Invoices,
+    #[sea_orm(has_many = "super::kyc_documents::Entity")]
+    KycDocuments,
+    #[sea_orm(has_many = "super::kyc_reviews::Entity")]
+    KycReviews,
+    #[sea_orm(has_many = "super::ledger_accounts::Entity")]
+    LedgerAccounts,
+    #[sea_orm(has_many = "super::listings::Entity")]
+    Listings,
+    #[sea_orm(has_many = "super::notifications::Entity")]
+    Notifications,
+    #[sea_orm(has_many = "super::one_time_codes::Entity")]
+    OneTimeCodes,
+    #[sea_orm(has_many = "super::orders::Entity")]
+    Orders,
+    #[sea_orm(has_many = "super::payout_batches::Entity")]
+    PayoutBatches,
+    #[sea_orm(has_many = "super::payouts::Entity")]
+    Payouts,
+    #[sea_orm(has_many = "super::phone_number_changes::Entity")]
+    PhoneNumberChanges,
+    #[sea_orm(has_many = "super::phone_number_history::Entity")]
+    PhoneNumberHistory,
+}
+
+impl Related<super::auction_registrations::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AuctionRegistrations.def()
+    }
 }
 
 impl Related<super::auction_results::Entity> for Entity {
@@ -29,10 +90,100 @@ impl Related<super::auction_results::Entity> for Entity {
     }
 }
 
+impl Related<super::bid_audits::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BidAudits.def()
+    }
+}
+
+impl Related<super::bid_commitments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BidCommitments.def()
+    }
+}
+
+impl Related<super::bidder_limits::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BidderLimits.def()
+    }
+}
+
 impl Related<super::bids::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Bids.def()
     }
 }
 
+impl Related<super::invoices::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Invoices.def()
+    }
+}
+
+impl Related<super::kyc_documents::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::KycDocuments.def()
+    }
+}
+
+impl Related<super::kyc_reviews::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::KycReviews.def()
+    }
+}
+
+impl Related<super::ledger_accounts::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::LedgerAccounts.def()
+    }
+}
+
+impl Related<super::listings::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Listings.def()
+    }
+}
+
+impl Related<super::notifications::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Notifications.def()
+    }
+}
+
+impl Related<super::one_time_codes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OneTimeCodes.def()
+    }
+}
+
+impl Related<super::orders::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Orders.def()
+    }
+}
+
+impl Related<super::payout_batches::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PayoutBatches.def()
+    }
+}
+
+impl Related<super::payouts::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Payouts.def()
+    }
+}
+
+impl Related<super::phone_number_changes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PhoneNumberChanges.def()
+    }
+}
+
+impl Related<super::phone_number_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PhoneNumberHistory.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
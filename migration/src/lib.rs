@@ -2,10 +2,43 @@ pub use sea_orm_migration::prelude::*;
 
 // mod m20220101_000001_create_table;
 mod m20250219_000013_create_auctions_table;
+// Shipped migrations are left as they are, unused idents included
+#[allow(dead_code)]
 mod m20250219_000451_create_listings_table;
 mod m20250219_001043_create_users_table;
 mod m20250219_001443_create_bids_table;
+#[allow(dead_code)]
 mod m20250219_002133_create_auction_results_table;
+mod m20250301_000100_create_fee_rules_table;
+mod m20250301_000200_create_auction_result_fees_table;
+mod m20250305_000100_create_invoices_table;
+mod m20250310_000100_create_ledger_tables;
+mod m20250315_000100_create_bidder_limits_table;
+mod m20250320_000100_add_seller_to_listings;
+mod m20250325_000100_create_payout_tables;
+mod m20250330_000100_add_quantities_to_bids;
+mod m20250405_000100_add_units_to_listings;
+mod m20250410_000100_add_format_to_auctions;
+mod m20250415_000100_create_bid_commitments_table;
+mod m20250420_000100_add_dutch_auctions;
+mod m20250425_000100_add_direction_to_auctions;
+mod m20250430_000100_create_order_book_tables;
+mod m20250505_000100_add_buy_now_to_listings;
+mod m20250510_000100_add_candle_close_to_auctions;
+mod m20250515_000100_add_live_mode_to_auctions;
+mod m20250520_000100_add_staff_bids;
+mod m20250525_000100_create_bid_audits_table;
+mod m20250530_000100_create_auction_registrations_table;
+mod m20250604_000100_add_kyc_verification;
+mod m20250609_000100_add_user_profiles;
+mod m20250614_000100_create_phone_number_changes;
+mod m20250619_000100_add_user_erasure;
+mod m20250624_000100_create_one_time_codes;
+mod m20250629_000100_add_data_code_purposes;
+mod m20250704_000100_add_unique_invoice_lines;
+mod m20250709_000100_add_approver_to_payout_batches;
+mod m20250714_000100_add_carried_forward_to_payouts;
+mod m20250719_000100_add_sending_payout_status;
 
 pub struct Migrator;
 
@@ -19,6 +52,36 @@ impl MigratorTrait for Migrator {
             Box::new(m20250219_001043_create_users_table::Migration),
             Box::new(m20250219_001443_create_bids_table::Migration),
             Box::new(m20250219_002133_create_auction_results_table::Migration),
+            Box::new(m20250301_000100_create_fee_rules_table::Migration),
+            Box::new(m20250301_000200_create_auction_result_fees_table::Migration),
+            Box::new(m20250305_000100_create_invoices_table::Migration),
+            Box::new(m20250310_000100_create_ledger_tables::Migration),
+            Box::new(m20250315_000100_create_bidder_limits_table::Migration),
+            Box::new(m20250320_000100_add_seller_to_listings::Migration),
+            Box::new(m20250325_000100_create_payout_tables::Migration),
+            Box::new(m20250330_000100_add_quantities_to_bids::Migration),
+            Box::new(m20250405_000100_add_units_to_listings::Migration),
+            Box::new(m20250410_000100_add_format_to_auctions::Migration),
+            Box::new(m20250415_000100_create_bid_commitments_table::Migration),
+            Box::new(m20250420_000100_add_dutch_auctions::Migration),
+            Box::new(m20250425_000100_add_direction_to_auctions::Migration),
+            Box::new(m20250430_000100_create_order_book_tables::Migration),
+            Box::new(m20250505_000100_add_buy_now_to_listings::Migration),
+            Box::new(m20250510_000100_add_candle_close_to_auctions::Migration),
+            Box::new(m20250515_000100_add_live_mode_to_auctions::Migration),
+            Box::new(m20250520_000100_add_staff_bids::Migration),
+            Box::new(m20250525_000100_create_bid_audits_table::Migration),
+            Box::new(m20250530_000100_create_auction_registrations_table::Migration),
+            Box::new(m20250604_000100_add_kyc_verification::Migration),
+            Box::new(m20250609_000100_add_user_profiles::Migration),
+            Box::new(m20250614_000100_create_phone_number_changes::Migration),
+            Box::new(m20250619_000100_add_user_erasure::Migration),
+            Box::new(m20250624_000100_create_one_time_codes::Migration),
+            Box::new(m20250629_000100_add_data_code_purposes::Migration),
+            Box::new(m20250704_000100_add_unique_invoice_lines::Migration),
+            Box::new(m20250709_000100_add_approver_to_payout_batches::Migration),
+            Box::new(m20250714_000100_add_carried_forward_to_payouts::Migration),
+            Box::new(m20250719_000100_add_sending_payout_status::Migration),
         ]
     }
 }
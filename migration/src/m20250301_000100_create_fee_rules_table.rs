@@ -0,0 +1,132 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(FeeType::Enum)
+                    .values([
+                        FeeType::BuyersPremium,
+                        FeeType::SellerCommission,
+                        FeeType::LotFee,
+                        FeeType::Tax,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(FeeParty::Enum)
+                    .values([FeeParty::Buyer, FeeParty::Seller])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(FeeRules::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(FeeRules::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(FeeRules::AuctionId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-fee_rules-auction_id")
+                            .from(FeeRules::Table, FeeRules::AuctionId)
+                            .to(Auctions::Table, Auctions::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(FeeRules::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(FeeRules::FeeType)
+                            .enumeration(FeeType::Enum, [
+                                FeeType::BuyersPremium,
+                                FeeType::SellerCommission,
+                                FeeType::LotFee,
+                                FeeType::Tax,
+                            ])
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(FeeRules::Party)
+                            .enumeration(FeeParty::Enum, [FeeParty::Buyer, FeeParty::Seller])
+                            .not_null()
+                    )
+                    .col(ColumnDef::new(FeeRules::Rate).decimal_len(7, 4))
+                    .col(ColumnDef::new(FeeRules::FixedAmount).decimal_len(10, 2))
+                    .col(ColumnDef::new(FeeRules::MinAmount).decimal_len(10, 2).not_null().default(0))
+                    .col(ColumnDef::new(FeeRules::DeletedAt).timestamp())
+                    .col(ColumnDef::new(FeeRules::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(FeeRules::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(FeeRules::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(FeeParty::Enum).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(FeeType::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum FeeRules {
+    Table,
+    Id,
+    AuctionId,
+    Name,
+    FeeType,
+    Party,
+    Rate,
+    FixedAmount,
+    MinAmount,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum FeeType {
+    #[sea_orm(iden = "fee_type")]
+    Enum,
+    BuyersPremium,
+    SellerCommission,
+    LotFee,
+    Tax,
+}
+
+#[derive(DeriveIden)]
+enum FeeParty {
+    #[sea_orm(iden = "fee_party")]
+    Enum,
+    Buyer,
+    Seller,
+}
+
+#[derive(DeriveIden)]
+enum Auctions {
+    Table,
+    Id,
+}
@@ -0,0 +1,116 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuctionResultFees::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuctionResultFees::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AuctionResultFees::AuctionResultId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-auction_result_fees-auction_result_id")
+                            .from(AuctionResultFees::Table, AuctionResultFees::AuctionResultId)
+                            .to(AuctionResults::Table, AuctionResults::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(AuctionResultFees::FeeRuleId).integer())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-auction_result_fees-fee_rule_id")
+                            .from(AuctionResultFees::Table, AuctionResultFees::FeeRuleId)
+                            .to(FeeRules::Table, FeeRules::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                    )
+                    .col(
+                        ColumnDef::new(AuctionResultFees::FeeType)
+                            .enumeration(FeeType::Enum, [
+                                FeeType::BuyersPremium,
+                                FeeType::SellerCommission,
+                                FeeType::LotFee,
+                                FeeType::Tax,
+                            ])
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AuctionResultFees::Party)
+                            .enumeration(FeeParty::Enum, [FeeParty::Buyer, FeeParty::Seller])
+                            .not_null()
+                    )
+                    .col(ColumnDef::new(AuctionResultFees::Description).string().not_null())
+                    .col(ColumnDef::new(AuctionResultFees::BaseAmount).decimal_len(10, 2).not_null())
+                    .col(ColumnDef::new(AuctionResultFees::Rate).decimal_len(7, 4))
+                    .col(ColumnDef::new(AuctionResultFees::Amount).decimal_len(10, 2).not_null())
+                    .col(ColumnDef::new(AuctionResultFees::DeletedAt).timestamp())
+                    .col(ColumnDef::new(AuctionResultFees::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(AuctionResultFees::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuctionResultFees::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuctionResultFees {
+    Table,
+    Id,
+    AuctionResultId,
+    FeeRuleId,
+    FeeType,
+    Party,
+    Description,
+    BaseAmount,
+    Rate,
+    Amount,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum FeeType {
+    #[sea_orm(iden = "fee_type")]
+    Enum,
+    BuyersPremium,
+    SellerCommission,
+    LotFee,
+    Tax,
+}
+
+#[derive(DeriveIden)]
+enum FeeParty {
+    #[sea_orm(iden = "fee_party")]
+    Enum,
+    Buyer,
+    Seller,
+}
+
+#[derive(DeriveIden)]
+enum AuctionResults {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum FeeRules {
+    Table,
+    Id,
+}
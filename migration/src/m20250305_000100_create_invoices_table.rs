@@ -0,0 +1,195 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(InvoiceStatus::Enum)
+                    .values([InvoiceStatus::Issued, InvoiceStatus::Paid, InvoiceStatus::Void])
+                    .to_owned(),
+            )
+            .await?;
+
+        // Invoice numbers come from their own sequence so they increase in issue order and are never reused
+        manager
+            .get_connection()
+            .execute_unprepared("CREATE SEQUENCE IF NOT EXISTS invoice_number_seq START 1")
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Invoices::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Invoices::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Invoices::InvoiceNumber).string().not_null().unique_key())
+                    .col(ColumnDef::new(Invoices::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-invoices-user_id")
+                            .from(Invoices::Table, Invoices::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(Invoices::AuctionId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-invoices-auction_id")
+                            .from(Invoices::Table, Invoices::AuctionId)
+                            .to(Auctions::Table, Auctions::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(
+                        ColumnDef::new(Invoices::Status)
+                            .enumeration(InvoiceStatus::Enum, [InvoiceStatus::Issued, InvoiceStatus::Paid, InvoiceStatus::Void])
+                            .not_null()
+                            .default("issued")
+                    )
+                    .col(ColumnDef::new(Invoices::Subtotal).decimal_len(12, 2).not_null())
+                    .col(ColumnDef::new(Invoices::FeesTotal).decimal_len(12, 2).not_null())
+                    .col(ColumnDef::new(Invoices::TaxTotal).decimal_len(12, 2).not_null())
+                    .col(ColumnDef::new(Invoices::Total).decimal_len(12, 2).not_null())
+                    .col(ColumnDef::new(Invoices::IssuedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(Invoices::PaidAt).timestamp())
+                    .col(ColumnDef::new(Invoices::VoidedAt).timestamp())
+                    .col(ColumnDef::new(Invoices::DeletedAt).timestamp())
+                    .col(ColumnDef::new(Invoices::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(Invoices::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(InvoiceLines::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(InvoiceLines::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(InvoiceLines::InvoiceId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-invoice_lines-invoice_id")
+                            .from(InvoiceLines::Table, InvoiceLines::InvoiceId)
+                            .to(Invoices::Table, Invoices::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(InvoiceLines::AuctionResultId).integer())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-invoice_lines-auction_result_id")
+                            .from(InvoiceLines::Table, InvoiceLines::AuctionResultId)
+                            .to(AuctionResults::Table, AuctionResults::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                    )
+                    .col(ColumnDef::new(InvoiceLines::Description).string().not_null())
+                    .col(ColumnDef::new(InvoiceLines::HammerPrice).decimal_len(10, 2).not_null())
+                    .col(ColumnDef::new(InvoiceLines::Fees).decimal_len(10, 2).not_null())
+                    .col(ColumnDef::new(InvoiceLines::Tax).decimal_len(10, 2).not_null())
+                    .col(ColumnDef::new(InvoiceLines::Total).decimal_len(10, 2).not_null())
+                    .col(ColumnDef::new(InvoiceLines::DeletedAt).timestamp())
+                    .col(ColumnDef::new(InvoiceLines::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(InvoiceLines::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(InvoiceLines::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Invoices::Table).to_owned())
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared("DROP SEQUENCE IF EXISTS invoice_number_seq")
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(InvoiceStatus::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Invoices {
+    Table,
+    Id,
+    InvoiceNumber,
+    UserId,
+    AuctionId,
+    Status,
+    Subtotal,
+    FeesTotal,
+    TaxTotal,
+    Total,
+    IssuedAt,
+    PaidAt,
+    VoidedAt,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum InvoiceLines {
+    Table,
+    Id,
+    InvoiceId,
+    AuctionResultId,
+    Description,
+    HammerPrice,
+    Fees,
+    Tax,
+    Total,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum InvoiceStatus {
+    #[sea_orm(iden = "invoice_status")]
+    Enum,
+    Issued,
+    Paid,
+    Void,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Auctions {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum AuctionResults {
+    Table,
+    Id,
+}
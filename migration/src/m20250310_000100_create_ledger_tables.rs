@@ -0,0 +1,237 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(LedgerAccountType::Enum)
+                    .values([
+                        LedgerAccountType::Asset,
+                        LedgerAccountType::Liability,
+                        LedgerAccountType::Equity,
+                        LedgerAccountType::Revenue,
+                        LedgerAccountType::Expense,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(LedgerAccounts::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LedgerAccounts::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LedgerAccounts::Code).string().not_null().unique_key())
+                    .col(ColumnDef::new(LedgerAccounts::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(LedgerAccounts::AccountType)
+                            .enumeration(LedgerAccountType::Enum, [
+                                LedgerAccountType::Asset,
+                                LedgerAccountType::Liability,
+                                LedgerAccountType::Equity,
+                                LedgerAccountType::Revenue,
+                                LedgerAccountType::Expense,
+                            ])
+                            .not_null()
+                    )
+                    .col(ColumnDef::new(LedgerAccounts::UserId).integer())
+                    // Money history must survive user deletion, so user accounts are never cascaded away
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-ledger_accounts-user_id")
+                            .from(LedgerAccounts::Table, LedgerAccounts::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Restrict)
+                    )
+                    .col(ColumnDef::new(LedgerAccounts::DeletedAt).timestamp())
+                    .col(ColumnDef::new(LedgerAccounts::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(LedgerAccounts::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(JournalEntries::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(JournalEntries::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(JournalEntries::Reference).string().not_null())
+                    .col(ColumnDef::new(JournalEntries::Description).string().not_null())
+                    .col(ColumnDef::new(JournalEntries::PostedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(JournalEntries::DeletedAt).timestamp())
+                    .col(ColumnDef::new(JournalEntries::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(JournalEntries::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(LedgerPostings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LedgerPostings::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LedgerPostings::JournalEntryId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-ledger_postings-journal_entry_id")
+                            .from(LedgerPostings::Table, LedgerPostings::JournalEntryId)
+                            .to(JournalEntries::Table, JournalEntries::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(LedgerPostings::AccountId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-ledger_postings-account_id")
+                            .from(LedgerPostings::Table, LedgerPostings::AccountId)
+                            .to(LedgerAccounts::Table, LedgerAccounts::Id)
+                            .on_delete(ForeignKeyAction::Restrict)
+                    )
+                    .col(ColumnDef::new(LedgerPostings::Debit).decimal_len(12, 2).not_null().default(0))
+                    .col(ColumnDef::new(LedgerPostings::Credit).decimal_len(12, 2).not_null().default(0))
+                    .check(Expr::cust("debit >= 0 AND credit >= 0 AND (debit = 0) <> (credit = 0)"))
+                    .col(ColumnDef::new(LedgerPostings::DeletedAt).timestamp())
+                    .col(ColumnDef::new(LedgerPostings::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(LedgerPostings::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every journal entry must balance by the time its transaction commits
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                CREATE OR REPLACE FUNCTION check_journal_entry_balanced() RETURNS trigger AS $$
+                DECLARE
+                    entry_id integer := COALESCE(NEW.journal_entry_id, OLD.journal_entry_id);
+                    total_debit numeric;
+                    total_credit numeric;
+                BEGIN
+                    SELECT COALESCE(SUM(debit), 0), COALESCE(SUM(credit), 0)
+                        INTO total_debit, total_credit
+                        FROM ledger_postings
+                        WHERE journal_entry_id = entry_id;
+
+                    IF total_debit <> total_credit THEN
+                        RAISE EXCEPTION 'Journal entry % is not balanced (debits %, credits %)', entry_id, total_debit, total_credit;
+                    END IF;
+
+                    RETURN NULL;
+                END;
+                $$ LANGUAGE plpgsql;
+
+                CREATE CONSTRAINT TRIGGER ledger_postings_balanced
+                    AFTER INSERT OR UPDATE OR DELETE ON ledger_postings
+                    DEFERRABLE INITIALLY DEFERRED
+                    FOR EACH ROW EXECUTE FUNCTION check_journal_entry_balanced();
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LedgerPostings::Table).to_owned())
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared("DROP FUNCTION IF EXISTS check_journal_entry_balanced()")
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(JournalEntries::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(LedgerAccounts::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(LedgerAccountType::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LedgerAccounts {
+    Table,
+    Id,
+    Code,
+    Name,
+    AccountType,
+    UserId,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum JournalEntries {
+    Table,
+    Id,
+    Reference,
+    Description,
+    PostedAt,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum LedgerPostings {
+    Table,
+    Id,
+    JournalEntryId,
+    AccountId,
+    Debit,
+    Credit,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum LedgerAccountType {
+    #[sea_orm(iden = "ledger_account_type")]
+    Enum,
+    Asset,
+    Liability,
+    Equity,
+    Revenue,
+    Expense,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,141 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .add_column(ColumnDef::new(Auctions::DepositAmount).decimal_len(10, 2))
+                    .add_column(ColumnDef::new(Auctions::RequiresCreditApproval).boolean().not_null().default(false))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(DepositStatus::Enum)
+                    .values([DepositStatus::Held, DepositStatus::Released, DepositStatus::Applied])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(BidderLimits::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(BidderLimits::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(BidderLimits::AuctionId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-bidder_limits-auction_id")
+                            .from(BidderLimits::Table, BidderLimits::AuctionId)
+                            .to(Auctions::Table, Auctions::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(BidderLimits::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-bidder_limits-user_id")
+                            .from(BidderLimits::Table, BidderLimits::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(BidderLimits::DepositAmount).decimal_len(12, 2).not_null().default(0))
+                    .col(
+                        ColumnDef::new(BidderLimits::DepositStatus)
+                            .enumeration(DepositStatus::Enum, [
+                                DepositStatus::Held,
+                                DepositStatus::Released,
+                                DepositStatus::Applied,
+                            ])
+                    )
+                    .col(ColumnDef::new(BidderLimits::CreditLimit).decimal_len(12, 2))
+                    .col(ColumnDef::new(BidderLimits::ApprovedAt).timestamp())
+                    .col(ColumnDef::new(BidderLimits::SettledAt).timestamp())
+                    .col(ColumnDef::new(BidderLimits::DeletedAt).timestamp())
+                    .col(ColumnDef::new(BidderLimits::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(BidderLimits::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .index(
+                        Index::create()
+                            .name("idx-bidder_limits-auction_id-user_id")
+                            .col(BidderLimits::AuctionId)
+                            .col(BidderLimits::UserId)
+                            .unique()
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BidderLimits::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(DepositStatus::Enum).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .drop_column(Auctions::DepositAmount)
+                    .drop_column(Auctions::RequiresCreditApproval)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BidderLimits {
+    Table,
+    Id,
+    AuctionId,
+    UserId,
+    DepositAmount,
+    DepositStatus,
+    CreditLimit,
+    ApprovedAt,
+    SettledAt,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum DepositStatus {
+    #[sea_orm(iden = "deposit_status")]
+    Enum,
+    Held,
+    Released,
+    Applied,
+}
+
+#[derive(DeriveIden)]
+enum Auctions {
+    Table,
+    Id,
+    DepositAmount,
+    RequiresCreditApproval,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Listings::Table)
+                    .add_column(ColumnDef::new(Listings::SellerId).integer())
+                    // Consignors are owed the proceeds of their lots, so they cannot be deleted out from under them
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-listings-seller_id")
+                            .from_tbl(Listings::Table)
+                            .from_col(Listings::SellerId)
+                            .to_tbl(Users::Table)
+                            .to_col(Users::Id)
+                            .on_delete(ForeignKeyAction::Restrict)
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Listings::Table)
+                    .drop_foreign_key(Alias::new("fk-listings-seller_id"))
+                    .drop_column(Listings::SellerId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Listings {
+    Table,
+    SellerId,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
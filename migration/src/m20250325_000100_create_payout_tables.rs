@@ -0,0 +1,235 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(PayoutStatus::Enum)
+                    .values([
+                        PayoutStatus::Pending,
+                        PayoutStatus::Approved,
+                        PayoutStatus::Sent,
+                        PayoutStatus::Failed,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PayoutBatches::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PayoutBatches::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PayoutBatches::Status)
+                            .enumeration(PayoutStatus::Enum, [
+                                PayoutStatus::Pending,
+                                PayoutStatus::Approved,
+                                PayoutStatus::Sent,
+                                PayoutStatus::Failed,
+                            ])
+                            .not_null()
+                            .default("pending")
+                    )
+                    .col(ColumnDef::new(PayoutBatches::Total).decimal_len(12, 2).not_null())
+                    .col(ColumnDef::new(PayoutBatches::ApprovedAt).timestamp())
+                    .col(ColumnDef::new(PayoutBatches::SentAt).timestamp())
+                    .col(ColumnDef::new(PayoutBatches::DeletedAt).timestamp())
+                    .col(ColumnDef::new(PayoutBatches::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(PayoutBatches::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Payouts::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Payouts::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Payouts::PayoutBatchId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-payouts-payout_batch_id")
+                            .from(Payouts::Table, Payouts::PayoutBatchId)
+                            .to(PayoutBatches::Table, PayoutBatches::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(Payouts::SellerId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-payouts-seller_id")
+                            .from(Payouts::Table, Payouts::SellerId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Restrict)
+                    )
+                    .col(
+                        ColumnDef::new(Payouts::Status)
+                            .enumeration(PayoutStatus::Enum, [
+                                PayoutStatus::Pending,
+                                PayoutStatus::Approved,
+                                PayoutStatus::Sent,
+                                PayoutStatus::Failed,
+                            ])
+                            .not_null()
+                            .default("pending")
+                    )
+                    .col(ColumnDef::new(Payouts::HammerTotal).decimal_len(12, 2).not_null())
+                    .col(ColumnDef::new(Payouts::CommissionTotal).decimal_len(12, 2).not_null())
+                    .col(ColumnDef::new(Payouts::TaxTotal).decimal_len(12, 2).not_null())
+                    .col(ColumnDef::new(Payouts::Amount).decimal_len(12, 2).not_null())
+                    .col(ColumnDef::new(Payouts::ProviderReference).string())
+                    .col(ColumnDef::new(Payouts::FailureReason).text())
+                    .col(ColumnDef::new(Payouts::SentAt).timestamp())
+                    .col(ColumnDef::new(Payouts::DeletedAt).timestamp())
+                    .col(ColumnDef::new(Payouts::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(Payouts::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PayoutItems::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PayoutItems::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PayoutItems::PayoutId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-payout_items-payout_id")
+                            .from(PayoutItems::Table, PayoutItems::PayoutId)
+                            .to(Payouts::Table, Payouts::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(PayoutItems::AuctionResultId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-payout_items-auction_result_id")
+                            .from(PayoutItems::Table, PayoutItems::AuctionResultId)
+                            .to(AuctionResults::Table, AuctionResults::Id)
+                            .on_delete(ForeignKeyAction::Restrict)
+                    )
+                    .col(ColumnDef::new(PayoutItems::HammerPrice).decimal_len(10, 2).not_null())
+                    .col(ColumnDef::new(PayoutItems::Commission).decimal_len(10, 2).not_null())
+                    .col(ColumnDef::new(PayoutItems::Tax).decimal_len(10, 2).not_null())
+                    .col(ColumnDef::new(PayoutItems::Net).decimal_len(10, 2).not_null())
+                    .col(ColumnDef::new(PayoutItems::DeletedAt).timestamp())
+                    .col(ColumnDef::new(PayoutItems::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(PayoutItems::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PayoutItems::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Payouts::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(PayoutBatches::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(PayoutStatus::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PayoutBatches {
+    Table,
+    Id,
+    Status,
+    Total,
+    ApprovedAt,
+    SentAt,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Payouts {
+    Table,
+    Id,
+    PayoutBatchId,
+    SellerId,
+    Status,
+    HammerTotal,
+    CommissionTotal,
+    TaxTotal,
+    Amount,
+    ProviderReference,
+    FailureReason,
+    SentAt,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum PayoutItems {
+    Table,
+    Id,
+    PayoutId,
+    AuctionResultId,
+    HammerPrice,
+    Commission,
+    Tax,
+    Net,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum PayoutStatus {
+    #[sea_orm(iden = "payout_status")]
+    Enum,
+    Pending,
+    Approved,
+    Sent,
+    Failed,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum AuctionResults {
+    Table,
+    Id,
+}
@@ -0,0 +1,134 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ClearingRule::Enum)
+                    .values([ClearingRule::Discriminatory, ClearingRule::Uniform])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Listings::Table)
+                    .add_column(
+                        ColumnDef::new(Listings::ClearingRule)
+                            .enumeration(ClearingRule::Enum, [ClearingRule::Discriminatory, ClearingRule::Uniform])
+                            .not_null()
+                            .default("discriminatory")
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bids::Table)
+                    .add_column(ColumnDef::new(Bids::Quantity).integer().not_null().default(1))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuctionResults::Table)
+                    .add_column(ColumnDef::new(AuctionResults::Quantity).integer().not_null().default(1))
+                    .add_column(ColumnDef::new(AuctionResults::UnitPrice).decimal_len(10, 2))
+                    .add_column(ColumnDef::new(AuctionResults::HammerPrice).decimal_len(12, 2))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every existing result sold the whole listing at the winning bid
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE auction_results SET unit_price = bids.amount, hammer_price = bids.amount \
+                 FROM bids WHERE bids.id = auction_results.winning_bid_id",
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuctionResults::Table)
+                    .modify_column(ColumnDef::new(AuctionResults::UnitPrice).decimal_len(10, 2).not_null())
+                    .modify_column(ColumnDef::new(AuctionResults::HammerPrice).decimal_len(12, 2).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuctionResults::Table)
+                    .drop_column(AuctionResults::Quantity)
+                    .drop_column(AuctionResults::UnitPrice)
+                    .drop_column(AuctionResults::HammerPrice)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bids::Table)
+                    .drop_column(Bids::Quantity)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Listings::Table)
+                    .drop_column(Listings::ClearingRule)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(ClearingRule::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Listings {
+    Table,
+    ClearingRule,
+}
+
+#[derive(DeriveIden)]
+enum Bids {
+    Table,
+    Quantity,
+}
+
+#[derive(DeriveIden)]
+enum AuctionResults {
+    Table,
+    Quantity,
+    UnitPrice,
+    HammerPrice,
+}
+
+#[derive(DeriveIden)]
+enum ClearingRule {
+    #[sea_orm(iden = "clearing_rule")]
+    Enum,
+    Discriminatory,
+    Uniform,
+}
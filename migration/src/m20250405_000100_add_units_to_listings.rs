@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Listings::Table)
+                    .add_column(ColumnDef::new(Listings::UnitOfMeasure).string().not_null().default("unit"))
+                    .add_column(ColumnDef::new(Listings::MinQuantity).integer().not_null().default(1))
+                    .add_column(ColumnDef::new(Listings::MaxQuantity).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InvoiceLines::Table)
+                    .add_column(ColumnDef::new(InvoiceLines::Quantity).integer().not_null().default(1))
+                    .add_column(ColumnDef::new(InvoiceLines::UnitPrice).decimal_len(10, 2))
+                    .add_column(ColumnDef::new(InvoiceLines::UnitOfMeasure).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Lines already issued take their quantity and price from the result they bill
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE invoice_lines SET quantity = auction_results.quantity, \
+                 unit_price = auction_results.unit_price, unit_of_measure = listings.unit_of_measure \
+                 FROM auction_results JOIN listings ON listings.id = auction_results.listing_id \
+                 WHERE auction_results.id = invoice_lines.auction_result_id",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InvoiceLines::Table)
+                    .drop_column(InvoiceLines::Quantity)
+                    .drop_column(InvoiceLines::UnitPrice)
+                    .drop_column(InvoiceLines::UnitOfMeasure)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Listings::Table)
+                    .drop_column(Listings::UnitOfMeasure)
+                    .drop_column(Listings::MinQuantity)
+                    .drop_column(Listings::MaxQuantity)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Listings {
+    Table,
+    UnitOfMeasure,
+    MinQuantity,
+    MaxQuantity,
+}
+
+#[derive(DeriveIden)]
+enum InvoiceLines {
+    Table,
+    Quantity,
+    UnitPrice,
+    UnitOfMeasure,
+}
@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(AuctionFormat::Enum)
+                    .values([
+                        AuctionFormat::Open,
+                        AuctionFormat::SealedFirstPrice,
+                        AuctionFormat::SealedSecondPrice,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .add_column(
+                        ColumnDef::new(Auctions::Format)
+                            .enumeration(AuctionFormat::Enum, [
+                                AuctionFormat::Open,
+                                AuctionFormat::SealedFirstPrice,
+                                AuctionFormat::SealedSecondPrice,
+                            ])
+                            .not_null()
+                            .default("open")
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .drop_column(Auctions::Format)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(AuctionFormat::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Auctions {
+    Table,
+    Format,
+}
+
+#[derive(DeriveIden)]
+enum AuctionFormat {
+    #[sea_orm(iden = "auction_format")]
+    Enum,
+    Open,
+    SealedFirstPrice,
+    SealedSecondPrice,
+}
@@ -0,0 +1,126 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Sealed auctions with a reveal deadline take commitments instead of bids
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .add_column(ColumnDef::new(Auctions::RevealEndTime).timestamp())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(BidCommitments::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(BidCommitments::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(BidCommitments::ListingId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-bid_commitments-listing_id")
+                            .from(BidCommitments::Table, BidCommitments::ListingId)
+                            .to(Listings::Table, Listings::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(BidCommitments::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-bid_commitments-user_id")
+                            .from(BidCommitments::Table, BidCommitments::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(BidCommitments::Commitment).string_len(64).not_null())
+                    .col(ColumnDef::new(BidCommitments::BidId).integer())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-bid_commitments-bid_id")
+                            .from(BidCommitments::Table, BidCommitments::BidId)
+                            .to(Bids::Table, Bids::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                    )
+                    .col(ColumnDef::new(BidCommitments::CommittedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(BidCommitments::RevealedAt).timestamp())
+                    .col(ColumnDef::new(BidCommitments::DeletedAt).timestamp())
+                    .col(ColumnDef::new(BidCommitments::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(BidCommitments::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .index(
+                        Index::create()
+                            .name("idx-bid_commitments-listing_id-user_id")
+                            .col(BidCommitments::ListingId)
+                            .col(BidCommitments::UserId)
+                            .unique()
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BidCommitments::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .drop_column(Auctions::RevealEndTime)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BidCommitments {
+    Table,
+    Id,
+    ListingId,
+    UserId,
+    Commitment,
+    BidId,
+    CommittedAt,
+    RevealedAt,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Auctions {
+    Table,
+    RevealEndTime,
+}
+
+#[derive(DeriveIden)]
+enum Listings {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Bids {
+    Table,
+    Id,
+}
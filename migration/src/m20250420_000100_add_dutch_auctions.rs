@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(AuctionFormat::Enum)
+                    .add_value(AuctionFormat::Dutch)
+                    .to_owned(),
+            )
+            .await?;
+
+        // The clock starts each listing at its start price and lowers it by
+        // the step every interval, never going below the base price
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Listings::Table)
+                    .add_column(ColumnDef::new(Listings::StartPrice).decimal_len(10, 2))
+                    .add_column(ColumnDef::new(Listings::PriceStep).decimal_len(10, 2))
+                    .add_column(ColumnDef::new(Listings::PriceStepSeconds).integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Listings::Table)
+                    .drop_column(Listings::StartPrice)
+                    .drop_column(Listings::PriceStep)
+                    .drop_column(Listings::PriceStepSeconds)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Postgres cannot drop a value from an enum, so the type is rebuilt without it
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE auctions SET format = 'open' WHERE format = 'dutch'; \
+                 ALTER TABLE auctions ALTER COLUMN format DROP DEFAULT; \
+                 ALTER TYPE auction_format RENAME TO auction_format_old; \
+                 CREATE TYPE auction_format AS ENUM ('open', 'sealed_first_price', 'sealed_second_price'); \
+                 ALTER TABLE auctions ALTER COLUMN format TYPE auction_format USING format::text::auction_format; \
+                 ALTER TABLE auctions ALTER COLUMN format SET DEFAULT 'open'; \
+                 DROP TYPE auction_format_old",
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Listings {
+    Table,
+    StartPrice,
+    PriceStep,
+    PriceStepSeconds,
+}
+
+#[derive(DeriveIden)]
+enum AuctionFormat {
+    #[sea_orm(iden = "auction_format")]
+    Enum,
+    Dutch,
+}
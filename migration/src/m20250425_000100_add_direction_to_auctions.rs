@@ -0,0 +1,64 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(AuctionDirection::Enum)
+                    .values([AuctionDirection::Forward, AuctionDirection::Reverse])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .add_column(
+                        ColumnDef::new(Auctions::Direction)
+                            .enumeration(AuctionDirection::Enum, [AuctionDirection::Forward, AuctionDirection::Reverse])
+                            .not_null()
+                            .default("forward")
+                    )
+                    .add_column(ColumnDef::new(Auctions::BidIncrement).decimal_len(10, 2))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .drop_column(Auctions::Direction)
+                    .drop_column(Auctions::BidIncrement)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(AuctionDirection::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Auctions {
+    Table,
+    Direction,
+    BidIncrement,
+}
+
+#[derive(DeriveIden)]
+enum AuctionDirection {
+    #[sea_orm(iden = "auction_direction")]
+    Enum,
+    Forward,
+    Reverse,
+}
@@ -0,0 +1,259 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Double auctions trade their listings continuously through an order book
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(AuctionFormat::Enum)
+                    .add_value(AuctionFormat::DoubleAuction)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(OrderSide::Enum)
+                    .values([OrderSide::Buy, OrderSide::Sell])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(OrderStatus::Enum)
+                    .values([
+                        OrderStatus::Open,
+                        OrderStatus::PartiallyFilled,
+                        OrderStatus::Filled,
+                        OrderStatus::Cancelled,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Orders::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Orders::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Orders::ListingId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-orders-listing_id")
+                            .from(Orders::Table, Orders::ListingId)
+                            .to(Listings::Table, Listings::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(Orders::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-orders-user_id")
+                            .from(Orders::Table, Orders::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(
+                        ColumnDef::new(Orders::Side)
+                            .enumeration(OrderSide::Enum, [OrderSide::Buy, OrderSide::Sell])
+                            .not_null()
+                    )
+                    .col(ColumnDef::new(Orders::Price).decimal_len(10, 2).not_null())
+                    .col(ColumnDef::new(Orders::Quantity).integer().not_null())
+                    .col(ColumnDef::new(Orders::FilledQuantity).integer().not_null().default(0))
+                    .col(
+                        ColumnDef::new(Orders::Status)
+                            .enumeration(OrderStatus::Enum, [
+                                OrderStatus::Open,
+                                OrderStatus::PartiallyFilled,
+                                OrderStatus::Filled,
+                                OrderStatus::Cancelled,
+                            ])
+                            .not_null()
+                            .default("open")
+                    )
+                    .col(ColumnDef::new(Orders::CancelledAt).timestamp())
+                    .col(ColumnDef::new(Orders::DeletedAt).timestamp())
+                    .col(ColumnDef::new(Orders::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(Orders::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-orders-listing_id-status")
+                    .table(Orders::Table)
+                    .col(Orders::ListingId)
+                    .col(Orders::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Trades::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Trades::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Trades::ListingId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-trades-listing_id")
+                            .from(Trades::Table, Trades::ListingId)
+                            .to(Listings::Table, Listings::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(Trades::BuyOrderId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-trades-buy_order_id")
+                            .from(Trades::Table, Trades::BuyOrderId)
+                            .to(Orders::Table, Orders::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(Trades::SellOrderId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-trades-sell_order_id")
+                            .from(Trades::Table, Trades::SellOrderId)
+                            .to(Orders::Table, Orders::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(Trades::BuyerId).integer().not_null())
+                    .col(ColumnDef::new(Trades::SellerId).integer().not_null())
+                    .col(ColumnDef::new(Trades::Price).decimal_len(10, 2).not_null())
+                    .col(ColumnDef::new(Trades::Quantity).integer().not_null())
+                    .col(ColumnDef::new(Trades::DeletedAt).timestamp())
+                    .col(ColumnDef::new(Trades::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(Trades::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Trades::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Orders::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(OrderStatus::Enum).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(OrderSide::Enum).to_owned())
+            .await?;
+
+        // Postgres cannot drop a value from an enum, so the type is rebuilt without it
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE auctions SET format = 'open' WHERE format = 'double_auction'; \
+                 ALTER TABLE auctions ALTER COLUMN format DROP DEFAULT; \
+                 ALTER TYPE auction_format RENAME TO auction_format_old; \
+                 CREATE TYPE auction_format AS ENUM ('open', 'sealed_first_price', 'sealed_second_price', 'dutch'); \
+                 ALTER TABLE auctions ALTER COLUMN format TYPE auction_format USING format::text::auction_format; \
+                 ALTER TABLE auctions ALTER COLUMN format SET DEFAULT 'open'; \
+                 DROP TYPE auction_format_old",
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Orders {
+    Table,
+    Id,
+    ListingId,
+    UserId,
+    Side,
+    Price,
+    Quantity,
+    FilledQuantity,
+    Status,
+    CancelledAt,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Trades {
+    Table,
+    Id,
+    ListingId,
+    BuyOrderId,
+    SellOrderId,
+    BuyerId,
+    SellerId,
+    Price,
+    Quantity,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Listings {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum AuctionFormat {
+    #[sea_orm(iden = "auction_format")]
+    Enum,
+    DoubleAuction,
+}
+
+#[derive(DeriveIden)]
+enum OrderSide {
+    #[sea_orm(iden = "order_side")]
+    Enum,
+    Buy,
+    Sell,
+}
+
+#[derive(DeriveIden)]
+enum OrderStatus {
+    #[sea_orm(iden = "order_status")]
+    Enum,
+    Open,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+}
@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Listings::Table)
+                    .add_column(ColumnDef::new(Listings::BuyNowPrice).decimal_len(10, 2))
+                    .add_column(ColumnDef::new(Listings::BuyNowThreshold).decimal_len(10, 2))
+                    .add_column(ColumnDef::new(Listings::ClosedAt).timestamp())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Listings::Table)
+                    .drop_column(Listings::BuyNowPrice)
+                    .drop_column(Listings::BuyNowThreshold)
+                    .drop_column(Listings::ClosedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Listings {
+    Table,
+    BuyNowPrice,
+    BuyNowThreshold,
+    ClosedAt,
+}
@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Candle auctions close at a random moment between the end time and
+        // the candle end time, drawn from a seed whose hash is published first
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .add_column(ColumnDef::new(Auctions::CandleEndTime).timestamp())
+                    .add_column(ColumnDef::new(Auctions::CandleSeed).string_len(64))
+                    .add_column(ColumnDef::new(Auctions::CandleSeedHash).string_len(64))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .drop_column(Auctions::CandleEndTime)
+                    .drop_column(Auctions::CandleSeed)
+                    .drop_column(Auctions::CandleSeedHash)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Auctions {
+    Table,
+    CandleEndTime,
+    CandleSeed,
+    CandleSeedHash,
+}
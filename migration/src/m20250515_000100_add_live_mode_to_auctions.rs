@@ -0,0 +1,64 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Live auctions are run lot by lot from the auctioneer's console
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .add_column(ColumnDef::new(Auctions::Live).boolean().not_null().default(false))
+                    .to_owned(),
+            )
+            .await?;
+
+        // A lot is on the block from when it is opened until it is hammered
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Listings::Table)
+                    .add_column(ColumnDef::new(Listings::LotNumber).integer())
+                    .add_column(ColumnDef::new(Listings::OpenedAt).timestamp())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Listings::Table)
+                    .drop_column(Listings::LotNumber)
+                    .drop_column(Listings::OpenedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .drop_column(Auctions::Live)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Auctions {
+    Table,
+    Live,
+}
+
+#[derive(DeriveIden)]
+enum Listings {
+    Table,
+    LotNumber,
+    OpenedAt,
+}
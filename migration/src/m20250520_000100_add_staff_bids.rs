@@ -0,0 +1,125 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(UserRole::Enum)
+                    .values([UserRole::Bidder, UserRole::Staff, UserRole::Admin])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(
+                        ColumnDef::new(Users::Role)
+                            .enumeration(UserRole::Enum, [UserRole::Bidder, UserRole::Staff, UserRole::Admin])
+                            .not_null()
+                            .default("bidder")
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(BidChannel::Enum)
+                    .values([BidChannel::Online, BidChannel::Absentee, BidChannel::Phone, BidChannel::Floor])
+                    .to_owned(),
+            )
+            .await?;
+
+        // Bids staff place for someone else record who placed them and how
+        // the bidder reached them
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bids::Table)
+                    .add_column(
+                        ColumnDef::new(Bids::Channel)
+                            .enumeration(BidChannel::Enum, [BidChannel::Online, BidChannel::Absentee, BidChannel::Phone, BidChannel::Floor])
+                            .not_null()
+                            .default("online")
+                    )
+                    .add_column(ColumnDef::new(Bids::PlacedById).integer())
+                    .add_column(ColumnDef::new(Bids::Note).text())
+                    .add_column(ColumnDef::new(Bids::MaxAmount).decimal_len(10, 2))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bids::Table)
+                    .drop_column(Bids::Channel)
+                    .drop_column(Bids::PlacedById)
+                    .drop_column(Bids::Note)
+                    .drop_column(Bids::MaxAmount)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(BidChannel::Enum).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::Role)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(UserRole::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Role,
+}
+
+#[derive(DeriveIden)]
+enum Bids {
+    Table,
+    Channel,
+    PlacedById,
+    Note,
+    MaxAmount,
+}
+
+#[derive(DeriveIden)]
+enum UserRole {
+    #[sea_orm(iden = "user_role")]
+    Enum,
+    Bidder,
+    Staff,
+    Admin,
+}
+
+#[derive(DeriveIden)]
+enum BidChannel {
+    #[sea_orm(iden = "bid_channel")]
+    Enum,
+    Online,
+    Absentee,
+    Phone,
+    Floor,
+}
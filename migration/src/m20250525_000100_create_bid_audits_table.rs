@@ -0,0 +1,113 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(BidAuditAction::Enum)
+                    .values([BidAuditAction::Retracted, BidAuditAction::Voided])
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every bid taken back, by its bidder or by an admin, leaves a record
+        manager
+            .create_table(
+                Table::create()
+                    .table(BidAudits::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(BidAudits::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(BidAudits::BidId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-bid_audits-bid_id")
+                            .from(BidAudits::Table, BidAudits::BidId)
+                            .to(Bids::Table, Bids::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(
+                        ColumnDef::new(BidAudits::Action)
+                            .enumeration(BidAuditAction::Enum, [BidAuditAction::Retracted, BidAuditAction::Voided])
+                            .not_null()
+                    )
+                    .col(ColumnDef::new(BidAudits::ActorId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-bid_audits-actor_id")
+                            .from(BidAudits::Table, BidAudits::ActorId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(BidAudits::Reason).text())
+                    .col(ColumnDef::new(BidAudits::DeletedAt).timestamp())
+                    .col(ColumnDef::new(BidAudits::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(BidAudits::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-bid_audits-bid_id")
+                    .table(BidAudits::Table)
+                    .col(BidAudits::BidId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BidAudits::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(BidAuditAction::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BidAudits {
+    Table,
+    Id,
+    BidId,
+    Action,
+    ActorId,
+    Reason,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum BidAuditAction {
+    #[sea_orm(iden = "bid_audit_action")]
+    Enum,
+    Retracted,
+    Voided,
+}
+
+#[derive(DeriveIden)]
+enum Bids {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,148 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Registration-required auctions only take bids from approved bidders
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .add_column(ColumnDef::new(Auctions::RequiresRegistration).boolean().not_null().default(false))
+                    .add_column(ColumnDef::new(Auctions::Terms).text())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(RegistrationStatus::Enum)
+                    .values([RegistrationStatus::Pending, RegistrationStatus::Approved, RegistrationStatus::Rejected])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuctionRegistrations::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuctionRegistrations::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AuctionRegistrations::AuctionId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-auction_registrations-auction_id")
+                            .from(AuctionRegistrations::Table, AuctionRegistrations::AuctionId)
+                            .to(Auctions::Table, Auctions::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(AuctionRegistrations::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-auction_registrations-user_id")
+                            .from(AuctionRegistrations::Table, AuctionRegistrations::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(
+                        ColumnDef::new(AuctionRegistrations::Status)
+                            .enumeration(RegistrationStatus::Enum, [RegistrationStatus::Pending, RegistrationStatus::Approved, RegistrationStatus::Rejected])
+                            .not_null()
+                            .default("pending")
+                    )
+                    // Which version of the terms the bidder accepted
+                    .col(ColumnDef::new(AuctionRegistrations::TermsHash).string_len(64))
+                    .col(ColumnDef::new(AuctionRegistrations::TermsAcceptedAt).timestamp().not_null())
+                    .col(ColumnDef::new(AuctionRegistrations::ReviewedById).integer())
+                    .col(ColumnDef::new(AuctionRegistrations::ReviewedAt).timestamp())
+                    .col(ColumnDef::new(AuctionRegistrations::ReviewNote).text())
+                    .col(ColumnDef::new(AuctionRegistrations::DeletedAt).timestamp())
+                    .col(ColumnDef::new(AuctionRegistrations::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(AuctionRegistrations::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-auction_registrations-auction_id-user_id")
+                    .table(AuctionRegistrations::Table)
+                    .col(AuctionRegistrations::AuctionId)
+                    .col(AuctionRegistrations::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuctionRegistrations::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(RegistrationStatus::Enum).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .drop_column(Auctions::RequiresRegistration)
+                    .drop_column(Auctions::Terms)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuctionRegistrations {
+    Table,
+    Id,
+    AuctionId,
+    UserId,
+    Status,
+    TermsHash,
+    TermsAcceptedAt,
+    ReviewedById,
+    ReviewedAt,
+    ReviewNote,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum RegistrationStatus {
+    #[sea_orm(iden = "registration_status")]
+    Enum,
+    Pending,
+    Approved,
+    Rejected,
+}
+
+#[derive(DeriveIden)]
+enum Auctions {
+    Table,
+    Id,
+    RequiresRegistration,
+    Terms,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,310 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(KycStatus::Enum)
+                    .values([KycStatus::Unverified, KycStatus::Pending, KycStatus::Verified, KycStatus::Rejected])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(VerificationLevel::Enum)
+                    .values([VerificationLevel::None, VerificationLevel::Basic, VerificationLevel::Enhanced])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(KycDocumentType::Enum)
+                    .values([
+                        KycDocumentType::NationalId,
+                        KycDocumentType::Passport,
+                        KycDocumentType::DrivingLicence,
+                        KycDocumentType::ProofOfAddress,
+                        KycDocumentType::Selfie,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(ColumnDef::new(Users::NationalIdNumber).string_len(32).unique_key())
+                    .add_column(
+                        ColumnDef::new(Users::KycStatus)
+                            .enumeration(KycStatus::Enum, [KycStatus::Unverified, KycStatus::Pending, KycStatus::Verified, KycStatus::Rejected])
+                            .not_null()
+                            .default("unverified")
+                    )
+                    .add_column(
+                        ColumnDef::new(Users::VerificationLevel)
+                            .enumeration(VerificationLevel::Enum, [VerificationLevel::None, VerificationLevel::Basic, VerificationLevel::Enhanced])
+                            .not_null()
+                            .default("none")
+                    )
+                    .add_column(ColumnDef::new(Users::KycSubmittedAt).timestamp())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Regulated auctions and lots only take bids from verified bidders; a
+        // listing can ask for more than its auction but never less
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .add_column(
+                        ColumnDef::new(Auctions::MinVerificationLevel)
+                            .enumeration(VerificationLevel::Enum, [VerificationLevel::None, VerificationLevel::Basic, VerificationLevel::Enhanced])
+                            .not_null()
+                            .default("none")
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Listings::Table)
+                    .add_column(
+                        ColumnDef::new(Listings::MinVerificationLevel)
+                            .enumeration(VerificationLevel::Enum, [VerificationLevel::None, VerificationLevel::Basic, VerificationLevel::Enhanced])
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(KycDocuments::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(KycDocuments::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(KycDocuments::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-kyc_documents-user_id")
+                            .from(KycDocuments::Table, KycDocuments::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(
+                        ColumnDef::new(KycDocuments::DocumentType)
+                            .enumeration(
+                                KycDocumentType::Enum,
+                                [
+                                    KycDocumentType::NationalId,
+                                    KycDocumentType::Passport,
+                                    KycDocumentType::DrivingLicence,
+                                    KycDocumentType::ProofOfAddress,
+                                    KycDocumentType::Selfie,
+                                ],
+                            )
+                            .not_null()
+                    )
+                    .col(ColumnDef::new(KycDocuments::FileName).string().not_null())
+                    .col(ColumnDef::new(KycDocuments::ContentType).string().not_null())
+                    .col(ColumnDef::new(KycDocuments::StoragePath).string().not_null())
+                    .col(ColumnDef::new(KycDocuments::SizeBytes).big_integer().not_null())
+                    .col(ColumnDef::new(KycDocuments::Sha256).string_len(64).not_null())
+                    .col(ColumnDef::new(KycDocuments::DeletedAt).timestamp())
+                    .col(ColumnDef::new(KycDocuments::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(KycDocuments::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every review decision is kept with the reviewer's notes
+        manager
+            .create_table(
+                Table::create()
+                    .table(KycReviews::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(KycReviews::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(KycReviews::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-kyc_reviews-user_id")
+                            .from(KycReviews::Table, KycReviews::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(KycReviews::ReviewerId).integer().not_null())
+                    .col(
+                        ColumnDef::new(KycReviews::Status)
+                            .enumeration(KycStatus::Enum, [KycStatus::Unverified, KycStatus::Pending, KycStatus::Verified, KycStatus::Rejected])
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(KycReviews::VerificationLevel)
+                            .enumeration(VerificationLevel::Enum, [VerificationLevel::None, VerificationLevel::Basic, VerificationLevel::Enhanced])
+                            .not_null()
+                    )
+                    .col(ColumnDef::new(KycReviews::Note).text())
+                    .col(ColumnDef::new(KycReviews::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(KycReviews::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(KycReviews::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(KycDocuments::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Listings::Table)
+                    .drop_column(Listings::MinVerificationLevel)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Auctions::Table)
+                    .drop_column(Auctions::MinVerificationLevel)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::NationalIdNumber)
+                    .drop_column(Users::KycStatus)
+                    .drop_column(Users::VerificationLevel)
+                    .drop_column(Users::KycSubmittedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(KycDocumentType::Enum).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(VerificationLevel::Enum).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(KycStatus::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum KycStatus {
+    #[sea_orm(iden = "kyc_status")]
+    Enum,
+    Unverified,
+    Pending,
+    Verified,
+    Rejected,
+}
+
+#[derive(DeriveIden)]
+enum VerificationLevel {
+    #[sea_orm(iden = "verification_level")]
+    Enum,
+    None,
+    Basic,
+    Enhanced,
+}
+
+#[derive(DeriveIden)]
+enum KycDocumentType {
+    #[sea_orm(iden = "kyc_document_type")]
+    Enum,
+    NationalId,
+    Passport,
+    DrivingLicence,
+    ProofOfAddress,
+    Selfie,
+}
+
+#[derive(DeriveIden)]
+enum KycDocuments {
+    Table,
+    Id,
+    UserId,
+    DocumentType,
+    FileName,
+    ContentType,
+    StoragePath,
+    SizeBytes,
+    Sha256,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum KycReviews {
+    Table,
+    Id,
+    UserId,
+    ReviewerId,
+    Status,
+    VerificationLevel,
+    Note,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+    NationalIdNumber,
+    KycStatus,
+    VerificationLevel,
+    KycSubmittedAt,
+}
+
+#[derive(DeriveIden)]
+enum Auctions {
+    Table,
+    MinVerificationLevel,
+}
+
+#[derive(DeriveIden)]
+enum Listings {
+    Table,
+    MinVerificationLevel,
+}
@@ -0,0 +1,212 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(PreferredLanguage::Enum)
+                    .values([PreferredLanguage::En, PreferredLanguage::Sw])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(NotificationChannel::Enum)
+                    .values([NotificationChannel::Whatsapp, NotificationChannel::Email])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(NotificationCategory::Enum)
+                    .values([NotificationCategory::Account, NotificationCategory::Auction, NotificationCategory::Marketing])
+                    .to_owned(),
+            )
+            .await?;
+
+        // Quiet hours are local times; the offset turns them into UTC
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(ColumnDef::new(Users::Email).string().unique_key())
+                    .add_column(ColumnDef::new(Users::County).string())
+                    .add_column(ColumnDef::new(Users::Location).string())
+                    .add_column(
+                        ColumnDef::new(Users::PreferredLanguage)
+                            .enumeration(PreferredLanguage::Enum, [PreferredLanguage::En, PreferredLanguage::Sw])
+                            .not_null()
+                            .default("en")
+                    )
+                    .add_column(ColumnDef::new(Users::NotifyWhatsapp).boolean().not_null().default(true))
+                    .add_column(ColumnDef::new(Users::NotifyEmail).boolean().not_null().default(false))
+                    .add_column(ColumnDef::new(Users::QuietHoursStart).time())
+                    .add_column(ColumnDef::new(Users::QuietHoursEnd).time())
+                    .add_column(ColumnDef::new(Users::UtcOffsetMinutes).integer().not_null().default(180))
+                    .add_column(ColumnDef::new(Users::MarketingConsent).boolean().not_null().default(false))
+                    .add_column(ColumnDef::new(Users::MarketingConsentAt).timestamp())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Messages wait here until they are delivered, so messages held back
+        // by quiet hours and failed deliveries are sent later
+        manager
+            .create_table(
+                Table::create()
+                    .table(Notifications::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Notifications::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Notifications::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-notifications-user_id")
+                            .from(Notifications::Table, Notifications::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(
+                        ColumnDef::new(Notifications::Channel)
+                            .enumeration(NotificationChannel::Enum, [NotificationChannel::Whatsapp, NotificationChannel::Email])
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(Notifications::Category)
+                            .enumeration(NotificationCategory::Enum, [NotificationCategory::Account, NotificationCategory::Auction, NotificationCategory::Marketing])
+                            .not_null()
+                    )
+                    .col(ColumnDef::new(Notifications::Subject).string().not_null())
+                    .col(ColumnDef::new(Notifications::Body).text().not_null())
+                    .col(ColumnDef::new(Notifications::SendAfter).timestamp().not_null())
+                    .col(ColumnDef::new(Notifications::SentAt).timestamp())
+                    .col(ColumnDef::new(Notifications::Attempts).integer().not_null().default(0))
+                    .col(ColumnDef::new(Notifications::LastError).text())
+                    .col(ColumnDef::new(Notifications::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(Notifications::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-notifications-sent_at-send_after")
+                    .table(Notifications::Table)
+                    .col(Notifications::SentAt)
+                    .col(Notifications::SendAfter)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Notifications::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::Email)
+                    .drop_column(Users::County)
+                    .drop_column(Users::Location)
+                    .drop_column(Users::PreferredLanguage)
+                    .drop_column(Users::NotifyWhatsapp)
+                    .drop_column(Users::NotifyEmail)
+                    .drop_column(Users::QuietHoursStart)
+                    .drop_column(Users::QuietHoursEnd)
+                    .drop_column(Users::UtcOffsetMinutes)
+                    .drop_column(Users::MarketingConsent)
+                    .drop_column(Users::MarketingConsentAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(NotificationCategory::Enum).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(NotificationChannel::Enum).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(PreferredLanguage::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PreferredLanguage {
+    #[sea_orm(iden = "preferred_language")]
+    Enum,
+    En,
+    Sw,
+}
+
+#[derive(DeriveIden)]
+enum NotificationChannel {
+    #[sea_orm(iden = "notification_channel")]
+    Enum,
+    Whatsapp,
+    Email,
+}
+
+#[derive(DeriveIden)]
+enum NotificationCategory {
+    #[sea_orm(iden = "notification_category")]
+    Enum,
+    Account,
+    Auction,
+    Marketing,
+}
+
+#[derive(DeriveIden)]
+enum Notifications {
+    Table,
+    Id,
+    UserId,
+    Channel,
+    Category,
+    Subject,
+    Body,
+    SendAfter,
+    SentAt,
+    Attempts,
+    LastError,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+    Email,
+    County,
+    Location,
+    PreferredLanguage,
+    NotifyWhatsapp,
+    NotifyEmail,
+    QuietHoursStart,
+    QuietHoursEnd,
+    UtcOffsetMinutes,
+    MarketingConsent,
+    MarketingConsentAt,
+}
@@ -0,0 +1,187 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(PhoneChangeStatus::Enum)
+                    .values([
+                        PhoneChangeStatus::AwaitingVerification,
+                        PhoneChangeStatus::CoolingOff,
+                        PhoneChangeStatus::Completed,
+                        PhoneChangeStatus::Cancelled,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        // One-time codes are stored only as keyed hashes
+        manager
+            .create_table(
+                Table::create()
+                    .table(PhoneNumberChanges::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PhoneNumberChanges::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PhoneNumberChanges::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-phone_number_changes-user_id")
+                            .from(PhoneNumberChanges::Table, PhoneNumberChanges::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(PhoneNumberChanges::OldPhoneNumber).string().not_null())
+                    .col(ColumnDef::new(PhoneNumberChanges::NewPhoneNumber).string().not_null())
+                    .col(
+                        ColumnDef::new(PhoneNumberChanges::Status)
+                            .enumeration(
+                                PhoneChangeStatus::Enum,
+                                [
+                                    PhoneChangeStatus::AwaitingVerification,
+                                    PhoneChangeStatus::CoolingOff,
+                                    PhoneChangeStatus::Completed,
+                                    PhoneChangeStatus::Cancelled,
+                                ],
+                            )
+                            .not_null()
+                            .default("awaiting_verification")
+                    )
+                    .col(ColumnDef::new(PhoneNumberChanges::OldCodeHash).string_len(64).not_null())
+                    .col(ColumnDef::new(PhoneNumberChanges::NewCodeHash).string_len(64).not_null())
+                    .col(ColumnDef::new(PhoneNumberChanges::CodesExpireAt).timestamp().not_null())
+                    .col(ColumnDef::new(PhoneNumberChanges::FailedAttempts).integer().not_null().default(0))
+                    .col(ColumnDef::new(PhoneNumberChanges::OldVerifiedAt).timestamp())
+                    .col(ColumnDef::new(PhoneNumberChanges::NewVerifiedAt).timestamp())
+                    .col(ColumnDef::new(PhoneNumberChanges::EffectiveAt).timestamp())
+                    .col(ColumnDef::new(PhoneNumberChanges::CompletedAt).timestamp())
+                    .col(ColumnDef::new(PhoneNumberChanges::CancelledAt).timestamp())
+                    .col(ColumnDef::new(PhoneNumberChanges::CancelReason).text())
+                    .col(ColumnDef::new(PhoneNumberChanges::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(PhoneNumberChanges::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-phone_number_changes-new_phone_number")
+                    .table(PhoneNumberChanges::Table)
+                    .col(PhoneNumberChanges::NewPhoneNumber)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Numbers a user has moved away from
+        manager
+            .create_table(
+                Table::create()
+                    .table(PhoneNumberHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PhoneNumberHistory::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PhoneNumberHistory::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-phone_number_history-user_id")
+                            .from(PhoneNumberHistory::Table, PhoneNumberHistory::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(PhoneNumberHistory::PhoneNumber).string().not_null())
+                    .col(ColumnDef::new(PhoneNumberHistory::ChangeId).integer())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-phone_number_history-change_id")
+                            .from(PhoneNumberHistory::Table, PhoneNumberHistory::ChangeId)
+                            .to(PhoneNumberChanges::Table, PhoneNumberChanges::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                    )
+                    .col(ColumnDef::new(PhoneNumberHistory::ValidUntil).timestamp().not_null())
+                    .col(ColumnDef::new(PhoneNumberHistory::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(PhoneNumberHistory::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PhoneNumberHistory::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(PhoneNumberChanges::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(PhoneChangeStatus::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PhoneChangeStatus {
+    #[sea_orm(iden = "phone_change_status")]
+    Enum,
+    AwaitingVerification,
+    CoolingOff,
+    Completed,
+    Cancelled,
+}
+
+#[derive(DeriveIden)]
+enum PhoneNumberChanges {
+    Table,
+    Id,
+    UserId,
+    OldPhoneNumber,
+    NewPhoneNumber,
+    Status,
+    OldCodeHash,
+    NewCodeHash,
+    CodesExpireAt,
+    FailedAttempts,
+    OldVerifiedAt,
+    NewVerifiedAt,
+    EffectiveAt,
+    CompletedAt,
+    CancelledAt,
+    CancelReason,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum PhoneNumberHistory {
+    Table,
+    Id,
+    UserId,
+    PhoneNumber,
+    ChangeId,
+    ValidUntil,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
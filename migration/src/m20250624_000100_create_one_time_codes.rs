@@ -0,0 +1,107 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(OneTimeCodePurpose::Enum)
+                    .values([OneTimeCodePurpose::SignIn])
+                    .to_owned(),
+            )
+            .await?;
+
+        // Codes sent to a number to prove it is held. A code for a number
+        // with no account yet carries the name to create it under.
+        manager
+            .create_table(
+                Table::create()
+                    .table(OneTimeCodes::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OneTimeCodes::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(OneTimeCodes::UserId).integer())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-one_time_codes-user_id")
+                            .from(OneTimeCodes::Table, OneTimeCodes::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .col(ColumnDef::new(OneTimeCodes::PhoneNumber).string().not_null())
+                    .col(
+                        ColumnDef::new(OneTimeCodes::Purpose)
+                            .enumeration(OneTimeCodePurpose::Enum, [OneTimeCodePurpose::SignIn])
+                            .not_null()
+                    )
+                    .col(ColumnDef::new(OneTimeCodes::Name).string())
+                    .col(ColumnDef::new(OneTimeCodes::CodeHash).string_len(64).not_null())
+                    .col(ColumnDef::new(OneTimeCodes::ExpiresAt).timestamp().not_null())
+                    .col(ColumnDef::new(OneTimeCodes::FailedAttempts).integer().not_null().default(0))
+                    .col(ColumnDef::new(OneTimeCodes::UsedAt).timestamp())
+                    .col(ColumnDef::new(OneTimeCodes::CreatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .col(ColumnDef::new(OneTimeCodes::UpdatedAt).timestamp().default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-one_time_codes-phone_number-purpose")
+                    .table(OneTimeCodes::Table)
+                    .col(OneTimeCodes::PhoneNumber)
+                    .col(OneTimeCodes::Purpose)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OneTimeCodes::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(OneTimeCodePurpose::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OneTimeCodePurpose {
+    #[sea_orm(iden = "one_time_code_purpose")]
+    Enum,
+    SignIn,
+}
+
+#[derive(DeriveIden)]
+enum OneTimeCodes {
+    Table,
+    Id,
+    UserId,
+    PhoneNumber,
+    Purpose,
+    Name,
+    CodeHash,
+    ExpiresAt,
+    FailedAttempts,
+    UsedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
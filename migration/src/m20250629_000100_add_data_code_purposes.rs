@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Exporting or erasing an account needs a code sent to its number
+        // on top of the session
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(OneTimeCodePurpose::Enum)
+                    .add_value(OneTimeCodePurpose::DataExport)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(OneTimeCodePurpose::Enum)
+                    .add_value(OneTimeCodePurpose::AccountErasure)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Postgres cannot drop a value from an enum, so the type is rebuilt without them
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "DELETE FROM one_time_codes WHERE purpose IN ('data_export', 'account_erasure'); \
+                 ALTER TYPE one_time_code_purpose RENAME TO one_time_code_purpose_old; \
+                 CREATE TYPE one_time_code_purpose AS ENUM ('sign_in'); \
+                 ALTER TABLE one_time_codes ALTER COLUMN purpose TYPE one_time_code_purpose USING purpose::text::one_time_code_purpose; \
+                 DROP TYPE one_time_code_purpose_old",
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum OneTimeCodePurpose {
+    #[sea_orm(iden = "one_time_code_purpose")]
+    Enum,
+    DataExport,
+    AccountErasure,
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Lines of a voided invoice are stamped too, so the result they
+        // billed can go on a new invoice
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InvoiceLines::Table)
+                    .add_column(ColumnDef::new(InvoiceLines::VoidedAt).timestamp())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Partial indexes are not supported by the index builder
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE invoice_lines SET voided_at = invoices.voided_at \
+                 FROM invoices WHERE invoices.id = invoice_lines.invoice_id AND invoices.status = 'void'; \
+                 CREATE UNIQUE INDEX \"idx-invoice_lines-auction_result_id\" ON invoice_lines (auction_result_id) \
+                 WHERE auction_result_id IS NOT NULL AND voided_at IS NULL",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-invoice_lines-auction_result_id")
+                    .table(InvoiceLines::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InvoiceLines::Table)
+                    .drop_column(InvoiceLines::VoidedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum InvoiceLines {
+    Table,
+    VoidedAt,
+}
@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PayoutBatches::Table)
+                    .add_column(ColumnDef::new(PayoutBatches::ApprovedBy).integer())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-payout_batches-approved_by")
+                            .from_tbl(PayoutBatches::Table)
+                            .from_col(PayoutBatches::ApprovedBy)
+                            .to_tbl(Users::Table)
+                            .to_col(Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PayoutBatches::Table)
+                    .drop_foreign_key(Alias::new("fk-payout_batches-approved_by"))
+                    .drop_column(PayoutBatches::ApprovedBy)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PayoutBatches {
+    Table,
+    ApprovedBy,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
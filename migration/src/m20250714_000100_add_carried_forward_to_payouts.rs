@@ -0,0 +1,37 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Payouts go out in whole shillings, and the cents held back are
+        // added to the consignor's next payout
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Payouts::Table)
+                    .add_column(ColumnDef::new(Payouts::CarriedForward).decimal_len(12, 2).not_null().default(0))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Payouts::Table)
+                    .drop_column(Payouts::CarriedForward)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Payouts {
+    Table,
+    CarriedForward,
+}
@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::{extension::postgres::Type, *};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // A batch or payout that has been claimed for sending but not yet
+        // settled, so an interrupted send can be told apart from a finished one
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(PayoutStatus::Enum)
+                    .add_value(PayoutStatus::Sending)
+                    .before(PayoutStatus::Sent)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Postgres cannot drop a value from an enum, so the type is rebuilt without it
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE payout_batches SET status = 'approved' WHERE status = 'sending'; \
+                 UPDATE payouts SET status = 'approved' WHERE status = 'sending'; \
+                 ALTER TABLE payout_batches ALTER COLUMN status DROP DEFAULT; \
+                 ALTER TABLE payouts ALTER COLUMN status DROP DEFAULT; \
+                 ALTER TYPE payout_status RENAME TO payout_status_old; \
+                 CREATE TYPE payout_status AS ENUM ('pending', 'approved', 'sent', 'failed'); \
+                 ALTER TABLE payout_batches ALTER COLUMN status TYPE payout_status USING status::text::payout_status; \
+                 ALTER TABLE payouts ALTER COLUMN status TYPE payout_status USING status::text::payout_status; \
+                 ALTER TABLE payout_batches ALTER COLUMN status SET DEFAULT 'pending'; \
+                 ALTER TABLE payouts ALTER COLUMN status SET DEFAULT 'pending'; \
+                 DROP TYPE payout_status_old",
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum PayoutStatus {
+    #[sea_orm(iden = "payout_status")]
+    Enum,
+    Sending,
+    Sent,
+}
@@ -4,17 +4,48 @@ use actix_web::{delete, get, post, put, web};
 use chrono::{NaiveDateTime, Utc};
 use migration::Expr;
 use rust_decimal::Decimal;
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QuerySelect, Set};
+use sea_orm::{ActiveModelTrait, ColumnTrait, Condition, EntityTrait, PaginatorTrait, QueryFilter, QuerySelect, Set};
+use entity::sea_orm_active_enums::{AuctionDirection, AuctionFormat, VerificationLevel};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::utils::{api_response::ApiResponse, app_state::AppState, json_response::response};
+use crate::{
+    helpers::{
+        candle::{candle_json, generate_seed, seed_hash},
+        clearing::is_sealed_format,
+    },
+    utils::{api_response::ApiResponse, app_state::AppState, json_response::response},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 struct AuctionData {
     name: String,
     start_time: NaiveDateTime,
     end_time: NaiveDateTime,
+    deposit_amount: Option<Decimal>,
+    #[serde(default)]
+    requires_credit_approval: bool,
+    format: Option<AuctionFormat>,
+    /// Set on sealed auctions that take bid commitments; bids are revealed
+    /// between the end time and this.
+    reveal_end_time: Option<NaiveDateTime>,
+    direction: Option<AuctionDirection>,
+    /// How much each open bid must improve on the offer it is trying to beat.
+    bid_increment: Option<Decimal>,
+    /// Set on candle auctions, which close at a random moment between the end
+    /// time and this.
+    candle_end_time: Option<NaiveDateTime>,
+    /// Live auctions are sold lot by lot from the auctioneer's console.
+    #[serde(default)]
+    live: bool,
+    /// Registration-required auctions only take bids from bidders an admin
+    /// has approved.
+    #[serde(default)]
+    requires_registration: bool,
+    /// Terms bidders accept when registering.
+    terms: Option<String>,
+    /// Bidders need at least this verified identity level to bid.
+    min_verification_level: Option<VerificationLevel>,
 } 
 
 impl AuctionData {
@@ -43,6 +74,52 @@ impl AuctionData {
             return Err("End time must be after start time".to_string());
         }
 
+        if self.deposit_amount.is_some_and(|amount| amount <= Decimal::ZERO) {
+            return Err("Deposit amount must be greater than zero".to_string());
+        }
+
+        if self.bid_increment.is_some_and(|increment| increment <= Decimal::ZERO) {
+            return Err("Bid increment must be greater than zero".to_string());
+        }
+
+        if matches!(self.format, Some(AuctionFormat::Dutch | AuctionFormat::DoubleAuction))
+            && self.direction == Some(AuctionDirection::Reverse)
+        {
+            return Err("Only open and sealed auctions can run in reverse".to_string());
+        }
+
+        if let Some(reveal_end_time) = self.reveal_end_time {
+            if !self.format.as_ref().is_some_and(is_sealed_format) {
+                return Err("Only sealed auctions can take bid commitments".to_string());
+            }
+
+            if reveal_end_time <= self.end_time {
+                return Err("Reveal end time must be after end time".to_string());
+            }
+        }
+
+        if let Some(candle_end_time) = self.candle_end_time {
+            if !matches!(self.format, None | Some(AuctionFormat::Open)) {
+                return Err("Only open auctions can close by candle".to_string());
+            }
+
+            if candle_end_time <= self.end_time {
+                return Err("Candle end time must be after end time".to_string());
+            }
+        }
+
+        if self.live
+            && (!matches!(self.format, None | Some(AuctionFormat::Open))
+                || self.direction == Some(AuctionDirection::Reverse)
+                || self.candle_end_time.is_some())
+        {
+            return Err("Only open forward auctions without a candle close can run live".to_string());
+        }
+
+        if self.terms.as_ref().is_some_and(|terms| terms.trim().is_empty()) {
+            return Err("Terms cannot be empty".to_string());
+        }
+
         Ok(())
     }
 }
@@ -60,10 +137,26 @@ pub async fn create_auction(
         )));
     }
 
+    // The seed is fixed, and its hash published, before the auction starts
+    let candle_seed = auction_data.candle_end_time.map(|_| generate_seed());
+
     let _auction_model = entity::auctions::ActiveModel {
         name: Set(auction_data.name.clone()),
         start_time: Set(auction_data.start_time),
         end_time: Set(auction_data.end_time),
+        deposit_amount: Set(auction_data.deposit_amount),
+        requires_credit_approval: Set(auction_data.requires_credit_approval),
+        format: Set(auction_data.format.clone().unwrap_or(AuctionFormat::Open)),
+        reveal_end_time: Set(auction_data.reveal_end_time),
+        direction: Set(auction_data.direction.clone().unwrap_or(AuctionDirection::Forward)),
+        bid_increment: Set(auction_data.bid_increment),
+        candle_end_time: Set(auction_data.candle_end_time),
+        candle_seed: Set(candle_seed.clone()),
+        candle_seed_hash: Set(candle_seed.as_deref().map(seed_hash)),
+        live: Set(auction_data.live),
+        requires_registration: Set(auction_data.requires_registration),
+        terms: Set(auction_data.terms.clone()),
+        min_verification_level: Set(auction_data.min_verification_level.clone().unwrap_or(VerificationLevel::None)),
         ..Default::default()
     }
         .insert(&app_state.db)
@@ -118,11 +211,66 @@ pub async fn update_auction(
             ))
         })?;
 
+    let now = Utc::now().naive_utc();
+
+    // Moving the closing window of a started candle auction would move its cutoff
+    if auction_model.start_time <= now
+        && (auction_model.candle_end_time != auction_data.candle_end_time
+            || (auction_model.candle_end_time.is_some() && auction_model.end_time != auction_data.end_time))
+    {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "The closing window of a candle auction cannot change once it has started".to_string()
+            })
+        )));
+    }
+
+    // Bidders registered against the terms as they were
+    if auction_model.terms != auction_data.terms {
+        let registrations = entity::auction_registrations::Entity::find()
+            .filter(entity::auction_registrations::Column::DeletedAt.is_null())
+            .filter(entity::auction_registrations::Column::AuctionId.eq(auction_model.id))
+            .count(&app_state.db)
+            .await
+            .map_err(|err| {
+                ApiResponse::new(500, response(
+                    json!({
+                        "error": err.to_string()
+                    })
+                ))
+            })?;
+
+        if registrations > 0 {
+            return Err(ApiResponse::new(400, response(
+                json!({
+                    "error": "The terms cannot change once bidders have registered".to_string()
+                })
+            )));
+        }
+    }
+
+    let candle_seed = auction_data
+        .candle_end_time
+        .map(|_| auction_model.candle_seed.clone().unwrap_or_else(generate_seed));
+
     let mut update_auction_model: entity::auctions::ActiveModel = auction_model.to_owned().into();
     update_auction_model.name = Set(auction_data.name.clone());
-    update_auction_model.start_time = Set(auction_data.start_time.clone());
-    update_auction_model.end_time = Set(auction_data.end_time.clone());
-    update_auction_model.updated_at = Set(Utc::now().naive_utc());
+    update_auction_model.start_time = Set(auction_data.start_time);
+    update_auction_model.end_time = Set(auction_data.end_time);
+    update_auction_model.deposit_amount = Set(auction_data.deposit_amount);
+    update_auction_model.requires_credit_approval = Set(auction_data.requires_credit_approval);
+    update_auction_model.format = Set(auction_data.format.clone().unwrap_or(AuctionFormat::Open));
+    update_auction_model.reveal_end_time = Set(auction_data.reveal_end_time);
+    update_auction_model.direction = Set(auction_data.direction.clone().unwrap_or(AuctionDirection::Forward));
+    update_auction_model.bid_increment = Set(auction_data.bid_increment);
+    update_auction_model.candle_end_time = Set(auction_data.candle_end_time);
+    update_auction_model.candle_seed_hash = Set(candle_seed.as_deref().map(seed_hash));
+    update_auction_model.candle_seed = Set(candle_seed);
+    update_auction_model.live = Set(auction_data.live);
+    update_auction_model.requires_registration = Set(auction_data.requires_registration);
+    update_auction_model.terms = Set(auction_data.terms.clone());
+    update_auction_model.min_verification_level = Set(auction_data.min_verification_level.clone().unwrap_or(VerificationLevel::None));
+    update_auction_model.updated_at = Set(now);
     update_auction_model
         .update(&app_state.db)
         .await
@@ -163,6 +311,18 @@ pub async fn get_all_auctions(
                 "name": row.name,
                 "start_time": row.start_time,
                 "end_time": row.end_time,
+                "deposit_amount": row.deposit_amount,
+                "requires_credit_approval": row.requires_credit_approval,
+                "format": row.format,
+                "reveal_end_time": row.reveal_end_time,
+                "direction": row.direction,
+                "bid_increment": row.bid_increment,
+                "candle_end_time": row.candle_end_time,
+                "candle_seed_hash": row.candle_seed_hash,
+                "live": row.live,
+                "requires_registration": row.requires_registration,
+                "terms": row.terms,
+                "min_verification_level": row.min_verification_level,
                 "updated_at": row.updated_at,
             })
         })
@@ -176,6 +336,43 @@ pub async fn get_all_auctions(
     )))
 }
 
+/// The candle draw for an auction: the seed hash from the start, and the seed
+/// and the cutoff it yields once the closing window is over.
+#[get("/auction/candle/{id}")]
+pub async fn get_auction_candle(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> Result<ApiResponse, ApiResponse> {
+    let auction_id = path.into_inner();
+
+    let auction_model = entity::auctions::Entity::find_by_id(auction_id)
+        .filter(entity::auctions::Column::DeletedAt.is_null())
+        .filter(entity::auctions::Column::CandleEndTime.is_not_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Candle auction not found".to_string()
+                })
+            ))
+        })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "candle": candle_json(&auction_model, Utc::now().naive_utc()),
+            "message": "Candle draw fetched successfully".to_string()
+        })
+    )))
+}
+
 #[delete("/auction/delete/{id}")]
 pub async fn delete_auction(
     app_state: web::Data<AppState>,
@@ -212,7 +409,11 @@ pub async fn get_user_auctions(
     // Fetch only auctions that are either Not Started or Active (exclude Ended ones)
     let active_auctions = entity::auctions::Entity::find()
         .inner_join(entity::listings::Entity)
-        .filter(entity::auctions::Column::EndTime.gte(now))
+        .filter(
+            Condition::any()
+                .add(entity::auctions::Column::EndTime.gte(now))
+                .add(entity::auctions::Column::CandleEndTime.gte(now))
+        )
         .select_only()
         .column(entity::listings::Column::Id)
         .column(entity::auctions::Column::StartTime)
@@ -221,20 +422,26 @@ pub async fn get_user_auctions(
         .column(entity::listings::Column::Description)
         .column(entity::listings::Column::BasePrice)
         .column(entity::listings::Column::AvailableVolume)
-        .into_tuple::<(i32, NaiveDateTime, NaiveDateTime, String, String, Decimal, i32)>()
+        .column(entity::listings::Column::UnitOfMeasure)
+        .column(entity::auctions::Column::Format)
+        .column(entity::auctions::Column::Direction)
+        .into_tuple::<(i32, NaiveDateTime, NaiveDateTime, String, Option<String>, Decimal, i32, String, AuctionFormat, AuctionDirection)>()
         .all(&app_state.db)
         .await
         .map_err(|err| {
             ApiResponse::new(500, response(json!({ "error": err.to_string() })))
         })?;
 
+    // Retracted and voided bids no longer count towards anything shown here
     let highest_user_bids = entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
         .filter(entity::bids::Column::UserId.eq(user_id))
         .group_by(entity::bids::Column::ListingId)
         .select_only()
         .column(entity::bids::Column::ListingId)
         .column_as(Expr::col(entity::bids::Column::Amount).max(), "max_bid_user")
-        .into_tuple::<(i32, Decimal)>()
+        .column_as(Expr::col(entity::bids::Column::Amount).min(), "min_bid_user")
+        .into_tuple::<(i32, Decimal, Decimal)>()
         .all(&app_state.db)
         .await
         .map_err(|err| {
@@ -242,11 +449,13 @@ pub async fn get_user_auctions(
         })?;
 
     let highest_bids_anyone = entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
         .group_by(entity::bids::Column::ListingId)
         .select_only()
         .column(entity::bids::Column::ListingId)
         .column_as(Expr::col(entity::bids::Column::Amount).max(), "max_bid_anyone")
-        .into_tuple::<(i32, Decimal)>()
+        .column_as(Expr::col(entity::bids::Column::Amount).min(), "min_bid_anyone")
+        .into_tuple::<(i32, Decimal, Decimal)>()
         .all(&app_state.db)
         .await
         .map_err(|err| {
@@ -254,6 +463,7 @@ pub async fn get_user_auctions(
         })?;
 
     let total_bids = entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
         .group_by(entity::bids::Column::ListingId)
         .select_only()
         .column(entity::bids::Column::ListingId)
@@ -265,11 +475,27 @@ pub async fn get_user_auctions(
             ApiResponse::new(500, response(json!({ "error": err.to_string() })))
         })?;
 
-    let highest_user_bids_map: HashMap<i32, Decimal> = highest_user_bids.into_iter().collect();
-    let highest_bids_anyone_map: HashMap<i32, Decimal> = highest_bids_anyone.into_iter().collect();
+    let user_bids_map: HashMap<i32, (Decimal, Decimal)> = highest_user_bids
+        .into_iter()
+        .map(|(listing_id, max_bid, min_bid)| (listing_id, (max_bid, min_bid)))
+        .collect();
+    let bids_anyone_map: HashMap<i32, (Decimal, Decimal)> = highest_bids_anyone
+        .into_iter()
+        .map(|(listing_id, max_bid, min_bid)| (listing_id, (max_bid, min_bid)))
+        .collect();
     let total_bids_map: HashMap<i32, i64> = total_bids.into_iter().collect();
 
-    let auctions_data = active_auctions.into_iter().map(|(listing_id, start_time, end_time, title, description, base_price, available_volume)| {
+    let auctions_data = active_auctions.into_iter().map(|(listing_id, start_time, end_time, title, description, base_price, available_volume, unit_of_measure, format, direction)| {
+        // Only ended auctions are left out above, so every sealed one here is still hiding its bids
+        let sealed = is_sealed_format(&format);
+
+        // The best offer is the highest bid, or the lowest in a reverse auction
+        let best_bid = |(max_bid, min_bid): (Decimal, Decimal)| {
+            if direction == AuctionDirection::Reverse { min_bid } else { max_bid }
+        };
+        let user_bids = user_bids_map.get(&listing_id).copied();
+        let bids_anyone = bids_anyone_map.get(&listing_id).copied();
+
         json!({
             "listing_id": listing_id,
             "end_time": end_time,
@@ -277,10 +503,15 @@ pub async fn get_user_auctions(
             "description": description,
             "base_price": base_price,
             "available_volume": available_volume,
+            "unit_of_measure": unit_of_measure,
             "status": if start_time > now { "Not Started".to_string() } else { "Active".to_string() },
-            "highest_user_bid": highest_user_bids_map.get(&listing_id).unwrap_or(&Decimal::ZERO),
-            "highest_anyone_bid": highest_bids_anyone_map.get(&listing_id).unwrap_or(&Decimal::ZERO),
-            "total_bids": total_bids_map.get(&listing_id).unwrap_or(&0),
+            "highest_user_bid": user_bids.map_or(Decimal::ZERO, |(max_bid, _)| max_bid),
+            "best_user_bid": user_bids.map(best_bid),
+            "format": format,
+            "direction": direction,
+            "highest_anyone_bid": (!sealed).then(|| bids_anyone.map_or(Decimal::ZERO, |(max_bid, _)| max_bid)),
+            "best_anyone_bid": (!sealed).then(|| bids_anyone.map(best_bid)).flatten(),
+            "total_bids": (!sealed).then(|| total_bids_map.get(&listing_id).unwrap_or(&0)),
         })
     }).collect::<Vec<_>>();
 
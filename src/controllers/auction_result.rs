@@ -1,20 +1,18 @@
 use std::{collections::HashMap, time::Duration};
 
-use actix_web::{get, rt::time::interval, web};
+use actix_session::Session;
+use actix_web::{get, http::header::{ContentDisposition, DispositionParam, DispositionType}, rt::time::interval, web, HttpResponse};
 use chrono::{NaiveDateTime, Utc};
+use entity::sea_orm_active_enums::AuctionFormat;
 use rust_decimal::Decimal;
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, FromQueryResult, QueryFilter, QuerySelect, Set, ActiveModelTrait};
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection, EntityTrait, FromQueryResult, QueryFilter, QuerySelect, Set, ActiveModelTrait, TransactionTrait};
+use serde::Deserialize;
 use serde_json::json;
 
-use crate::utils::{api_response::ApiResponse, app_state::AppState, constants, json_response::response, whatsapp::send_whatsapp_message};
-
-#[derive(Debug, FromQueryResult)]
-struct BidGetResult {
-    listing_id: i32,
-    winning_bid_id: i32,
-    winning_user_id: i32,
-    amount: Decimal,
-}
+use crate::{
+    helpers::{bidder_limits::settle_deposits, candle::cutoff_at, clearing::{allocate, is_reverse}, fees::{apply_fees, fee_lines_json, fees_for_results, FeeTotals}, invoices::{generate_invoices, InvoiceDocument}, live_auction::close_live_auction, staff::require_user_or_admin},
+    utils::{api_response::ApiResponse, app_state::AppState, constants, json_response::response, whatsapp::send_whatsapp_message},
+};
 
 pub async fn create_auction_result(db: DatabaseConnection) -> Result<(), ApiResponse> {
     let mut interval = interval(Duration::from_secs(60));
@@ -36,6 +34,20 @@ async fn process_auction_results(db: &DatabaseConnection) -> Result<(), ApiRespo
     let auctions_ended = entity::auctions::Entity::find()
         .filter(entity::auctions::Column::DeletedAt.is_null())
         .filter(entity::auctions::Column::EndTime.lt(now))
+        // Double auctions settle trade by trade as orders match
+        .filter(entity::auctions::Column::Format.ne(AuctionFormat::DoubleAuction))
+        // Commit-reveal auctions are only decided once their reveal window has closed
+        .filter(
+            Condition::any()
+                .add(entity::auctions::Column::RevealEndTime.is_null())
+                .add(entity::auctions::Column::RevealEndTime.lt(now))
+        )
+        // Candle auctions are only decided once their closing window has passed
+        .filter(
+            Condition::any()
+                .add(entity::auctions::Column::CandleEndTime.is_null())
+                .add(entity::auctions::Column::CandleEndTime.lt(now))
+        )
         .all(db)
         .await
         .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
@@ -46,44 +58,48 @@ async fn process_auction_results(db: &DatabaseConnection) -> Result<(), ApiRespo
         return Ok(()); // No ended auctions, skip processing
     }
 
-    // Get highest bids
-    let highest_bids = entity::bids::Entity::find()
+    // Live auctions the auctioneer did not finish are wound up here, and their
+    // lots are left alone below
+    for auction in auctions_ended.iter().filter(|auction| auction.live) {
+        close_live_auction(db, auction.id).await?;
+    }
+
+    let all_listings = entity::listings::Entity::find()
+        .filter(entity::listings::Column::AuctionId.is_in(auction_ids.clone()))
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let all_bids = entity::bids::Entity::find()
         .inner_join(entity::listings::Entity)
-        .inner_join(entity::users::Entity)
         .filter(entity::bids::Column::DeletedAt.is_null())
         .filter(entity::listings::Column::AuctionId.is_in(auction_ids.clone()))
-        .select_only()
-        .column_as(entity::bids::Column::Id, "winning_bid_id")
-        .column_as(entity::users::Column::Id, "winning_user_id")
-        .column_as(entity::listings::Column::Id, "listing_id")
-        .column(entity::bids::Column::Amount)
-        .into_model::<BidGetResult>()
         .all(db)
         .await
         .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
 
-    let mut highest_bids_map: HashMap<i32, BidGetResult> = HashMap::new();
-    for bid in highest_bids {
-        match highest_bids_map.get(&bid.listing_id) {
-            Some(current_highest) => {
-                if bid.amount > current_highest.amount {
-                    highest_bids_map.insert(bid.listing_id, bid);
-                }
-            }
-            None => {
-                highest_bids_map.insert(bid.listing_id, bid);
-            }
-        }
-    }
-
-    let all_listings = entity::listings::Entity::find()
-        .filter(entity::listings::Column::AuctionId.is_in(auction_ids))
+    let user_names: HashMap<i32, String> = entity::users::Entity::find()
+        .filter(entity::users::Column::Id.is_in(all_bids.iter().map(|bid| bid.user_id).collect::<Vec<_>>()))
         .all(db)
         .await
-        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .into_iter()
+        .map(|user| (user.id, user.name))
+        .collect();
+
+    let auctions_by_id: HashMap<i32, &entity::auctions::Model> =
+        auctions_ended.iter().map(|auction| (auction.id, auction)).collect();
 
     for listing in &all_listings {
         let title = listing.title.clone();
+        let Some(auction) = auctions_by_id.get(&listing.auction_id) else {
+            continue;
+        };
+
+        // Lots in a live auction get their results when they are hammered
+        if auction.live {
+            continue;
+        }
 
         // Check if auction result already exists
         let existing_result = entity::auction_results::Entity::find()
@@ -93,41 +109,105 @@ async fn process_auction_results(db: &DatabaseConnection) -> Result<(), ApiRespo
             .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
 
         if existing_result.is_none() {
-            if let Some(bid) = highest_bids_map.get(&listing.id) {
-                let user = entity::users::Entity::find_by_id(bid.winning_user_id)
-                    .one(db)
-                    .await
-                    .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
-                    .map(|u| u.name)
-                    .unwrap_or_else(|| "Unknown User".to_string());
-
-                let _auction_result = entity::auction_results::ActiveModel {
-                    listing_id: Set(listing.id),
-                    winning_bid_id: Set(bid.winning_bid_id),
-                    winning_user_id: Set(bid.winning_user_id),
-                    ..Default::default()
+            // Bids placed after a candle auction's cutoff are discarded
+            let candle_bids;
+            let bids = match cutoff_at(auction) {
+                Some(cutoff) => {
+                    candle_bids = all_bids.iter().filter(|bid| bid.created_at < cutoff).cloned().collect::<Vec<_>>();
+                    &candle_bids
                 }
-                .insert(db)
-                .await
-                .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+                None => &all_bids,
+            };
 
-                let message = format!(
-                    "Auction Result:\nAuction ID: {}\nListing: {}\nWinning Bid: {}\nWinner: {}",
-                    listing.auction_id, title, bid.amount, user
-                );
+            let awards = allocate(auction, listing, bids);
 
-                send_whatsapp_message(&whatsapp_number, &message).await?;
-            } else {
+            if awards.is_empty() {
                 let message = format!(
                     "Auction Result:\nAuction ID: {}\nListing: {}\nNo bids were placed.",
                     listing.auction_id, title
                 );
 
-                send_whatsapp_message(&whatsapp_number, &message).await?;
+                if let Err(err) = send_whatsapp_message(&whatsapp_number, &message).await {
+                    eprintln!("Error sending auction result notification: {:?}", err);
+                }
+
+                continue;
+            }
+
+            // All of a listing's results and their fee lines are stored together so
+            // a result never exists without its fees or its fellow winners
+            let txn = db
+                .begin()
+                .await
+                .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+            let mut winners = Vec::new();
+            for award in &awards {
+                let auction_result = entity::auction_results::ActiveModel {
+                    listing_id: Set(listing.id),
+                    winning_bid_id: Set(award.bid_id),
+                    winning_user_id: Set(award.user_id),
+                    quantity: Set(award.quantity),
+                    unit_price: Set(award.unit_price),
+                    hammer_price: Set(award.hammer_price()),
+                    ..Default::default()
+                }
+                .insert(&txn)
+                .await
+                .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+                let fee_lines = apply_fees(&txn, listing.auction_id, auction_result.id, auction_result.hammer_price).await?;
+                let fee_totals = FeeTotals::from_models(&fee_lines);
+
+                let winner_name = user_names.get(&award.user_id).cloned().unwrap_or_else(|| "Unknown User".to_string());
+
+                // A reverse auction awards a supply contract rather than selling the lot
+                winners.push(if is_reverse(auction) {
+                    format!(
+                        "Awarded to: {}\nQuantity: {} {} at {} per {}\nContract Value: {}",
+                        winner_name,
+                        award.quantity,
+                        listing.unit_of_measure,
+                        award.unit_price,
+                        listing.unit_of_measure,
+                        auction_result.hammer_price
+                    )
+                } else {
+                    format!(
+                        "Winner: {}\nQuantity: {} {} at {} per {}\nTotal Payable: {}",
+                        winner_name,
+                        award.quantity,
+                        listing.unit_of_measure,
+                        award.unit_price,
+                        listing.unit_of_measure,
+                        fee_totals.buyer_total(auction_result.hammer_price)
+                    )
+                });
+            }
+
+            txn.commit()
+                .await
+                .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+            let message = format!(
+                "Auction Result:\nAuction ID: {}\nListing: {}\n{}",
+                listing.auction_id, title, winners.join("\n")
+            );
+
+            // A failed notification must not hold up invoicing and deposit settlement
+            if let Err(err) = send_whatsapp_message(&whatsapp_number, &message).await {
+                eprintln!("Error sending auction result notification: {:?}", err);
             }
         }
     }
 
+    // Only results that are not yet on an invoice are picked up, so this also
+    // catches results left uninvoiced by an earlier failed run
+    generate_invoices(db, auction_ids.clone(), false).await?;
+
+    // Runs after invoicing so a winner's deposit can go towards their invoice
+    settle_deposits(db, auction_ids).await?;
+
     Ok(())
 }
 
@@ -135,6 +215,9 @@ async fn process_auction_results(db: &DatabaseConnection) -> Result<(), ApiRespo
 struct AuctionResultDataResult {
     id: i32,
     name: String,
+    quantity: i32,
+    unit_price: Decimal,
+    unit_of_measure: String,
     amount: Decimal,
     title: String,
     created_at: NaiveDateTime,
@@ -149,12 +232,14 @@ pub async fn get_auction_results(
         .filter(entity::auction_results::Column::DeletedAt.is_null())
         .inner_join(entity::listings::Entity)
         .inner_join(entity::users::Entity)
-        .inner_join(entity::bids::Entity)
         .select_only()
         .column(entity::auction_results::Column::Id)
         .column(entity::users::Column::Name)
-        .column_as(entity::bids::Column::Id, "bid_id")
-        .column(entity::bids::Column::Amount)
+        .column_as(entity::auction_results::Column::WinningBidId, "bid_id")
+        .column(entity::auction_results::Column::Quantity)
+        .column(entity::auction_results::Column::UnitPrice)
+        .column(entity::listings::Column::UnitOfMeasure)
+        .column_as(entity::auction_results::Column::HammerPrice, "amount")
         .column(entity::listings::Column::Title)
         .column(entity::auction_results::Column::CreatedAt)
         .into_model::<AuctionResultDataResult>()
@@ -166,15 +251,29 @@ pub async fn get_auction_results(
                     "error": err.to_string()
                 })
             ))
-        })?
+        })?;
+
+    let fees_map = fees_for_results(&app_state.db, auction_results.iter().map(|row| row.id).collect()).await?;
+
+    let auction_results = auction_results
         .into_iter()
         .map(|row| {
+            let fee_lines = fees_map.get(&row.id).map(Vec::as_slice).unwrap_or_default();
+            let fee_totals = FeeTotals::from_models(fee_lines);
+
             json!({
                 "id": row.id,
                 "name": row.name,
+                "quantity": row.quantity,
+                "unit_of_measure": row.unit_of_measure,
+                "unit_price": row.unit_price,
                 "amount": row.amount,
                 "bid_id": row.bid_id,
                 "title": row.title,
+                "fees": fee_lines_json(fee_lines),
+                "buyer_fees": fee_totals.buyer_fees,
+                "buyer_tax": fee_totals.buyer_tax,
+                "total_payable": fee_totals.buyer_total(row.amount),
                 "created_at": row.created_at,
             })
         })
@@ -186,4 +285,143 @@ pub async fn get_auction_results(
             "message": "Auction results fetched successfully".to_string()
         })
     )))
-}
\ No newline at end of file
+}
+
+#[derive(Debug, FromQueryResult)]
+struct SettlementDataResult {
+    id: i32,
+    listing_id: i32,
+    title: String,
+    winning_user_id: i32,
+    name: String,
+    quantity: i32,
+    unit_price: Decimal,
+    unit_of_measure: String,
+    amount: Decimal,
+}
+
+#[get("/auction_results/settlement/{id}")]
+pub async fn get_auction_settlement(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> Result<ApiResponse, ApiResponse> {
+    let auction_id = path.into_inner();
+
+    let auction_results = entity::auction_results::Entity::find()
+        .filter(entity::auction_results::Column::DeletedAt.is_null())
+        .filter(entity::listings::Column::AuctionId.eq(auction_id))
+        .inner_join(entity::listings::Entity)
+        .inner_join(entity::users::Entity)
+        .select_only()
+        .column(entity::auction_results::Column::Id)
+        .column(entity::auction_results::Column::ListingId)
+        .column(entity::listings::Column::Title)
+        .column(entity::auction_results::Column::WinningUserId)
+        .column(entity::users::Column::Name)
+        .column(entity::auction_results::Column::Quantity)
+        .column(entity::auction_results::Column::UnitPrice)
+        .column(entity::listings::Column::UnitOfMeasure)
+        .column_as(entity::auction_results::Column::HammerPrice, "amount")
+        .into_model::<SettlementDataResult>()
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let fees_map = fees_for_results(&app_state.db, auction_results.iter().map(|row| row.id).collect()).await?;
+
+    let mut hammer_total = Decimal::ZERO;
+    let mut auction_totals = FeeTotals::default();
+
+    let settlements = auction_results
+        .into_iter()
+        .map(|row| {
+            let fee_lines = fees_map.get(&row.id).map(Vec::as_slice).unwrap_or_default();
+            let fee_totals = FeeTotals::from_models(fee_lines);
+
+            hammer_total += row.amount;
+            auction_totals.buyer_fees += fee_totals.buyer_fees;
+            auction_totals.buyer_tax += fee_totals.buyer_tax;
+            auction_totals.seller_fees += fee_totals.seller_fees;
+            auction_totals.seller_tax += fee_totals.seller_tax;
+
+            json!({
+                "auction_result_id": row.id,
+                "listing_id": row.listing_id,
+                "title": row.title,
+                "winning_user_id": row.winning_user_id,
+                "name": row.name,
+                "quantity": row.quantity,
+                "unit_of_measure": row.unit_of_measure,
+                "unit_price": row.unit_price,
+                "hammer_price": row.amount,
+                "fees": fee_lines_json(fee_lines),
+                "buyer_fees": fee_totals.buyer_fees,
+                "buyer_tax": fee_totals.buyer_tax,
+                "buyer_total": fee_totals.buyer_total(row.amount),
+                "seller_fees": fee_totals.seller_fees,
+                "seller_tax": fee_totals.seller_tax,
+                "seller_net": fee_totals.seller_net(row.amount),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "auction_id": auction_id,
+            "settlements": settlements,
+            "totals": {
+                "hammer_total": hammer_total,
+                "buyer_fees": auction_totals.buyer_fees,
+                "buyer_tax": auction_totals.buyer_tax,
+                "buyer_total": auction_totals.buyer_total(hammer_total),
+                "seller_fees": auction_totals.seller_fees,
+                "seller_tax": auction_totals.seller_tax,
+                "seller_net": auction_totals.seller_net(hammer_total),
+            },
+            "message": "Auction settlement fetched successfully".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+struct InvoiceFormatQuery {
+    format: Option<String>,
+}
+
+#[get("/auction_results/invoice/{id}")]
+pub async fn get_auction_result_invoice(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    query: web::Query<InvoiceFormatQuery>,
+    session: Session,
+) -> Result<HttpResponse, ApiResponse> {
+    let invoice_id = path.into_inner();
+
+    let invoice_document = InvoiceDocument::load(&app_state.db, invoice_id).await?;
+    require_user_or_admin(&app_state.db, &session, invoice_document.invoice.user_id).await?;
+    let invoice_number = invoice_document.invoice.invoice_number.clone();
+
+    match query.format.as_deref().unwrap_or("html") {
+        "html" => Ok(HttpResponse::Ok()
+            .content_type("text/html; charset=utf-8")
+            .body(invoice_document.render_html())),
+        "pdf" => Ok(HttpResponse::Ok()
+            .content_type("application/pdf")
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(format!("{}.pdf", invoice_number))],
+            })
+            .body(invoice_document.render_pdf()?)),
+        _ => Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Format must be either html or pdf".to_string()
+            })
+        ))),
+    }
+}
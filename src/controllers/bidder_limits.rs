@@ -0,0 +1,344 @@
+use actix_session::Session;
+use actix_web::{get, post, put, web};
+use chrono::Utc;
+use entity::sea_orm_active_enums::DepositStatus;
+use rust_decimal::Decimal;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    helpers::{
+        bidder_limits::{effective_limit, is_restricted, leading_exposure},
+        ledger::{account_balance, lock_user_account, post_journal_entry, Posting, UserAccount},
+        staff::require_admin,
+    },
+    utils::{api_response::ApiResponse, app_state::AppState, json_response::response, session::session_user_id},
+};
+
+fn bidder_limit_json(bidder_limit: &entity::bidder_limits::Model) -> serde_json::Value {
+    json!({
+        "id": bidder_limit.id,
+        "auction_id": bidder_limit.auction_id,
+        "user_id": bidder_limit.user_id,
+        "deposit_amount": bidder_limit.deposit_amount,
+        "deposit_status": bidder_limit.deposit_status,
+        "credit_limit": bidder_limit.credit_limit,
+        "approved_at": bidder_limit.approved_at,
+        "settled_at": bidder_limit.settled_at,
+    })
+}
+
+async fn find_auction(app_state: &AppState, auction_id: i32) -> Result<entity::auctions::Model, ApiResponse> {
+    entity::auctions::Entity::find_by_id(auction_id)
+        .filter(entity::auctions::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Auction not found".to_string()
+                })
+            ))
+        })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DepositData {
+    auction_id: i32,
+}
+
+#[post("/bidder_limit/deposit")]
+pub async fn lodge_deposit(
+    app_state: web::Data<AppState>,
+    deposit_data: web::Json<DepositData>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_id = session_user_id(&session)?;
+    let auction_model = find_auction(&app_state, deposit_data.auction_id).await?;
+
+    let Some(deposit_amount) = auction_model.deposit_amount else {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "This auction does not take deposits".to_string()
+            })
+        )));
+    };
+
+    if auction_model.end_time < Utc::now().naive_utc() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "The auction has already ended".to_string()
+            })
+        )));
+    }
+
+    let txn = app_state.db.begin().await.map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    let bidder_limit_model = entity::bidder_limits::Entity::find()
+        .filter(entity::bidder_limits::Column::DeletedAt.is_null())
+        .filter(entity::bidder_limits::Column::AuctionId.eq(deposit_data.auction_id))
+        .filter(entity::bidder_limits::Column::UserId.eq(user_id))
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    if bidder_limit_model.as_ref().is_some_and(|model| model.deposit_status.is_some()) {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "A deposit has already been lodged for this auction".to_string()
+            })
+        )));
+    }
+
+    let wallet = lock_user_account(&txn, user_id, UserAccount::Wallet).await?;
+
+    if account_balance(&txn, wallet).await? < deposit_amount {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Insufficient wallet balance to lodge the deposit".to_string()
+            })
+        )));
+    }
+
+    let now = Utc::now().naive_utc();
+    let bidder_limit_model = match bidder_limit_model {
+        Some(model) => {
+            let mut update_bidder_limit_model: entity::bidder_limits::ActiveModel = model.into();
+            update_bidder_limit_model.deposit_amount = Set(deposit_amount);
+            update_bidder_limit_model.deposit_status = Set(Some(DepositStatus::Held));
+            update_bidder_limit_model.updated_at = Set(now);
+            update_bidder_limit_model.update(&txn).await
+        }
+        None => {
+            entity::bidder_limits::ActiveModel {
+                auction_id: Set(deposit_data.auction_id),
+                user_id: Set(user_id),
+                deposit_amount: Set(deposit_amount),
+                deposit_status: Set(Some(DepositStatus::Held)),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await
+        }
+    }
+    .map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    let deposit_account = lock_user_account(&txn, user_id, UserAccount::Deposit).await?;
+
+    post_journal_entry(
+        &txn,
+        &format!("deposit:{}:lodge", bidder_limit_model.id),
+        &format!("Deposit lodged for auction {}", deposit_data.auction_id),
+        vec![Posting::debit(wallet, deposit_amount), Posting::credit(deposit_account, deposit_amount)],
+    )
+    .await?;
+
+    txn.commit().await.map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "bidder_limit": bidder_limit_json(&bidder_limit_model),
+            "message": "Deposit lodged successfully".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CreditLimitData {
+    auction_id: i32,
+    user_id: i32,
+    credit_limit: Decimal,
+}
+
+#[put("/bidder_limit/credit")]
+pub async fn approve_credit_limit(
+    app_state: web::Data<AppState>,
+    credit_data: web::Json<CreditLimitData>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    require_admin(&app_state.db, &session).await?;
+
+    if credit_data.credit_limit < Decimal::ZERO {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Credit limit cannot be negative".to_string()
+            })
+        )));
+    }
+
+    find_auction(&app_state, credit_data.auction_id).await?;
+
+    entity::users::Entity::find_by_id(credit_data.user_id)
+        .filter(entity::users::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "User not found".to_string()
+                })
+            ))
+        })?;
+
+    let bidder_limit_model = entity::bidder_limits::Entity::find()
+        .filter(entity::bidder_limits::Column::DeletedAt.is_null())
+        .filter(entity::bidder_limits::Column::AuctionId.eq(credit_data.auction_id))
+        .filter(entity::bidder_limits::Column::UserId.eq(credit_data.user_id))
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let now = Utc::now().naive_utc();
+    let bidder_limit_model = match bidder_limit_model {
+        Some(model) => {
+            let mut update_bidder_limit_model: entity::bidder_limits::ActiveModel = model.into();
+            update_bidder_limit_model.credit_limit = Set(Some(credit_data.credit_limit));
+            update_bidder_limit_model.approved_at = Set(Some(now));
+            update_bidder_limit_model.updated_at = Set(now);
+            update_bidder_limit_model.update(&app_state.db).await
+        }
+        None => {
+            entity::bidder_limits::ActiveModel {
+                auction_id: Set(credit_data.auction_id),
+                user_id: Set(credit_data.user_id),
+                credit_limit: Set(Some(credit_data.credit_limit)),
+                approved_at: Set(Some(now)),
+                ..Default::default()
+            }
+            .insert(&app_state.db)
+            .await
+        }
+    }
+    .map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "bidder_limit": bidder_limit_json(&bidder_limit_model),
+            "message": "Credit limit approved successfully".to_string()
+        })
+    )))
+}
+
+#[get("/bidder_limits/get/{id}")]
+pub async fn get_auction_bidder_limits(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> Result<ApiResponse, ApiResponse> {
+    let auction_id = path.into_inner();
+
+    let bidder_limits = entity::bidder_limits::Entity::find()
+        .filter(entity::bidder_limits::Column::DeletedAt.is_null())
+        .filter(entity::bidder_limits::Column::AuctionId.eq(auction_id))
+        .order_by_asc(entity::bidder_limits::Column::Id)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .iter()
+        .map(bidder_limit_json)
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "bidder_limits": bidder_limits,
+            "message": "Bidder limits fetched successfully".to_string()
+        })
+    )))
+}
+
+#[get("/bidder_limit/user/{id}/{auction_id}")]
+pub async fn get_user_bidder_limit(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+) -> Result<ApiResponse, ApiResponse> {
+    let (user_id, auction_id) = path.into_inner();
+
+    let auction_model = find_auction(&app_state, auction_id).await?;
+
+    let bidder_limit_model = entity::bidder_limits::Entity::find()
+        .filter(entity::bidder_limits::Column::DeletedAt.is_null())
+        .filter(entity::bidder_limits::Column::AuctionId.eq(auction_id))
+        .filter(entity::bidder_limits::Column::UserId.eq(user_id))
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let exposure = leading_exposure(&app_state.db, &auction_model, user_id, None).await?;
+    let limit = is_restricted(&auction_model).then(|| effective_limit(&auction_model, bidder_limit_model.as_ref()));
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "restricted": is_restricted(&auction_model),
+            "deposit_required": auction_model.deposit_amount,
+            "requires_credit_approval": auction_model.requires_credit_approval,
+            "bidder_limit": bidder_limit_model.as_ref().map(bidder_limit_json),
+            "limit": limit,
+            "exposure": exposure,
+            "available": limit.map(|limit| (limit - exposure).max(Decimal::ZERO)),
+            "message": "Bidder limit fetched successfully".to_string()
+        })
+    )))
+}
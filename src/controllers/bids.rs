@@ -1,17 +1,42 @@
+use actix_session::Session;
 use actix_web::{get, post, web};
 use chrono::{NaiveDateTime, Utc};
 use rust_decimal::Decimal;
-use sea_orm::{ActiveModelTrait, ColumnTrait, Condition, EntityTrait, FromQueryResult, QueryFilter, QueryOrder, QuerySelect, Set};
+use entity::sea_orm_active_enums::{AuctionFormat, BidAuditAction, BidChannel, UserRole};
+use sea_orm::{ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, EntityTrait, FromQueryResult, JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set, TransactionTrait};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
 
-use crate::utils::{api_response::ApiResponse, app_state::AppState, json_response::response};
+use crate::{
+    helpers::{
+        bidder_limits::check_bid_within_limit,
+        clearing::{allocate, best_offer, bidding_closes_at, bids_hidden, is_better_offer, is_reverse, price_to_beat},
+        commitments::{commitment_hash, fits_bid_scale, is_valid_commitment, receipt_public_key, sign_receipt, verify_receipt},
+        dutch::{accepted_quantities, current_price, is_dutch},
+        absentee::{absentee_amount, execute_absentee_bids},
+        bid_withdrawals::withdraw_bid,
+        live_auction::live_bid_json,
+        pseudonyms::{bidder_label, bidder_numbers},
+        kyc::check_verification,
+        registrations::check_registered,
+        staff::{require_admin, require_staff},
+    },
+    utils::{api_response::ApiResponse, app_state::AppState, json_response::response, session::session_user_id},
+};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct BidData {
-    listing_id: i32,
-    user_id: i32,
-    amount: Decimal,
+/// A bid for a given bidder. Online bids are always for the signed-in user;
+/// only staff can name someone else.
+#[derive(Debug)]
+pub(crate) struct BidData {
+    pub(crate) listing_id: i32,
+    pub(crate) user_id: i32,
+    /// Price offered for each unit of the listing.
+    pub(crate) amount: Decimal,
+    pub(crate) quantity: i32,
+}
+
+pub(crate) fn default_quantity() -> i32 {
+    1
 }
 
 impl BidData {
@@ -28,34 +53,43 @@ impl BidData {
             return Err("Bid amount must be greater than zero".to_string());
         }
 
+        if self.quantity <= 0 {
+            return Err("Bid quantity must be at least one".to_string());
+        }
+
         Ok(())
     }
 }
 
-#[derive(Debug, FromQueryResult)]
-struct BidResult {
-    end_time: NaiveDateTime
-}
+/// Checks a bid's quantity against the listing's purchase limits.
+fn check_quantity(listing_model: &entity::listings::Model, quantity: i32) -> Result<(), ApiResponse> {
+    if quantity < listing_model.min_quantity {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": format!("The minimum purchase on this listing is {} {}", listing_model.min_quantity, listing_model.unit_of_measure)
+            })
+        )))
+    }
 
-#[post("/bid/create")]
-pub async fn create_bid(
-    bid_data: web::Json<BidData>,
-    app_state: web::Data<AppState>,
-) -> Result<ApiResponse, ApiResponse> {
-    if let Err(err) = bid_data.validate() {
-        return Err(ApiResponse::new(500, response(
+    let max_quantity = listing_model.max_quantity.unwrap_or(listing_model.available_volume).min(listing_model.available_volume);
+    if quantity > max_quantity {
+        return Err(ApiResponse::new(400, response(
             json!({
-                "error": err.to_string()
+                "error": format!("The most you can bid for on this listing is {} {}", max_quantity, listing_model.unit_of_measure)
             })
-        )));
+        )))
     }
 
-    let listing_model = entity::listings::Entity::find_by_id(bid_data.listing_id)
-        .inner_join(entity::auctions::Entity)
+    Ok(())
+}
+
+async fn find_listing_with_auction(
+    app_state: &AppState,
+    listing_id: i32,
+) -> Result<(entity::listings::Model, entity::auctions::Model), ApiResponse> {
+    let (listing_model, auction_model) = entity::listings::Entity::find_by_id(listing_id)
+        .find_also_related(entity::auctions::Entity)
         .filter(entity::listings::Column::DeletedAt.is_null())
-        .select_only()
-        .column(entity::auctions::Column::EndTime)
-        .into_model::<BidResult>()
         .one(&app_state.db)
         .await
         .map_err(|err| {
@@ -73,21 +107,23 @@ pub async fn create_bid(
             ))
         })?;
 
-    if listing_model.end_time < Utc::now().naive_utc() {
-        return Err(ApiResponse::new(400, response(
+    let auction_model = auction_model.ok_or_else(|| {
+        ApiResponse::new(404, response(
             json!({
-                "error": "Bidding is closed. The auction has already ended.".to_string()
+                "error": "Auction not found".to_string()
             })
-        )))
-    }
+        ))
+    })?;
 
-    entity::bids::ActiveModel {
-        listing_id: Set(bid_data.listing_id.clone()),
-        user_id: Set(bid_data.user_id.clone()),
-        amount: Set(bid_data.amount.clone()),
-        ..Default::default()
-    }
-        .insert(&app_state.db)
+    Ok((listing_model, auction_model))
+}
+
+/// Refuses to show a listing's bids while its auction is sealed and open.
+async fn ensure_bids_visible(app_state: &AppState, listing_id: i32) -> Result<(), ApiResponse> {
+    let auction_model = entity::auctions::Entity::find()
+        .inner_join(entity::listings::Entity)
+        .filter(entity::listings::Column::Id.eq(listing_id))
+        .one(&app_state.db)
         .await
         .map_err(|err| {
             ApiResponse::new(500, response(
@@ -97,36 +133,33 @@ pub async fn create_bid(
             ))
         })?;
 
-    Ok(ApiResponse::new(200, response(
-        json!({
-            "message": "Bid created successfully".to_string()
-        })
-    )))
-}
+    if auction_model.is_some_and(|auction| bids_hidden(&auction, Utc::now().naive_utc())) {
+        return Err(ApiResponse::new(403, response(
+            json!({
+                "error": "Bids on this sealed auction are hidden until it closes".to_string()
+            })
+        )));
+    }
 
-#[derive(Debug, FromQueryResult)]
-struct BidGetResult {
-    name: String,
-    listing_title: String,
-    amount: Decimal,
-    created_at: NaiveDateTime,
+    Ok(())
 }
 
-#[get("/bids/get")]
-pub async fn get_all_bids(
-    app_state: web::Data<AppState>,
-) -> Result<ApiResponse, ApiResponse> {
-    let bids = entity::bids::Entity::find()
+/// Makes a bid in an open auction improve on the offer it has to beat by at
+/// least the auction's increment: upwards normally, downwards in a reverse
+/// auction. The caller holds the listing lock until the bid is stored so two
+/// bids cannot both clear the same offer.
+async fn check_bid_increment<C: ConnectionTrait>(
+    db: &C,
+    auction_model: &entity::auctions::Model,
+    listing_model: &entity::listings::Model,
+    user_id: i32,
+    amount: Decimal,
+    bid_increment: Decimal,
+) -> Result<(), ApiResponse> {
+    let listing_bids = entity::bids::Entity::find()
         .filter(entity::bids::Column::DeletedAt.is_null())
-        .inner_join(entity::listings::Entity)
-        .inner_join(entity::users::Entity)
-        .select_only()
-        .column(entity::users::Column::Name)
-        .column_as(entity::listings::Column::Title, "listing_title")
-        .column(entity::bids::Column::Amount)
-        .column(entity::bids::Column::CreatedAt)
-        .into_model::<BidGetResult>()
-        .all(&app_state.db)
+        .filter(entity::bids::Column::ListingId.eq(listing_model.id))
+        .all(db)
         .await
         .map_err(|err| {
             ApiResponse::new(500, response(
@@ -134,43 +167,124 @@ pub async fn get_all_bids(
                     "error": err.to_string()
                 })
             ))
-        })?
-        .into_iter()
-        .map(|row| {
+        })?;
+
+    let Some(price_to_beat) = price_to_beat(auction_model, listing_model, &listing_bids, user_id) else {
+        return Ok(());
+    };
+
+    let required = if is_reverse(auction_model) {
+        price_to_beat - bid_increment
+    } else {
+        price_to_beat + bid_increment
+    };
+
+    if is_better_offer(auction_model, required, amount) {
+        return Err(ApiResponse::new(400, response(
             json!({
-                "name": row.name,
-                "listing_title": row.listing_title,
-                "amount": row.amount,
-                "created_at": row.created_at,
+                "error": format!(
+                    "Your bid must be {} {} to beat the current best offer",
+                    if is_reverse(auction_model) { "at most" } else { "at least" },
+                    required
+                ),
+                "required": required,
             })
-        })
-        .collect::<Vec<_>>();
+        )))
+    }
 
-    Ok(ApiResponse::new(200, response(
-        json!({
-            "bids": bids,
-            "message": "Bids fetched successfully".to_string()
-        })
-    )))
+    Ok(())
 }
 
-#[get("/bids/active")]
-pub async fn get_active_bids(
-    app_state: web::Data<AppState>,
-) -> Result<ApiResponse, ApiResponse> {
-    let now = Utc::now().naive_utc();
+/// How a bid reached the platform. Bids staff enter for someone else record
+/// the staff member, the channel and a note; absentee bids also carry the
+/// bidder's written maximum.
+pub(crate) struct BidEntry {
+    pub(crate) channel: BidChannel,
+    pub(crate) placed_by_id: Option<i32>,
+    pub(crate) note: Option<String>,
+    pub(crate) max_amount: Option<Decimal>,
+}
 
-    let active_listing_ids = entity::auctions::Entity::find()
-        .inner_join(entity::listings::Entity)
-        .filter(
-            Condition::all()
-                .add(entity::auctions::Column::StartTime.lte(now))
-                .add(entity::auctions::Column::EndTime.gte(now))
-        )
-        .select_only()
-        .column(entity::listings::Column::Id)
-        .into_tuple::<i32>()
-        .all(&app_state.db)
+impl BidEntry {
+    pub(crate) fn online() -> Self {
+        Self {
+            channel: BidChannel::Online,
+            placed_by_id: None,
+            note: None,
+            max_amount: None,
+        }
+    }
+}
+
+/// Validates and stores a bid. Online bids, bids staff place for a bidder
+/// and floor bids entered from the auctioneer's console all come through
+/// here. In an open auction any absentee bids the new bid has overtaken are
+/// raised straight after it; they follow the new bid in the returned list.
+pub(crate) async fn place_bid(
+    app_state: &AppState,
+    bid_data: &BidData,
+    bid_entry: BidEntry,
+) -> Result<(entity::auctions::Model, Vec<entity::bids::Model>), ApiResponse> {
+    if let Err(err) = bid_data.validate() {
+        return Err(ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        )));
+    }
+
+    let (listing_model, auction_model) = find_listing_with_auction(app_state, bid_data.listing_id).await?;
+
+    if bidding_closes_at(&auction_model) < Utc::now().naive_utc() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Bidding is closed. The auction has already ended.".to_string()
+            })
+        )))
+    }
+
+    if auction_model.reveal_end_time.is_some() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "This auction only accepts sealed commitments".to_string()
+            })
+        )))
+    }
+
+    if is_dutch(&auction_model) {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Bids on a Dutch auction are placed by accepting the current price".to_string()
+            })
+        )))
+    }
+
+    if auction_model.format == AuctionFormat::DoubleAuction {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Listings in a double auction are traded through the order book".to_string()
+            })
+        )))
+    }
+
+    check_registered(&app_state.db, &auction_model, bid_data.user_id).await?;
+    check_verification(&app_state.db, &auction_model, &listing_model, bid_data.user_id).await?;
+
+    check_quantity(&listing_model, bid_data.quantity)?;
+
+    // The limit row stays locked until the bid is stored so concurrent bids cannot both squeeze under it
+    let txn = app_state.db.begin().await.map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    // Bids on the listing queue behind each other and behind a buy-now sale
+    let listing_model = entity::listings::Entity::find_by_id(listing_model.id)
+        .lock_exclusive()
+        .one(&txn)
         .await
         .map_err(|err| {
             ApiResponse::new(500, response(
@@ -178,66 +292,74 @@ pub async fn get_active_bids(
                     "error": err.to_string()
                 })
             ))
-        })?;
-
-    let bids = entity::bids::Entity::find()
-        .filter(entity::bids::Column::DeletedAt.is_null())
-        .filter(entity::bids::Column::ListingId.is_in(active_listing_ids))
-        .inner_join(entity::listings::Entity)
-        .inner_join(entity::users::Entity)
-        .select_only()
-        .column(entity::users::Column::Name)
-        .column_as(entity::listings::Column::Title, "listing_title")
-        .column(entity::bids::Column::Amount)
-        .column(entity::bids::Column::CreatedAt)
-        .into_model::<BidGetResult>()
-        .all(&app_state.db)
-        .await
-        .map_err(|err| {
-            ApiResponse::new(500, response(
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
                 json!({
-                    "error": err.to_string()
+                    "error": "Listing not found".to_string()
                 })
             ))
-        })?
-        .into_iter()
-        .map(|row| {
+        })?;
+
+    // In a live auction only the lot on the block takes bids
+    if auction_model.live && (listing_model.opened_at.is_none() || listing_model.closed_at.is_some()) {
+        return Err(ApiResponse::new(400, response(
             json!({
-                "name": row.name,
-                "listing_title": row.listing_title,
-                "amount": row.amount,
-                "created_at": row.created_at,
+                "error": "This lot is not on the block".to_string()
             })
-        })
-        .collect::<Vec<_>>();
-    
-    Ok(ApiResponse::new(200, response(
-        json!({
-            "bids": bids,
-            "message": "Bids for active auctions fetched successfully".to_string()
-        })
-    )))
-}
+        )))
+    }
 
-#[get("/bids/listings/get/{id}")]
-pub async fn get_all_listings_bids(
-    path: web::Path<i32>,
-    app_state: web::Data<AppState>,
-) -> Result<ApiResponse, ApiResponse> {
-    let listing_id = path.into_inner();
-    
-    let bids = entity::bids::Entity::find()
-        .filter(entity::bids::Column::DeletedAt.is_null())
-        .filter(entity::listings::Column::Id.eq(listing_id))
-        .inner_join(entity::listings::Entity)
-        .inner_join(entity::users::Entity)
-        .select_only()
-        .column(entity::users::Column::Name)
-        .column_as(entity::listings::Column::Title, "listing_title")
-        .column(entity::bids::Column::Amount)
-        .column(entity::bids::Column::CreatedAt)
-        .into_model::<BidGetResult>()
-        .all(&app_state.db)
+    if listing_model.closed_at.is_some() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "This listing has already been sold".to_string()
+            })
+        )))
+    }
+
+    let open_outcry = auction_model.format == AuctionFormat::Open;
+
+    // An absentee bid in an open auction starts only as high as it needs to
+    // and is raised from there; elsewhere it is simply a bid at the maximum
+    let amount = match bid_entry.max_amount.filter(|_| open_outcry) {
+        Some(max_amount) => {
+            let listing_bids = entity::bids::Entity::find()
+                .filter(entity::bids::Column::DeletedAt.is_null())
+                .filter(entity::bids::Column::ListingId.eq(listing_model.id))
+                .all(&txn)
+                .await
+                .map_err(|err| {
+                    ApiResponse::new(500, response(
+                        json!({
+                            "error": err.to_string()
+                        })
+                    ))
+                })?;
+
+            absentee_amount(&auction_model, &listing_model, &listing_bids, bid_data.user_id, max_amount)
+        }
+        None => bid_data.amount,
+    };
+
+    if let Some(bid_increment) = auction_model.bid_increment.filter(|_| open_outcry) {
+        check_bid_increment(&txn, &auction_model, &listing_model, bid_data.user_id, amount, bid_increment).await?;
+    }
+
+    check_bid_within_limit(&txn, &auction_model, bid_data.user_id, bid_data.listing_id, amount, bid_data.quantity).await?;
+
+    let bid_model = entity::bids::ActiveModel {
+        listing_id: Set(bid_data.listing_id),
+        user_id: Set(bid_data.user_id),
+        amount: Set(amount),
+        quantity: Set(bid_data.quantity),
+        channel: Set(bid_entry.channel),
+        placed_by_id: Set(bid_entry.placed_by_id),
+        note: Set(bid_entry.note),
+        max_amount: Set(bid_entry.max_amount),
+        ..Default::default()
+    }
+        .insert(&txn)
         .await
         .map_err(|err| {
             ApiResponse::new(500, response(
@@ -245,43 +367,440 @@ pub async fn get_all_listings_bids(
                     "error": err.to_string()
                 })
             ))
-        })?
-        .into_iter()
-        .map(|row| {
+        })?;
+
+    let mut bid_models = vec![bid_model];
+    if open_outcry {
+        bid_models.extend(execute_absentee_bids(&txn, &auction_model, &listing_model).await?);
+    }
+
+    txn.commit().await.map_err(|err| {
+        ApiResponse::new(500, response(
             json!({
-                "name": row.name,
-                "listing_title": row.listing_title,
-                "amount": row.amount,
-                "created_at": row.created_at,
+                "error": err.to_string()
             })
-        })
-        .collect::<Vec<_>>();
+        ))
+    })?;
 
-    Ok(ApiResponse::new(200, response(
-        json!({
-            "bids": bids,
-            "message": "Bids fetched successfully".to_string()
-        })
-    )))
+    Ok((auction_model, bid_models))
 }
 
-#[get("/bids/listings/active/{id}")]
-pub async fn get_active_listings_bids(
-    path: web::Path<i32>,
-    app_state: web::Data<AppState>,
-) -> Result<ApiResponse, ApiResponse> {
-    let listing_id = path.into_inner();
+/// The auctioneer and the room see bids in a live auction as they arrive.
+pub(crate) async fn publish_live_bids(
+    app_state: &AppState,
+    auction_model: &entity::auctions::Model,
+    bid_models: &[entity::bids::Model],
+) -> Result<(), ApiResponse> {
+    if auction_model.live {
+        let numbers = bidder_numbers(&app_state.db, vec![auction_model.id]).await?;
 
-    let now = Utc::now().naive_utc();
+        for bid_model in bid_models {
+            let bidder = bidder_label(&numbers, auction_model.id, bid_model.user_id);
+            app_state.live_feed.publish(auction_model.id, live_bid_json(bid_model, bidder));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct OnlineBidData {
+    listing_id: i32,
+    amount: Decimal,
+    #[serde(default = "default_quantity")]
+    quantity: i32,
+}
+
+#[post("/bid/create")]
+pub async fn create_bid(
+    online_bid_data: web::Json<OnlineBidData>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let bid_data = BidData {
+        listing_id: online_bid_data.listing_id,
+        user_id: session_user_id(&session)?,
+        amount: online_bid_data.amount,
+        quantity: online_bid_data.quantity,
+    };
+
+    let (auction_model, bid_models) = place_bid(&app_state, &bid_data, BidEntry::online()).await?;
+
+    publish_live_bids(&app_state, &auction_model, &bid_models).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "message": "Bid created successfully".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+struct StaffBidData {
+    listing_id: i32,
+    user_id: i32,
+    /// The bid itself, or for an absentee bid the bidder's written maximum.
+    amount: Decimal,
+    #[serde(default = "default_quantity")]
+    quantity: i32,
+    channel: BidChannel,
+    note: Option<String>,
+}
+
+/// Places a bid on behalf of a bidder who sent a written maximum, phoned in
+/// or is bidding in the room. Absentee maximums in open auctions are bid up
+/// only as far as they need to lead by one increment, never beyond the maximum.
+#[post("/bid/staff")]
+pub async fn create_staff_bid(
+    staff_bid_data: web::Json<StaffBidData>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    if staff_bid_data.channel == BidChannel::Online {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Staff bids are absentee, phone or floor bids".to_string()
+            })
+        )));
+    }
+
+    let staff_model = require_staff(&app_state.db, &session).await?;
+
+    let bid_data = BidData {
+        listing_id: staff_bid_data.listing_id,
+        user_id: staff_bid_data.user_id,
+        amount: staff_bid_data.amount,
+        quantity: staff_bid_data.quantity,
+    };
+
+    let bid_entry = BidEntry {
+        channel: staff_bid_data.channel.clone(),
+        placed_by_id: Some(staff_model.id),
+        note: staff_bid_data.note.clone().filter(|note| !note.trim().is_empty()),
+        max_amount: (staff_bid_data.channel == BidChannel::Absentee).then_some(staff_bid_data.amount),
+    };
+
+    let (auction_model, bid_models) = place_bid(&app_state, &bid_data, bid_entry).await?;
+
+    publish_live_bids(&app_state, &auction_model, &bid_models).await?;
+
+    let bid_model = &bid_models[0];
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "bid_id": bid_model.id,
+            "amount": bid_model.amount,
+            "max_amount": bid_model.max_amount,
+            "channel": bid_model.channel,
+            "message": "Bid placed on behalf of the bidder".to_string()
+        })
+    )))
+}
+
+/// Works out a listing's standing after a bid has been taken back and tells
+/// everyone watching the auction, unless its bids are still sealed.
+async fn publish_withdrawal(
+    app_state: &AppState,
+    auction_model: &entity::auctions::Model,
+    listing_model: &entity::listings::Model,
+    bid_model: &entity::bids::Model,
+    action: BidAuditAction,
+) -> Result<(), ApiResponse> {
+    if bids_hidden(auction_model, Utc::now().naive_utc()) {
+        return Ok(());
+    }
+
+    let listing_bids = entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .filter(entity::bids::Column::ListingId.eq(listing_model.id))
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let numbers = bidder_numbers(&app_state.db, vec![auction_model.id]).await?;
+    let leaders = allocate(auction_model, listing_model, &listing_bids)
+        .iter()
+        .map(|award| json!({
+            "bidder": bidder_label(&numbers, auction_model.id, award.user_id),
+            "quantity": award.quantity,
+            "unit_price": award.unit_price,
+        }))
+        .collect::<Vec<_>>();
+
+    app_state.live_feed.publish(auction_model.id, json!({
+        "type": "bid_withdrawn",
+        "auction_id": auction_model.id,
+        "listing_id": listing_model.id,
+        "bid_id": bid_model.id,
+        "action": action,
+        "best_offer": best_offer(auction_model, listing_model, &listing_bids),
+        "leaders": leaders,
+    }));
+
+    Ok(())
+}
+
+/// Lets the signed-in bidder take back a mistaken bid of their own. Their
+/// previous bid on the listing, if any, stands again.
+#[post("/bid/retract/{id}")]
+pub async fn retract_bid(
+    path: web::Path<i32>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_id = session_user_id(&session)?;
+
+    let (auction_model, listing_model, bid_model) =
+        withdraw_bid(&app_state.db, path.into_inner(), BidAuditAction::Retracted, user_id, None).await?;
+
+    publish_withdrawal(&app_state, &auction_model, &listing_model, &bid_model, BidAuditAction::Retracted).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "bid_id": bid_model.id,
+            "message": "Bid retracted successfully".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+struct VoidBidData {
+    reason: String,
+}
+
+#[post("/bid/void/{id}")]
+pub async fn void_bid(
+    path: web::Path<i32>,
+    void_data: web::Json<VoidBidData>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    if void_data.reason.trim().is_empty() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "A reason is required to void a bid".to_string()
+            })
+        )));
+    }
+
+    let admin_model = require_admin(&app_state.db, &session).await?;
+
+    let (auction_model, listing_model, bid_model) = withdraw_bid(
+        &app_state.db,
+        path.into_inner(),
+        BidAuditAction::Voided,
+        admin_model.id,
+        Some(void_data.reason.trim().to_string()),
+    )
+    .await?;
+
+    publish_withdrawal(&app_state, &auction_model, &listing_model, &bid_model, BidAuditAction::Voided).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "bid_id": bid_model.id,
+            "message": "Bid voided successfully".to_string()
+        })
+    )))
+}
+
+/// Every bid taken back on a listing, with who took it back and why.
+#[get("/bids/audit/{listing_id}")]
+pub async fn get_listing_bid_audits(
+    path: web::Path<i32>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let listing_id = path.into_inner();
+
+    require_staff(&app_state.db, &session).await?;
+
+    let audits = entity::bid_audits::Entity::find()
+        .find_also_related(entity::bids::Entity)
+        .filter(entity::bid_audits::Column::DeletedAt.is_null())
+        .filter(entity::bids::Column::ListingId.eq(listing_id))
+        .order_by_asc(entity::bid_audits::Column::CreatedAt)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .into_iter()
+        .filter_map(|(audit, bid)| {
+            let bid = bid?;
+
+            Some(json!({
+                "id": audit.id,
+                "bid_id": bid.id,
+                "user_id": bid.user_id,
+                "amount": bid.amount,
+                "quantity": bid.quantity,
+                "channel": bid.channel,
+                "placed_at": bid.created_at,
+                "action": audit.action,
+                "actor_id": audit.actor_id,
+                "reason": audit.reason,
+                "created_at": audit.created_at,
+            }))
+        })
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "audits": audits,
+            "message": "Bid audit fetched successfully".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, FromQueryResult)]
+struct BidGetResult {
+    user_id: i32,
+    auction_id: i32,
+    name: String,
+    listing_title: String,
+    amount: Decimal,
+    quantity: i32,
+    channel: BidChannel,
+    created_at: NaiveDateTime,
+}
+
+/// Public bid history names each bidder by their number in the auction and
+/// marks the signed-in user's own bids. A signed-in admin also sees who bid.
+async fn bid_history_json(
+    app_state: &AppState,
+    session: &Session,
+    rows: Vec<BidGetResult>,
+) -> Result<Vec<Value>, ApiResponse> {
+    let viewer = match session_user_id(session) {
+        Ok(user_id) => entity::users::Entity::find_by_id(user_id)
+            .filter(entity::users::Column::DeletedAt.is_null())
+            .one(&app_state.db)
+            .await
+            .map_err(|err| {
+                ApiResponse::new(500, response(
+                    json!({
+                        "error": err.to_string()
+                    })
+                ))
+            })?,
+        Err(_) => None,
+    };
+
+    let viewer_id = viewer.as_ref().map(|user| user.id);
+    let show_identities = viewer.is_some_and(|user| user.role == UserRole::Admin);
+
+    let mut auction_ids = rows.iter().map(|row| row.auction_id).collect::<Vec<_>>();
+    auction_ids.sort_unstable();
+    auction_ids.dedup();
+    let numbers = bidder_numbers(&app_state.db, auction_ids).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let mut bid = json!({
+                "bidder": bidder_label(&numbers, row.auction_id, row.user_id),
+                "is_own": viewer_id == Some(row.user_id),
+                "listing_title": row.listing_title,
+                "amount": row.amount,
+                "quantity": row.quantity,
+                "channel": row.channel,
+                "created_at": row.created_at,
+            });
+
+            if show_identities {
+                bid["user_id"] = json!(row.user_id);
+                bid["name"] = json!(row.name);
+            }
+
+            bid
+        })
+        .collect())
+}
+
+#[get("/bids/get")]
+pub async fn get_all_bids(
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let now = Utc::now().naive_utc();
+
+    let bids = entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .inner_join(entity::listings::Entity)
+        .join(JoinType::InnerJoin, entity::listings::Relation::Auctions.def())
+        .inner_join(entity::users::Entity)
+        // Sealed auctions only show their bids once they have closed, or for
+        // commit-reveal ones once the reveal window has
+        .filter(
+            Condition::any()
+                .add(entity::auctions::Column::Format.is_not_in([AuctionFormat::SealedFirstPrice, AuctionFormat::SealedSecondPrice]))
+                .add(
+                    Condition::all()
+                        .add(entity::auctions::Column::RevealEndTime.is_null())
+                        .add(entity::auctions::Column::EndTime.lt(now))
+                )
+                .add(entity::auctions::Column::RevealEndTime.lt(now))
+        )
+        .select_only()
+        .column(entity::bids::Column::UserId)
+        .column(entity::listings::Column::AuctionId)
+        .column(entity::users::Column::Name)
+        .column_as(entity::listings::Column::Title, "listing_title")
+        .column(entity::bids::Column::Amount)
+        .column(entity::bids::Column::Quantity)
+        .column(entity::bids::Column::Channel)
+        .column(entity::bids::Column::CreatedAt)
+        .into_model::<BidGetResult>()
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let bids = bid_history_json(&app_state, &session, bids).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "bids": bids,
+            "message": "Bids fetched successfully".to_string()
+        })
+    )))
+}
+
+#[get("/bids/active")]
+pub async fn get_active_bids(
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let now = Utc::now().naive_utc();
 
     let active_listing_ids = entity::auctions::Entity::find()
         .inner_join(entity::listings::Entity)
         .filter(
             Condition::all()
                 .add(entity::auctions::Column::StartTime.lte(now))
-                .add(entity::auctions::Column::EndTime.gte(now))
+                .add(
+                    Condition::any()
+                        .add(entity::auctions::Column::EndTime.gte(now))
+                        .add(entity::auctions::Column::CandleEndTime.gte(now))
+                )
+                .add(entity::auctions::Column::Format.is_not_in([AuctionFormat::SealedFirstPrice, AuctionFormat::SealedSecondPrice]))
         )
-        .filter(entity::listings::Column::Id.eq(listing_id))
         .select_only()
         .column(entity::listings::Column::Id)
         .into_tuple::<i32>()
@@ -301,9 +820,13 @@ pub async fn get_active_listings_bids(
         .inner_join(entity::listings::Entity)
         .inner_join(entity::users::Entity)
         .select_only()
+        .column(entity::bids::Column::UserId)
+        .column(entity::listings::Column::AuctionId)
         .column(entity::users::Column::Name)
         .column_as(entity::listings::Column::Title, "listing_title")
         .column(entity::bids::Column::Amount)
+        .column(entity::bids::Column::Quantity)
+        .column(entity::bids::Column::Channel)
         .column(entity::bids::Column::CreatedAt)
         .into_model::<BidGetResult>()
         .all(&app_state.db)
@@ -314,17 +837,9 @@ pub async fn get_active_listings_bids(
                     "error": err.to_string()
                 })
             ))
-        })?
-        .into_iter()
-        .map(|row| {
-            json!({
-                "name": row.name,
-                "listing_title": row.listing_title,
-                "amount": row.amount,
-                "created_at": row.created_at,
-            })
-        })
-        .collect::<Vec<_>>();
+        })?;
+
+    let bids = bid_history_json(&app_state, &session, bids).await?;
     
     Ok(ApiResponse::new(200, response(
         json!({
@@ -334,26 +849,31 @@ pub async fn get_active_listings_bids(
     )))
 }
 
-#[get("/bids/user/{id}/{listing_id}")]
-pub async fn get_all_user_bids(
+#[get("/bids/listings/get/{id}")]
+pub async fn get_all_listings_bids(
+    path: web::Path<i32>,
     app_state: web::Data<AppState>,
-    path: web::Path<(i32, i32)>,
+    session: Session,
 ) -> Result<ApiResponse, ApiResponse> {
-    let (user_id, listing_id) = path.into_inner();
+    let listing_id = path.into_inner();
+
+    ensure_bids_visible(&app_state, listing_id).await?;
     
     let bids = entity::bids::Entity::find()
         .filter(entity::bids::Column::DeletedAt.is_null())
-        .filter(entity::bids::Column::UserId.eq(user_id))
-        .filter(entity::bids::Column::ListingId.eq(listing_id))
+        .filter(entity::listings::Column::Id.eq(listing_id))
         .inner_join(entity::listings::Entity)
-        .order_by_asc(entity::bids::Column::CreatedAt)
+        .inner_join(entity::users::Entity)
         .select_only()
-        .column(entity::bids::Column::Id)
+        .column(entity::bids::Column::UserId)
+        .column(entity::listings::Column::AuctionId)
+        .column(entity::users::Column::Name)
         .column_as(entity::listings::Column::Title, "listing_title")
-        .column(entity::listings::Column::Description)
         .column(entity::bids::Column::Amount)
+        .column(entity::bids::Column::Quantity)
+        .column(entity::bids::Column::Channel)
         .column(entity::bids::Column::CreatedAt)
-        .into_tuple::<(i32, String, String, Decimal, NaiveDateTime)>()
+        .into_model::<BidGetResult>()
         .all(&app_state.db)
         .await
         .map_err(|err| {
@@ -362,23 +882,680 @@ pub async fn get_all_user_bids(
                     "error": err.to_string()
                 })
             ))
-        })?
-        .into_iter()
-        .map(|(id, listing_title, description, amount, created_at)| {
-            json!({
-                "id": id,
-                "listing_title": listing_title,
-                "description": description,
-                "amount": amount,
-                "created_at": created_at,
-            })
-        })
-        .collect::<Vec<_>>();
+        })?;
+
+    let bids = bid_history_json(&app_state, &session, bids).await?;
 
     Ok(ApiResponse::new(200, response(
         json!({
             "bids": bids,
-            "message": "All your bids fetched successfully"
+            "message": "Bids fetched successfully".to_string()
         })
     )))
-}
\ No newline at end of file
+}
+
+#[get("/bids/listings/active/{id}")]
+pub async fn get_active_listings_bids(
+    path: web::Path<i32>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let listing_id = path.into_inner();
+
+    ensure_bids_visible(&app_state, listing_id).await?;
+
+    let now = Utc::now().naive_utc();
+
+    let active_listing_ids = entity::auctions::Entity::find()
+        .inner_join(entity::listings::Entity)
+        .filter(
+            Condition::all()
+                .add(entity::auctions::Column::StartTime.lte(now))
+                .add(
+                    Condition::any()
+                        .add(entity::auctions::Column::EndTime.gte(now))
+                        .add(entity::auctions::Column::CandleEndTime.gte(now))
+                )
+        )
+        .filter(entity::listings::Column::Id.eq(listing_id))
+        .select_only()
+        .column(entity::listings::Column::Id)
+        .into_tuple::<i32>()
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let bids = entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .filter(entity::bids::Column::ListingId.is_in(active_listing_ids))
+        .inner_join(entity::listings::Entity)
+        .inner_join(entity::users::Entity)
+        .select_only()
+        .column(entity::bids::Column::UserId)
+        .column(entity::listings::Column::AuctionId)
+        .column(entity::users::Column::Name)
+        .column_as(entity::listings::Column::Title, "listing_title")
+        .column(entity::bids::Column::Amount)
+        .column(entity::bids::Column::Quantity)
+        .column(entity::bids::Column::Channel)
+        .column(entity::bids::Column::CreatedAt)
+        .into_model::<BidGetResult>()
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let bids = bid_history_json(&app_state, &session, bids).await?;
+    
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "bids": bids,
+            "message": "Bids for active auctions fetched successfully".to_string()
+        })
+    )))
+}
+
+#[get("/bids/user/{id}/{listing_id}")]
+pub async fn get_all_user_bids(
+    app_state: web::Data<AppState>,
+    path: web::Path<(i32, i32)>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let (user_id, listing_id) = path.into_inner();
+    // An absentee maximum is the bidder's secret, shown to nobody else
+    let is_owner = session_user_id(&session).is_ok_and(|viewer_id| viewer_id == user_id);
+
+    let auction_model = entity::auctions::Entity::find()
+        .inner_join(entity::listings::Entity)
+        .filter(entity::listings::Column::Id.eq(listing_id))
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    // Sealed amounts stay with their bidder until bidding closes
+    let amounts_hidden = !is_owner && auction_model.is_some_and(|auction| bids_hidden(&auction, Utc::now().naive_utc()));
+
+    let bids = entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .filter(entity::bids::Column::UserId.eq(user_id))
+        .filter(entity::bids::Column::ListingId.eq(listing_id))
+        .inner_join(entity::listings::Entity)
+        .order_by_asc(entity::bids::Column::CreatedAt)
+        .select_only()
+        .column(entity::bids::Column::Id)
+        .column_as(entity::listings::Column::Title, "listing_title")
+        .column(entity::listings::Column::Description)
+        .column(entity::bids::Column::Amount)
+        .column(entity::bids::Column::Quantity)
+        .column(entity::bids::Column::Channel)
+        .column(entity::bids::Column::MaxAmount)
+        .column(entity::bids::Column::CreatedAt)
+        .into_tuple::<(i32, String, Option<String>, Decimal, i32, BidChannel, Option<Decimal>, NaiveDateTime)>()
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .into_iter()
+        .map(|(id, listing_title, description, amount, quantity, channel, max_amount, created_at)| {
+            json!({
+                "id": id,
+                "listing_title": listing_title,
+                "description": description,
+                "amount": (!amounts_hidden).then_some(amount),
+                "quantity": quantity,
+                "channel": channel,
+                "max_amount": max_amount.filter(|_| is_owner),
+                "created_at": created_at,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "bids": bids,
+            "message": "All your bids fetched successfully"
+        })
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BidCommitmentData {
+    listing_id: i32,
+    /// Hex SHA-256 of the bid, see `commitment_hash`.
+    commitment: String,
+}
+
+impl BidCommitmentData {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.listing_id <= 0 {
+            return Err("Invalid Listing ID".to_string());
+        }
+
+        if !is_valid_commitment(&self.commitment) {
+            return Err("Commitment must be a hex encoded SHA-256 hash".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[post("/bid/commit")]
+pub async fn commit_bid(
+    commitment_data: web::Json<BidCommitmentData>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_id = session_user_id(&session)?;
+
+    if let Err(err) = commitment_data.validate() {
+        return Err(ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        )));
+    }
+
+    let (listing_model, auction_model) = find_listing_with_auction(&app_state, commitment_data.listing_id).await?;
+
+    if auction_model.reveal_end_time.is_none() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "This auction does not take sealed commitments".to_string()
+            })
+        )))
+    }
+
+    check_registered(&app_state.db, &auction_model, user_id).await?;
+    check_verification(&app_state.db, &auction_model, &listing_model, user_id).await?;
+
+    let now = Utc::now().naive_utc();
+
+    if auction_model.end_time < now {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Bidding is closed. The auction has already ended.".to_string()
+            })
+        )))
+    }
+
+    let commitment = commitment_data.commitment.to_lowercase();
+
+    let existing_commitment = entity::bid_commitments::Entity::find()
+        .filter(entity::bid_commitments::Column::DeletedAt.is_null())
+        .filter(entity::bid_commitments::Column::ListingId.eq(commitment_data.listing_id))
+        .filter(entity::bid_commitments::Column::UserId.eq(user_id))
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    // Committing again before the deadline replaces the earlier commitment
+    let commitment_model = match existing_commitment {
+        Some(existing_commitment) => {
+            let mut update_commitment_model: entity::bid_commitments::ActiveModel = existing_commitment.into();
+            update_commitment_model.commitment = Set(commitment);
+            update_commitment_model.committed_at = Set(now);
+            update_commitment_model.updated_at = Set(now);
+            update_commitment_model.update(&app_state.db).await
+        }
+        None => {
+            entity::bid_commitments::ActiveModel {
+                listing_id: Set(commitment_data.listing_id),
+                user_id: Set(user_id),
+                commitment: Set(commitment),
+                committed_at: Set(now),
+                ..Default::default()
+            }
+                .insert(&app_state.db)
+                .await
+        }
+    }
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let receipt = sign_receipt(json!({
+        "kind": "commitment",
+        "listing_id": commitment_model.listing_id,
+        "user_id": commitment_model.user_id,
+        "commitment": commitment_model.commitment,
+        "committed_at": commitment_model.committed_at,
+        "reveal_end_time": auction_model.reveal_end_time,
+    }));
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "receipt": receipt,
+            "message": "Bid commitment recorded successfully".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BidRevealData {
+    listing_id: i32,
+    amount: Decimal,
+    #[serde(default = "default_quantity")]
+    quantity: i32,
+    nonce: String,
+}
+
+impl BidRevealData {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.listing_id <= 0 {
+            return Err("Invalid Listing ID".to_string());
+        }
+
+        if self.amount <= Decimal::ZERO {
+            return Err("Bid amount must be greater than zero".to_string());
+        }
+
+        if !fits_bid_scale(self.amount) {
+            return Err("Bid amount can have at most two decimal places".to_string());
+        }
+
+        if self.quantity <= 0 {
+            return Err("Bid quantity must be at least one".to_string());
+        }
+
+        if self.nonce.is_empty() {
+            return Err("Nonce is required".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[post("/bid/reveal")]
+pub async fn reveal_bid(
+    reveal_data: web::Json<BidRevealData>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_id = session_user_id(&session)?;
+
+    if let Err(err) = reveal_data.validate() {
+        return Err(ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        )));
+    }
+
+    let (listing_model, auction_model) = find_listing_with_auction(&app_state, reveal_data.listing_id).await?;
+
+    let now = Utc::now().naive_utc();
+
+    let reveal_end_time = auction_model.reveal_end_time.ok_or_else(|| {
+        ApiResponse::new(400, response(
+            json!({
+                "error": "This auction does not take sealed commitments".to_string()
+            })
+        ))
+    })?;
+
+    if auction_model.end_time >= now {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Bids can only be revealed once bidding has closed".to_string()
+            })
+        )))
+    }
+
+    if reveal_end_time < now {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "The reveal window for this auction has closed".to_string()
+            })
+        )))
+    }
+
+    let commitment_model = entity::bid_commitments::Entity::find()
+        .filter(entity::bid_commitments::Column::DeletedAt.is_null())
+        .filter(entity::bid_commitments::Column::ListingId.eq(reveal_data.listing_id))
+        .filter(entity::bid_commitments::Column::UserId.eq(user_id))
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "No bid commitment found for this listing".to_string()
+                })
+            ))
+        })?;
+
+    if commitment_model.revealed_at.is_some() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "This bid has already been revealed".to_string()
+            })
+        )))
+    }
+
+    let revealed_hash = commitment_hash(
+        reveal_data.listing_id,
+        user_id,
+        reveal_data.amount,
+        reveal_data.quantity,
+        &reveal_data.nonce,
+    );
+
+    if revealed_hash != commitment_model.commitment {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "The revealed bid does not match its commitment".to_string()
+            })
+        )))
+    }
+
+    check_quantity(&listing_model, reveal_data.quantity)?;
+
+    let txn = app_state.db.begin().await.map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    check_bid_within_limit(&txn, &auction_model, user_id, reveal_data.listing_id, reveal_data.amount, reveal_data.quantity).await?;
+
+    // The bid counts from when it was committed, which settles ties between equal amounts
+    let bid_model = entity::bids::ActiveModel {
+        listing_id: Set(reveal_data.listing_id),
+        user_id: Set(user_id),
+        amount: Set(reveal_data.amount),
+        quantity: Set(reveal_data.quantity),
+        created_at: Set(commitment_model.committed_at),
+        ..Default::default()
+    }
+        .insert(&txn)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let mut update_commitment_model: entity::bid_commitments::ActiveModel = commitment_model.clone().into();
+    update_commitment_model.bid_id = Set(Some(bid_model.id));
+    update_commitment_model.revealed_at = Set(Some(now));
+    update_commitment_model.updated_at = Set(now);
+    update_commitment_model
+        .update(&txn)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    txn.commit().await.map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    let receipt = sign_receipt(json!({
+        "kind": "reveal",
+        "listing_id": bid_model.listing_id,
+        "user_id": bid_model.user_id,
+        "bid_id": bid_model.id,
+        "amount": bid_model.amount,
+        "quantity": bid_model.quantity,
+        "commitment": commitment_model.commitment,
+        "committed_at": commitment_model.committed_at,
+        "revealed_at": now,
+    }));
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "receipt": receipt,
+            "message": "Bid revealed successfully".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ReceiptData {
+    payload: Value,
+    signature: String,
+}
+
+#[post("/bid/receipt/verify")]
+pub async fn verify_bid_receipt(
+    receipt_data: web::Json<ReceiptData>,
+) -> Result<ApiResponse, ApiResponse> {
+    let valid = verify_receipt(&receipt_data.payload, &receipt_data.signature);
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "valid": valid,
+            "message": if valid { "Receipt signature is valid" } else { "Receipt signature is not valid" }
+        })
+    )))
+}
+
+/// The public key receipts are signed with, so bidders can check a receipt
+/// without asking the platform.
+#[get("/bid/receipt/key")]
+pub async fn get_receipt_key() -> Result<ApiResponse, ApiResponse> {
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "algorithm": "ed25519",
+            "public_key": receipt_public_key(),
+            "message": "Receipt public key fetched successfully"
+        })
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AcceptPriceData {
+    listing_id: i32,
+    #[serde(default = "default_quantity")]
+    quantity: i32,
+}
+
+impl AcceptPriceData {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.listing_id <= 0 {
+            return Err("Invalid Listing ID".to_string());
+        }
+
+        if self.quantity <= 0 {
+            return Err("Bid quantity must be at least one".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Buys units off a Dutch auction's clock at whatever the price is right now.
+#[post("/bid/accept")]
+pub async fn accept_price(
+    accept_data: web::Json<AcceptPriceData>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_id = session_user_id(&session)?;
+
+    if let Err(err) = accept_data.validate() {
+        return Err(ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        )));
+    }
+
+    let (listing_model, auction_model) = find_listing_with_auction(&app_state, accept_data.listing_id).await?;
+
+    if !is_dutch(&auction_model) {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Only Dutch auctions take price acceptances".to_string()
+            })
+        )))
+    }
+
+    check_registered(&app_state.db, &auction_model, user_id).await?;
+    check_verification(&app_state.db, &auction_model, &listing_model, user_id).await?;
+
+    let txn = app_state.db.begin().await.map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    // Locking the listing serialises acceptances so the volume cannot be oversold
+    let listing_model = entity::listings::Entity::find_by_id(accept_data.listing_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Listing not found".to_string()
+                })
+            ))
+        })?;
+
+    let now = Utc::now().naive_utc();
+
+    if auction_model.end_time < now {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Bidding is closed. The auction has already ended.".to_string()
+            })
+        )))
+    }
+
+    let price = current_price(&auction_model, &listing_model, now).ok_or_else(|| {
+        ApiResponse::new(400, response(
+            json!({
+                "error": "The auction has not started yet".to_string()
+            })
+        ))
+    })?;
+
+    let accepted = accepted_quantities(&txn, vec![listing_model.id]).await?;
+    let remaining_volume = listing_model.available_volume - accepted.get(&listing_model.id).copied().unwrap_or(0);
+
+    if remaining_volume <= 0 {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "This listing has sold out".to_string()
+            })
+        )))
+    }
+
+    if accept_data.quantity > remaining_volume {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": format!("Only {} {} remain on this listing", remaining_volume, listing_model.unit_of_measure)
+            })
+        )))
+    }
+
+    check_quantity(&listing_model, accept_data.quantity)?;
+
+    check_bid_within_limit(&txn, &auction_model, user_id, accept_data.listing_id, price, accept_data.quantity).await?;
+
+    let bid_model = entity::bids::ActiveModel {
+        listing_id: Set(accept_data.listing_id),
+        user_id: Set(user_id),
+        amount: Set(price),
+        quantity: Set(accept_data.quantity),
+        ..Default::default()
+    }
+        .insert(&txn)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    txn.commit().await.map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    let remaining_volume = remaining_volume - bid_model.quantity;
+
+    app_state.live_feed.publish(auction_model.id, json!({
+        "type": "accepted",
+        "auction_id": auction_model.id,
+        "listing_id": listing_model.id,
+        "price": price,
+        "quantity": bid_model.quantity,
+        "remaining_volume": remaining_volume,
+        "unit_of_measure": listing_model.unit_of_measure,
+    }));
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "price": price,
+            "quantity": bid_model.quantity,
+            "remaining_volume": remaining_volume,
+            "message": "Price accepted successfully".to_string()
+        })
+    )))
+}
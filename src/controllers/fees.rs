@@ -0,0 +1,299 @@
+use actix_session::Session;
+use actix_web::{delete, get, post, put, web};
+use chrono::Utc;
+use entity::sea_orm_active_enums::{FeeParty, FeeType};
+use rust_decimal::Decimal;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    helpers::staff::require_admin,
+    utils::{api_response::ApiResponse, app_state::AppState, json_response::response},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FeeRuleData {
+    auction_id: i32,
+    name: String,
+    fee_type: FeeType,
+    party: Option<FeeParty>,
+    rate: Option<Decimal>,
+    fixed_amount: Option<Decimal>,
+    min_amount: Option<Decimal>,
+}
+
+impl FeeRuleData {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.auction_id <= 0 {
+            return Err("Invalid Auction ID".to_string());
+        }
+
+        if self.name.is_empty() {
+            return Err("Name is required".to_string());
+        }
+
+        if let Some(rate) = self.rate {
+            if rate < Decimal::ZERO || rate > Decimal::ONE_HUNDRED {
+                return Err("Rate must be a percentage between 0 and 100".to_string());
+            }
+        }
+
+        if self.fixed_amount.is_some_and(|amount| amount < Decimal::ZERO) {
+            return Err("Fixed amount cannot be negative".to_string());
+        }
+
+        if self.min_amount.is_some_and(|amount| amount < Decimal::ZERO) {
+            return Err("Minimum amount cannot be negative".to_string());
+        }
+
+        match self.fee_type {
+            FeeType::BuyersPremium | FeeType::SellerCommission | FeeType::Tax if self.rate.is_none() => {
+                Err("Rate is required for this fee type".to_string())
+            }
+            FeeType::LotFee if self.rate.is_none() && self.fixed_amount.is_none() => {
+                Err("Either a rate or a fixed amount is required for lot fees".to_string())
+            }
+            FeeType::BuyersPremium if self.party == Some(FeeParty::Seller) => {
+                Err("Buyer's premium is charged to the buyer".to_string())
+            }
+            FeeType::SellerCommission if self.party == Some(FeeParty::Buyer) => {
+                Err("Seller commission is charged to the seller".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn party(&self) -> FeeParty {
+        match self.fee_type {
+            FeeType::BuyersPremium => FeeParty::Buyer,
+            FeeType::SellerCommission => FeeParty::Seller,
+            _ => self.party.clone().unwrap_or(FeeParty::Buyer),
+        }
+    }
+}
+
+#[post("/fee_rule/create")]
+pub async fn create_fee_rule(
+    app_state: web::Data<AppState>,
+    fee_rule_data: web::Json<FeeRuleData>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    require_admin(&app_state.db, &session).await?;
+
+    if let Err(err) = fee_rule_data.validate() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": err.to_string()
+            })
+        )));
+    }
+
+    entity::auctions::Entity::find_by_id(fee_rule_data.auction_id)
+        .filter(entity::auctions::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Auction not found".to_string()
+                })
+            ))
+        })?;
+
+    let fee_rule_model = entity::fee_rules::ActiveModel {
+        auction_id: Set(fee_rule_data.auction_id),
+        name: Set(fee_rule_data.name.clone()),
+        fee_type: Set(fee_rule_data.fee_type.clone()),
+        party: Set(fee_rule_data.party()),
+        rate: Set(fee_rule_data.rate),
+        fixed_amount: Set(fee_rule_data.fixed_amount),
+        min_amount: Set(fee_rule_data.min_amount.unwrap_or(Decimal::ZERO)),
+        ..Default::default()
+    }
+        .insert(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "fee_rule_id": fee_rule_model.id,
+            "message": "Fee rule created successfully".to_string()
+        })
+    )))
+}
+
+#[put("/fee_rule/update/{id}")]
+pub async fn update_fee_rule(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    fee_rule_data: web::Json<FeeRuleData>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    require_admin(&app_state.db, &session).await?;
+
+    let fee_rule_id = path.into_inner();
+
+    if let Err(err) = fee_rule_data.validate() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": err.to_string()
+            })
+        )));
+    }
+
+    let fee_rule_model = entity::fee_rules::Entity::find_by_id(fee_rule_id)
+        .filter(entity::fee_rules::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Fee rule not found".to_string()
+                })
+            ))
+        })?;
+
+    let mut update_fee_rule_model: entity::fee_rules::ActiveModel = fee_rule_model.into();
+    update_fee_rule_model.auction_id = Set(fee_rule_data.auction_id);
+    update_fee_rule_model.name = Set(fee_rule_data.name.clone());
+    update_fee_rule_model.fee_type = Set(fee_rule_data.fee_type.clone());
+    update_fee_rule_model.party = Set(fee_rule_data.party());
+    update_fee_rule_model.rate = Set(fee_rule_data.rate);
+    update_fee_rule_model.fixed_amount = Set(fee_rule_data.fixed_amount);
+    update_fee_rule_model.min_amount = Set(fee_rule_data.min_amount.unwrap_or(Decimal::ZERO));
+    update_fee_rule_model.updated_at = Set(Utc::now().naive_utc());
+    update_fee_rule_model
+        .update(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "message": "Fee rule updated successfully".to_string()
+        })
+    )))
+}
+
+#[get("/fee_rules/get/{id}")]
+pub async fn get_auction_fee_rules(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> Result<ApiResponse, ApiResponse> {
+    let auction_id = path.into_inner();
+
+    let fee_rules = entity::fee_rules::Entity::find()
+        .filter(entity::fee_rules::Column::DeletedAt.is_null())
+        .filter(entity::fee_rules::Column::AuctionId.eq(auction_id))
+        .order_by_asc(entity::fee_rules::Column::FeeType)
+        .order_by_asc(entity::fee_rules::Column::MinAmount)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .into_iter()
+        .map(|row| {
+            json!({
+                "id": row.id,
+                "auction_id": row.auction_id,
+                "name": row.name,
+                "fee_type": row.fee_type,
+                "party": row.party,
+                "rate": row.rate,
+                "fixed_amount": row.fixed_amount,
+                "min_amount": row.min_amount,
+                "updated_at": row.updated_at,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "fee_rules": fee_rules,
+            "message": "Fee rules fetched successfully".to_string()
+        })
+    )))
+}
+
+#[delete("/fee_rule/delete/{id}")]
+pub async fn delete_fee_rule(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    require_admin(&app_state.db, &session).await?;
+
+    let fee_rule_id = path.into_inner();
+
+    let fee_rule_model = entity::fee_rules::Entity::find_by_id(fee_rule_id)
+        .filter(entity::fee_rules::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Fee rule not found".to_string()
+                })
+            ))
+        })?;
+
+    // Kept so fees already charged under the rule can still be traced to it
+    let now = Utc::now().naive_utc();
+    let mut delete_fee_rule_model: entity::fee_rules::ActiveModel = fee_rule_model.into();
+    delete_fee_rule_model.deleted_at = Set(Some(now));
+    delete_fee_rule_model.updated_at = Set(now);
+    delete_fee_rule_model
+        .update(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "message": "Fee rule deleted successfully".to_string()
+        })
+    )))
+}
@@ -0,0 +1,249 @@
+use actix_session::Session;
+use actix_web::{get, post, put, web};
+use chrono::Utc;
+use entity::sea_orm_active_enums::InvoiceStatus;
+use migration::Expr;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    helpers::{
+        invoices::generate_invoices,
+        ledger::{post_invoice_payment, reverse_journal_entries, PaymentMethod},
+        staff::{require_admin, require_user_or_admin},
+    },
+    utils::{api_response::ApiResponse, app_state::AppState, json_response::response},
+};
+
+fn invoice_json(invoice: &entity::invoices::Model) -> serde_json::Value {
+    json!({
+        "id": invoice.id,
+        "invoice_number": invoice.invoice_number,
+        "user_id": invoice.user_id,
+        "auction_id": invoice.auction_id,
+        "status": invoice.status,
+        "subtotal": invoice.subtotal,
+        "fees_total": invoice.fees_total,
+        "tax_total": invoice.tax_total,
+        "total": invoice.total,
+        "issued_at": invoice.issued_at,
+        "paid_at": invoice.paid_at,
+        "voided_at": invoice.voided_at,
+    })
+}
+
+#[post("/invoices/generate/{id}")]
+pub async fn generate_auction_invoices(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let auction_id = path.into_inner();
+
+    require_admin(&app_state.db, &session).await?;
+
+    let invoices = generate_invoices(&app_state.db, vec![auction_id], true)
+        .await?
+        .iter()
+        .map(invoice_json)
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "invoices": invoices,
+            "message": "Invoices generated successfully".to_string()
+        })
+    )))
+}
+
+#[get("/invoices/get/{id}")]
+pub async fn get_auction_invoices(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let auction_id = path.into_inner();
+
+    require_admin(&app_state.db, &session).await?;
+
+    let invoices = entity::invoices::Entity::find()
+        .filter(entity::invoices::Column::DeletedAt.is_null())
+        .filter(entity::invoices::Column::AuctionId.eq(auction_id))
+        .order_by_asc(entity::invoices::Column::Id)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .iter()
+        .map(invoice_json)
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "invoices": invoices,
+            "message": "Invoices fetched successfully".to_string()
+        })
+    )))
+}
+
+#[get("/invoices/user/{id}")]
+pub async fn get_user_invoices(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_id = path.into_inner();
+
+    require_user_or_admin(&app_state.db, &session, user_id).await?;
+
+    let invoices = entity::invoices::Entity::find()
+        .filter(entity::invoices::Column::DeletedAt.is_null())
+        .filter(entity::invoices::Column::UserId.eq(user_id))
+        .order_by_desc(entity::invoices::Column::IssuedAt)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .iter()
+        .map(invoice_json)
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "invoices": invoices,
+            "message": "Your invoices fetched successfully".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct InvoiceStatusData {
+    status: InvoiceStatus,
+    payment_method: Option<PaymentMethod>,
+}
+
+#[put("/invoice/status/{id}")]
+pub async fn update_invoice_status(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    status_data: web::Json<InvoiceStatusData>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    require_admin(&app_state.db, &session).await?;
+
+    let invoice_id = path.into_inner();
+
+    let txn = app_state.db.begin().await.map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    let invoice_model = entity::invoices::Entity::find_by_id(invoice_id)
+        .filter(entity::invoices::Column::DeletedAt.is_null())
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Invoice not found".to_string()
+                })
+            ))
+        })?;
+
+    if invoice_model.status != InvoiceStatus::Issued || status_data.status == InvoiceStatus::Issued {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Only issued invoices can be marked as paid or void".to_string()
+            })
+        )));
+    }
+
+    let now = Utc::now().naive_utc();
+
+    match status_data.status {
+        InvoiceStatus::Paid => {
+            let payment_method = status_data.payment_method.unwrap_or(PaymentMethod::Bank);
+            post_invoice_payment(&txn, &invoice_model, payment_method).await?;
+        }
+        InvoiceStatus::Void => {
+            reverse_journal_entries(
+                &txn,
+                &format!("invoice:{}", invoice_model.id),
+                &format!("invoice:{}:void", invoice_model.id),
+                &format!("Invoice {} voided", invoice_model.invoice_number),
+            )
+            .await?;
+
+            // Frees the lines' results to be billed on a new invoice
+            entity::invoice_lines::Entity::update_many()
+                .col_expr(entity::invoice_lines::Column::VoidedAt, Expr::value(now))
+                .filter(entity::invoice_lines::Column::InvoiceId.eq(invoice_model.id))
+                .exec(&txn)
+                .await
+                .map_err(|err| {
+                    ApiResponse::new(500, response(
+                        json!({
+                            "error": err.to_string()
+                        })
+                    ))
+                })?;
+        }
+        InvoiceStatus::Issued => {}
+    }
+
+    let mut update_invoice_model: entity::invoices::ActiveModel = invoice_model.into();
+    update_invoice_model.status = Set(status_data.status.clone());
+    match status_data.status {
+        InvoiceStatus::Paid => update_invoice_model.paid_at = Set(Some(now)),
+        InvoiceStatus::Void => update_invoice_model.voided_at = Set(Some(now)),
+        InvoiceStatus::Issued => {}
+    }
+    update_invoice_model.updated_at = Set(now);
+    let invoice_model = update_invoice_model
+        .update(&txn)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    txn.commit().await.map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "invoice": invoice_json(&invoice_model),
+            "message": "Invoice status updated successfully".to_string()
+        })
+    )))
+}
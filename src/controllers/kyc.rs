@@ -0,0 +1,515 @@
+use actix_session::Session;
+use actix_web::{
+    get, http::header::{ContentDisposition, DispositionParam, DispositionType, CONTENT_TYPE}, post, put, web, HttpRequest,
+    HttpResponse,
+};
+use chrono::Utc;
+use entity::sea_orm_active_enums::{KycDocumentType, KycStatus, NotificationCategory, PreferredLanguage, VerificationLevel};
+use futures_util::StreamExt;
+use regex::Regex;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    helpers::{
+        kyc::{document_extension, document_json, kyc_json, read_document, review_json, store_document},
+        notifications::notify_user,
+        staff::{require_admin, require_staff},
+    },
+    utils::{api_response::ApiResponse, app_state::AppState, constants, json_response::response},
+};
+
+async fn find_user(app_state: &AppState, user_id: i32) -> Result<entity::users::Model, ApiResponse> {
+    entity::users::Entity::find_by_id(user_id)
+        .filter(entity::users::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "User not found".to_string()
+                })
+            ))
+        })
+}
+
+async fn find_documents(app_state: &AppState, user_id: i32) -> Result<Vec<entity::kyc_documents::Model>, ApiResponse> {
+    entity::kyc_documents::Entity::find()
+        .filter(entity::kyc_documents::Column::DeletedAt.is_null())
+        .filter(entity::kyc_documents::Column::UserId.eq(user_id))
+        .order_by_asc(entity::kyc_documents::Column::Id)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })
+}
+
+async fn find_reviews(app_state: &AppState, user_id: i32) -> Result<Vec<entity::kyc_reviews::Model>, ApiResponse> {
+    entity::kyc_reviews::Entity::find()
+        .filter(entity::kyc_reviews::Column::UserId.eq(user_id))
+        .order_by_desc(entity::kyc_reviews::Column::Id)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })
+}
+
+#[derive(Debug, Deserialize)]
+struct DocumentQuery {
+    document_type: KycDocumentType,
+    file_name: Option<String>,
+}
+
+/// Uploads an identity document. The file is sent as the raw request body
+/// with its content type; PDFs, JPEGs and PNGs up to
+/// `KYC_MAX_DOCUMENT_BYTES` are accepted.
+#[post("/kyc/document/{user_id}")]
+pub async fn upload_kyc_document(
+    req: HttpRequest,
+    path: web::Path<i32>,
+    query: web::Query<DocumentQuery>,
+    mut payload: web::Payload,
+    app_state: web::Data<AppState>,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_model = find_user(&app_state, path.into_inner()).await?;
+
+    let content_type = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    let Some(extension) = document_extension(&content_type) else {
+        return Err(ApiResponse::new(415, response(
+            json!({
+                "error": "Documents must be PDF, JPEG or PNG files".to_string()
+            })
+        )));
+    };
+
+    let max_bytes = *constants::KYC_MAX_DOCUMENT_BYTES;
+    let mut contents = Vec::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|err| {
+            ApiResponse::new(400, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+        if contents.len() + chunk.len() > max_bytes {
+            return Err(ApiResponse::new(413, response(
+                json!({
+                    "error": format!("Documents cannot be larger than {} bytes", max_bytes)
+                })
+            )));
+        }
+
+        contents.extend_from_slice(&chunk);
+    }
+
+    if contents.is_empty() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "The document is empty".to_string()
+            })
+        )));
+    }
+
+    let size_bytes = contents.len() as i64;
+    let (storage_path, checksum) = store_document(user_model.id, extension, contents).await?;
+
+    let file_name = query
+        .file_name
+        .clone()
+        .filter(|file_name| !file_name.trim().is_empty())
+        .unwrap_or_else(|| format!("document.{}", extension));
+
+    let document_model = entity::kyc_documents::ActiveModel {
+        user_id: Set(user_model.id),
+        document_type: Set(query.document_type.clone()),
+        file_name: Set(file_name),
+        content_type: Set(content_type),
+        storage_path: Set(storage_path),
+        size_bytes: Set(size_bytes),
+        sha256: Set(checksum),
+        ..Default::default()
+    }
+    .insert(&app_state.db)
+    .await
+    .map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    Ok(ApiResponse::new(201, response(
+        json!({
+            "document": document_json(&document_model),
+            "message": "Document uploaded successfully".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SubmissionData {
+    national_id_number: Option<String>,
+}
+
+impl SubmissionData {
+    fn validate_national_id_number(national_id_number: &str) -> bool {
+        let national_id_regex = Regex::new(r"^[A-Za-z0-9]{5,20}$").unwrap();
+        national_id_regex.is_match(national_id_number)
+    }
+}
+
+/// Sends a bidder's national ID number and uploaded documents for review.
+/// A verified bidder keeps their current level while a new submission, such
+/// as one for a higher level, is reviewed.
+#[put("/kyc/submit/{user_id}")]
+pub async fn submit_kyc(
+    path: web::Path<i32>,
+    submission_data: web::Json<SubmissionData>,
+    app_state: web::Data<AppState>,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_model = find_user(&app_state, path.into_inner()).await?;
+
+    if user_model.kyc_status == KycStatus::Pending {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Your details are already awaiting review".to_string()
+            })
+        )));
+    }
+
+    let national_id_number = submission_data
+        .national_id_number
+        .as_ref()
+        .map(|national_id_number| national_id_number.trim().to_uppercase())
+        .or_else(|| user_model.national_id_number.clone())
+        .ok_or_else(|| {
+            ApiResponse::new(400, response(
+                json!({
+                    "error": "National ID number is required".to_string()
+                })
+            ))
+        })?;
+
+    if !SubmissionData::validate_national_id_number(&national_id_number) {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "National ID number is not valid".to_string()
+            })
+        )));
+    }
+
+    let taken = entity::users::Entity::find()
+        .filter(entity::users::Column::NationalIdNumber.eq(national_id_number.clone()))
+        .filter(entity::users::Column::Id.ne(user_model.id))
+        .count(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    if taken > 0 {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "This national ID number is registered to another user".to_string()
+            })
+        )));
+    }
+
+    if find_documents(&app_state, user_model.id).await?.is_empty() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Upload at least one identity document before submitting".to_string()
+            })
+        )));
+    }
+
+    let now = Utc::now().naive_utc();
+    let mut update_user_model: entity::users::ActiveModel = user_model.into();
+    update_user_model.national_id_number = Set(Some(national_id_number));
+    update_user_model.kyc_status = Set(KycStatus::Pending);
+    update_user_model.kyc_submitted_at = Set(Some(now));
+    update_user_model.updated_at = Set(now);
+    let user_model = update_user_model
+        .update(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "kyc": kyc_json(&user_model),
+            "message": "Details submitted for review".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct KycReviewData {
+    status: KycStatus,
+    /// The level granted on approval. On rejection the bidder drops to this
+    /// level, or to none when it is left out.
+    verification_level: Option<VerificationLevel>,
+    note: Option<String>,
+}
+
+/// Approves or rejects a pending submission, recording the decision and the
+/// reviewer's notes.
+#[put("/kyc/review/{user_id}")]
+pub async fn review_kyc(
+    path: web::Path<i32>,
+    review_data: web::Json<KycReviewData>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let verification_level = match (&review_data.status, review_data.verification_level.clone()) {
+        (KycStatus::Verified, Some(VerificationLevel::None) | None) => {
+            return Err(ApiResponse::new(400, response(
+                json!({
+                    "error": "A verification level is required to verify a user".to_string()
+                })
+            )));
+        }
+        (KycStatus::Verified, Some(level)) => level,
+        (KycStatus::Rejected, level) => level.unwrap_or(VerificationLevel::None),
+        _ => {
+            return Err(ApiResponse::new(400, response(
+                json!({
+                    "error": "A submission can only be verified or rejected".to_string()
+                })
+            )));
+        }
+    };
+
+    let admin_model = require_admin(&app_state.db, &session).await?;
+    let user_model = find_user(&app_state, path.into_inner()).await?;
+
+    if user_model.kyc_status != KycStatus::Pending {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "There is no submission awaiting review".to_string()
+            })
+        )));
+    }
+
+    let note = review_data.note.clone().filter(|note| !note.trim().is_empty());
+
+    let review_model = entity::kyc_reviews::ActiveModel {
+        user_id: Set(user_model.id),
+        reviewer_id: Set(admin_model.id),
+        status: Set(review_data.status.clone()),
+        verification_level: Set(verification_level.clone()),
+        note: Set(note.clone()),
+        ..Default::default()
+    }
+    .insert(&app_state.db)
+    .await
+    .map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    let now = Utc::now().naive_utc();
+    let mut update_user_model: entity::users::ActiveModel = user_model.into();
+    update_user_model.kyc_status = Set(review_data.status.clone());
+    update_user_model.verification_level = Set(verification_level);
+    update_user_model.updated_at = Set(now);
+    let user_model = update_user_model
+        .update(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let notified = notify_user(&app_state.db, &user_model, NotificationCategory::Account, |language| {
+        match (&user_model.kyc_status, language) {
+            (KycStatus::Verified, PreferredLanguage::En) => "Your identity has been verified.".to_string(),
+            (KycStatus::Verified, PreferredLanguage::Sw) => "Utambulisho wako umethibitishwa.".to_string(),
+            (_, PreferredLanguage::En) => format!(
+                "We could not verify your identity.{}",
+                note.as_ref().map(|note| format!("\nReason: {}", note)).unwrap_or_default()
+            ),
+            (_, PreferredLanguage::Sw) => format!(
+                "Hatukuweza kuthibitisha utambulisho wako.{}",
+                note.as_ref().map(|note| format!("\nSababu: {}", note)).unwrap_or_default()
+            ),
+        }
+    })
+    .await;
+
+    if let Err(err) = notified {
+        eprintln!("Error sending verification notification: {:?}", err);
+    }
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "kyc": kyc_json(&user_model),
+            "review": review_json(&review_model),
+            "message": "Submission reviewed successfully".to_string()
+        })
+    )))
+}
+
+/// A bidder's own verification status, their documents and the outcome of
+/// their latest review.
+#[get("/kyc/status/{user_id}")]
+pub async fn get_kyc_status(
+    path: web::Path<i32>,
+    app_state: web::Data<AppState>,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_model = find_user(&app_state, path.into_inner()).await?;
+    let documents = find_documents(&app_state, user_model.id).await?;
+    let reviews = find_reviews(&app_state, user_model.id).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "kyc": kyc_json(&user_model),
+            "documents": documents.iter().map(document_json).collect::<Vec<_>>(),
+            "latest_review": reviews.first().map(review_json),
+            "message": "Verification status fetched successfully".to_string()
+        })
+    )))
+}
+
+/// Submissions waiting for review, oldest first.
+#[get("/kyc/queue")]
+pub async fn get_kyc_queue(
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    require_staff(&app_state.db, &session).await?;
+
+    let users = entity::users::Entity::find()
+        .filter(entity::users::Column::DeletedAt.is_null())
+        .filter(entity::users::Column::KycStatus.eq(KycStatus::Pending))
+        .order_by_asc(entity::users::Column::KycSubmittedAt)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .iter()
+        .map(|user| {
+            let mut kyc = kyc_json(user);
+            kyc["name"] = json!(user.name);
+            kyc
+        })
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "submissions": users,
+            "message": "Verification queue fetched successfully".to_string()
+        })
+    )))
+}
+
+/// Everything a reviewer needs about a bidder: their details, documents and
+/// every earlier decision with its notes.
+#[get("/kyc/review/{user_id}")]
+pub async fn get_kyc_review(
+    path: web::Path<i32>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    require_staff(&app_state.db, &session).await?;
+
+    let user_model = find_user(&app_state, path.into_inner()).await?;
+    let documents = find_documents(&app_state, user_model.id).await?;
+    let reviews = find_reviews(&app_state, user_model.id).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "kyc": kyc_json(&user_model),
+            "name": user_model.name,
+            "phone_number": user_model.phone_number,
+            "documents": documents.iter().map(document_json).collect::<Vec<_>>(),
+            "reviews": reviews.iter().map(review_json).collect::<Vec<_>>(),
+            "message": "Verification details fetched successfully".to_string()
+        })
+    )))
+}
+
+#[get("/kyc/document/file/{id}")]
+pub async fn get_kyc_document_file(
+    path: web::Path<i32>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<HttpResponse, ApiResponse> {
+    require_staff(&app_state.db, &session).await?;
+
+    let document_model = entity::kyc_documents::Entity::find_by_id(path.into_inner())
+        .filter(entity::kyc_documents::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Document not found".to_string()
+                })
+            ))
+        })?;
+
+    let contents = read_document(document_model.storage_path.clone()).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(document_model.content_type)
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(document_model.file_name)],
+        })
+        .body(contents))
+}
@@ -0,0 +1,295 @@
+use actix_web::{get, post, web};
+use entity::sea_orm_active_enums::LedgerAccountType;
+use migration::Expr;
+use rust_decimal::Decimal;
+use sea_orm::{
+    ColumnTrait, EntityTrait, FromQueryResult, QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    helpers::ledger::{
+        account_balance, lock_user_account, normal_balance, post_journal_entry, system_account, user_account_balance,
+        Posting, SystemAccount, UserAccount,
+    },
+    utils::{api_response::ApiResponse, app_state::AppState, json_response::response},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct WalletTransactionData {
+    user_id: i32,
+    amount: Decimal,
+    reference: Option<String>,
+}
+
+impl WalletTransactionData {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.user_id <= 0 {
+            return Err("Invalid User ID".to_string());
+        }
+
+        if self.amount <= Decimal::ZERO {
+            return Err("Amount must be greater than zero".to_string());
+        }
+
+        if self.amount.scale() > 2 {
+            return Err("Amount cannot have more than two decimal places".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[post("/wallet/deposit")]
+pub async fn deposit_to_wallet(
+    app_state: web::Data<AppState>,
+    deposit_data: web::Json<WalletTransactionData>,
+) -> Result<ApiResponse, ApiResponse> {
+    if let Err(err) = deposit_data.validate() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": err.to_string()
+            })
+        )));
+    }
+
+    entity::users::Entity::find_by_id(deposit_data.user_id)
+        .filter(entity::users::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "User not found".to_string()
+                })
+            ))
+        })?;
+
+    let txn = app_state.db.begin().await.map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    let wallet = lock_user_account(&txn, deposit_data.user_id, UserAccount::Wallet).await?;
+    let cash = system_account(&txn, SystemAccount::Cash).await?;
+
+    let journal_entry = post_journal_entry(
+        &txn,
+        &format!("wallet:{}:deposit:{}", deposit_data.user_id, deposit_data.reference.clone().unwrap_or_default()),
+        "Wallet deposit",
+        vec![Posting::debit(cash, deposit_data.amount), Posting::credit(wallet, deposit_data.amount)],
+    )
+    .await?;
+
+    let balance = account_balance(&txn, wallet).await?;
+
+    txn.commit().await.map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "journal_entry_id": journal_entry.id,
+            "balance": balance,
+            "message": "Wallet deposit recorded successfully".to_string()
+        })
+    )))
+}
+
+#[post("/wallet/refund")]
+pub async fn refund_from_wallet(
+    app_state: web::Data<AppState>,
+    refund_data: web::Json<WalletTransactionData>,
+) -> Result<ApiResponse, ApiResponse> {
+    if let Err(err) = refund_data.validate() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": err.to_string()
+            })
+        )));
+    }
+
+    let txn = app_state.db.begin().await.map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    let wallet = lock_user_account(&txn, refund_data.user_id, UserAccount::Wallet).await?;
+
+    if account_balance(&txn, wallet).await? < refund_data.amount {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Refund exceeds the wallet balance".to_string()
+            })
+        )));
+    }
+
+    let cash = system_account(&txn, SystemAccount::Cash).await?;
+
+    let journal_entry = post_journal_entry(
+        &txn,
+        &format!("wallet:{}:refund:{}", refund_data.user_id, refund_data.reference.clone().unwrap_or_default()),
+        "Wallet refund",
+        vec![Posting::debit(wallet, refund_data.amount), Posting::credit(cash, refund_data.amount)],
+    )
+    .await?;
+
+    let balance = account_balance(&txn, wallet).await?;
+
+    txn.commit().await.map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "journal_entry_id": journal_entry.id,
+            "balance": balance,
+            "message": "Wallet refund recorded successfully".to_string()
+        })
+    )))
+}
+
+#[get("/wallet/get/{id}")]
+pub async fn get_wallet(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_id = path.into_inner();
+
+    let balance = user_account_balance(&app_state.db, user_id, UserAccount::Wallet).await?;
+    let amount_owed = user_account_balance(&app_state.db, user_id, UserAccount::Receivable).await?;
+    let deposits_held = user_account_balance(&app_state.db, user_id, UserAccount::Deposit).await?;
+
+    let transactions = entity::ledger_postings::Entity::find()
+        .filter(entity::ledger_postings::Column::DeletedAt.is_null())
+        .filter(entity::ledger_accounts::Column::Code.eq(UserAccount::Wallet.code(user_id)))
+        .inner_join(entity::ledger_accounts::Entity)
+        .find_also_related(entity::journal_entries::Entity)
+        .order_by_desc(entity::ledger_postings::Column::Id)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .into_iter()
+        .map(|(posting, journal_entry)| {
+            json!({
+                "journal_entry_id": posting.journal_entry_id,
+                "reference": journal_entry.as_ref().map(|entry| entry.reference.clone()),
+                "description": journal_entry.as_ref().map(|entry| entry.description.clone()),
+                "posted_at": journal_entry.as_ref().map(|entry| entry.posted_at),
+                "amount": posting.credit - posting.debit,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "user_id": user_id,
+            "balance": balance,
+            "amount_owed": amount_owed,
+            "deposits_held": deposits_held,
+            "transactions": transactions,
+            "message": "Wallet fetched successfully".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, FromQueryResult)]
+struct TrialBalanceRow {
+    id: i32,
+    code: String,
+    name: String,
+    account_type: LedgerAccountType,
+    total_debit: Option<Decimal>,
+    total_credit: Option<Decimal>,
+}
+
+#[get("/ledger/trial_balance")]
+pub async fn get_trial_balance(
+    app_state: web::Data<AppState>,
+) -> Result<ApiResponse, ApiResponse> {
+    let rows = entity::ledger_accounts::Entity::find()
+        .filter(entity::ledger_accounts::Column::DeletedAt.is_null())
+        .filter(entity::ledger_postings::Column::DeletedAt.is_null())
+        .inner_join(entity::ledger_postings::Entity)
+        .select_only()
+        .column(entity::ledger_accounts::Column::Id)
+        .column(entity::ledger_accounts::Column::Code)
+        .column(entity::ledger_accounts::Column::Name)
+        .column(entity::ledger_accounts::Column::AccountType)
+        .column_as(Expr::col(entity::ledger_postings::Column::Debit).sum(), "total_debit")
+        .column_as(Expr::col(entity::ledger_postings::Column::Credit).sum(), "total_credit")
+        .group_by(entity::ledger_accounts::Column::Id)
+        .order_by_asc(entity::ledger_accounts::Column::Code)
+        .into_model::<TrialBalanceRow>()
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let mut debit_total = Decimal::ZERO;
+    let mut credit_total = Decimal::ZERO;
+    let accounts = rows
+        .iter()
+        .map(|row| {
+            let total_debit = row.total_debit.unwrap_or_default();
+            let total_credit = row.total_credit.unwrap_or_default();
+            let net = total_debit - total_credit;
+            let (debit, credit) = if net >= Decimal::ZERO { (net, Decimal::ZERO) } else { (Decimal::ZERO, -net) };
+            debit_total += debit;
+            credit_total += credit;
+
+            json!({
+                "account_id": row.id,
+                "code": row.code,
+                "name": row.name,
+                "account_type": row.account_type,
+                "debit": debit,
+                "credit": credit,
+                "balance": normal_balance(&row.account_type, total_debit, total_credit),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "accounts": accounts,
+            "debit_total": debit_total,
+            "credit_total": credit_total,
+            "balanced": debit_total == credit_total,
+            "message": "Trial balance fetched successfully".to_string()
+        })
+    )))
+}
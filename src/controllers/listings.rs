@@ -1,19 +1,50 @@
+use std::collections::HashMap;
+
+use actix_session::Session;
 use actix_web::{delete, get, post, put, web};
 use chrono::{NaiveDateTime, Utc};
 use rust_decimal::Decimal;
 use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, FromQueryResult, QueryFilter, QuerySelect, Set};
+use entity::sea_orm_active_enums::{ClearingRule, NotificationCategory, PreferredLanguage, VerificationLevel};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::utils::{api_response::ApiResponse, app_state::AppState, json_response::response};
+use crate::{
+    helpers::{
+        buy_now::{best_bids, buy_listing_now, buy_now_available, supports_buy_now},
+        kyc::check_verification,
+        notifications::notify_user,
+        registrations::check_registered,
+    },
+    utils::{api_response::ApiResponse, app_state::AppState, constants, json_response::response, session::session_user_id, whatsapp::send_whatsapp_message},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
-struct ListingData {
-    title: String,
-    description: Option<String>,
-    base_price: Decimal,
-    available_volume: Option<i32>,
-    auction_id: i32,
+pub(crate) struct ListingData {
+    pub(crate) title: String,
+    pub(crate) description: Option<String>,
+    pub(crate) base_price: Decimal,
+    pub(crate) available_volume: Option<i32>,
+    pub(crate) clearing_rule: Option<ClearingRule>,
+    pub(crate) unit_of_measure: Option<String>,
+    pub(crate) min_quantity: Option<i32>,
+    pub(crate) max_quantity: Option<i32>,
+    /// Dutch auctions only: the clock starts here and drops by `price_step`
+    /// every `price_step_seconds` until it reaches the base price.
+    pub(crate) start_price: Option<Decimal>,
+    pub(crate) price_step: Option<Decimal>,
+    pub(crate) price_step_seconds: Option<i32>,
+    /// Per-unit price at which the whole listing can be bought outright.
+    pub(crate) buy_now_price: Option<Decimal>,
+    /// Buy-now is withdrawn once the best bid reaches this; without it the
+    /// first bid withdraws it.
+    pub(crate) buy_now_threshold: Option<Decimal>,
+    /// Position in the catalogue; live auctions sell lots in this order.
+    pub(crate) lot_number: Option<i32>,
+    /// Raises the auction's verification requirement for this lot.
+    pub(crate) min_verification_level: Option<VerificationLevel>,
+    pub(crate) auction_id: i32,
+    pub(crate) seller_id: Option<i32>,
 }
 
 impl ListingData {
@@ -26,12 +57,103 @@ impl ListingData {
             return Err("Base price must be greater than zero".to_string());
         }
 
+        if self.available_volume.is_some_and(|available_volume| available_volume <= 0) {
+            return Err("Available volume must be at least one".to_string());
+        }
+
+        if self.unit_of_measure.as_ref().is_some_and(|unit| unit.trim().is_empty()) {
+            return Err("Unit of measure cannot be blank".to_string());
+        }
+
+        if self.min_quantity() <= 0 {
+            return Err("Minimum quantity must be at least one".to_string());
+        }
+
+        if self.min_quantity() > self.available_volume.unwrap_or(1) {
+            return Err("Minimum quantity cannot be more than the available volume".to_string());
+        }
+
+        if self.max_quantity.is_some_and(|max_quantity| max_quantity < self.min_quantity()) {
+            return Err("Maximum quantity cannot be less than the minimum quantity".to_string());
+        }
+
+        match (self.start_price, self.price_step, self.price_step_seconds) {
+            (None, None, None) => {}
+            (Some(start_price), Some(price_step), Some(price_step_seconds)) => {
+                if start_price <= self.base_price {
+                    return Err("Start price must be above the base price".to_string());
+                }
+
+                if price_step <= Decimal::ZERO {
+                    return Err("Price step must be greater than zero".to_string());
+                }
+
+                if price_step_seconds <= 0 {
+                    return Err("Price step interval must be at least one second".to_string());
+                }
+            }
+            _ => return Err("Start price, price step and price step interval must be set together".to_string()),
+        }
+
+        if self.buy_now_price.is_some_and(|buy_now_price| buy_now_price <= Decimal::ZERO) {
+            return Err("Buy now price must be greater than zero".to_string());
+        }
+
+        match (self.buy_now_price, self.buy_now_threshold) {
+            (None, Some(_)) => return Err("Buy now threshold needs a buy now price".to_string()),
+            (Some(buy_now_price), Some(threshold)) if threshold > buy_now_price => {
+                return Err("Buy now threshold cannot be above the buy now price".to_string());
+            }
+            _ => {}
+        }
+
+        if self.lot_number.is_some_and(|lot_number| lot_number <= 0) {
+            return Err("Lot number must be greater than zero".to_string());
+        }
+
         if self.auction_id.to_string().is_empty() {
             return Err("Auction ID is required".to_string());
         }
 
+        if self.seller_id.is_some_and(|seller_id| seller_id <= 0) {
+            return Err("Invalid Seller ID".to_string());
+        }
+
         Ok(())
     }
+
+    /// Base price and bids on the listing are per one of these units.
+    pub fn unit_of_measure(&self) -> String {
+        self.unit_of_measure
+            .as_deref()
+            .map(|unit| unit.trim().to_string())
+            .unwrap_or_else(|| "unit".to_string())
+    }
+
+    pub fn min_quantity(&self) -> i32 {
+        self.min_quantity.unwrap_or(1)
+    }
+
+    /// Copies the listing's details onto a new or existing row. Who the
+    /// seller is depends on the endpoint, so it is left to the caller.
+    pub fn apply_to(&self, listing_model: &mut entity::listings::ActiveModel) {
+        listing_model.title = Set(self.title.clone());
+        listing_model.description = Set(self.description.clone());
+        listing_model.auction_id = Set(self.auction_id);
+        listing_model.base_price = Set(self.base_price);
+        listing_model.available_volume = Set(self.available_volume.unwrap_or(1));
+        listing_model.clearing_rule = Set(self.clearing_rule.clone().unwrap_or(ClearingRule::Discriminatory));
+        listing_model.unit_of_measure = Set(self.unit_of_measure());
+        listing_model.min_quantity = Set(self.min_quantity());
+        listing_model.max_quantity = Set(self.max_quantity);
+        listing_model.start_price = Set(self.start_price);
+        listing_model.price_step = Set(self.price_step);
+        listing_model.price_step_seconds = Set(self.price_step_seconds);
+        listing_model.buy_now_price = Set(self.buy_now_price);
+        listing_model.buy_now_threshold = Set(self.buy_now_threshold);
+        listing_model.lot_number = Set(self.lot_number);
+        listing_model.min_verification_level = Set(self.min_verification_level.clone());
+    }
 }
 
 #[post("/listing/create")]
@@ -47,14 +169,12 @@ pub async fn create_listing(
         )));
     }
 
-    entity::listings::ActiveModel {
-        title: Set(listing_data.title.clone()),
-        description: Set(listing_data.description.clone()),
-        auction_id: Set(listing_data.auction_id.clone()),
-        base_price: Set(listing_data.base_price.clone()),
-        available_volume: Set(listing_data.available_volume.unwrap_or(1)),
+    let mut listing_model = entity::listings::ActiveModel {
+        seller_id: Set(listing_data.seller_id),
         ..Default::default()
-    }
+    };
+    listing_data.apply_to(&mut listing_model);
+    listing_model
         .insert(&app_state.db)
         .await
         .map_err(|err| {
@@ -80,6 +200,14 @@ pub async fn update_listing(
 ) -> Result<ApiResponse, ApiResponse> {
     let listing_id = path.into_inner();
 
+    if let Err(err) = listing_data.validate() {
+        return Err(ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        )));
+    }
+
     let listing_model = entity::listings::Entity::find_by_id(listing_id)
         .one(&app_state.db)
         .await
@@ -99,11 +227,8 @@ pub async fn update_listing(
         })?;
 
     let mut update_listing_model: entity::listings::ActiveModel = listing_model.to_owned().into();
-    update_listing_model.title = Set(listing_data.title.clone());
-    update_listing_model.auction_id = Set(listing_data.auction_id.clone());
-    update_listing_model.description = Set(listing_data.description.clone());
-    update_listing_model.base_price = Set(listing_data.base_price.clone());
-    update_listing_model.available_volume = Set(listing_data.available_volume.unwrap_or(1));
+    listing_data.apply_to(&mut update_listing_model);
+    update_listing_model.seller_id = Set(listing_data.seller_id);
     update_listing_model.updated_at = Set(Utc::now().naive_utc());
     update_listing_model
         .update(&app_state.db)
@@ -129,10 +254,25 @@ struct  AuctionResult {
     start_time: NaiveDateTime,
     end_time: NaiveDateTime,
     id: i32,
+    auction_id: i32,
     title: String,
     description: Option<String>,
     base_price: Decimal,
     available_volume: i32,
+    clearing_rule: ClearingRule,
+    unit_of_measure: String,
+    min_quantity: i32,
+    max_quantity: Option<i32>,
+    start_price: Option<Decimal>,
+    price_step: Option<Decimal>,
+    price_step_seconds: Option<i32>,
+    buy_now_price: Option<Decimal>,
+    buy_now_threshold: Option<Decimal>,
+    lot_number: Option<i32>,
+    min_verification_level: Option<VerificationLevel>,
+    opened_at: Option<NaiveDateTime>,
+    closed_at: Option<NaiveDateTime>,
+    seller_id: Option<i32>,
     updated_at: NaiveDateTime,
 }
 
@@ -143,7 +283,7 @@ pub async fn get_all_auction_listings(
 ) -> Result<ApiResponse, ApiResponse> {
     let auction_id = path.into_inner();
 
-    let listing_model = entity::listings::Entity::find()
+    let rows = entity::listings::Entity::find()
         .inner_join(entity::auctions::Entity)
         .filter(entity::auctions::Column::Id.eq(auction_id))
         .filter(entity::listings::Column::DeletedAt.is_null())
@@ -152,14 +292,41 @@ pub async fn get_all_auction_listings(
         .column(entity::auctions::Column::StartTime)
         .column(entity::auctions::Column::EndTime)
         .column(entity::listings::Column::Id)
+        .column(entity::listings::Column::AuctionId)
         .column(entity::listings::Column::Title)
         .column(entity::listings::Column::Description)
         .column(entity::listings::Column::BasePrice)
         .column(entity::listings::Column::AvailableVolume)
+        .column(entity::listings::Column::ClearingRule)
+        .column(entity::listings::Column::UnitOfMeasure)
+        .column(entity::listings::Column::MinQuantity)
+        .column(entity::listings::Column::MaxQuantity)
+        .column(entity::listings::Column::StartPrice)
+        .column(entity::listings::Column::PriceStep)
+        .column(entity::listings::Column::PriceStepSeconds)
+        .column(entity::listings::Column::BuyNowPrice)
+        .column(entity::listings::Column::BuyNowThreshold)
+        .column(entity::listings::Column::LotNumber)
+        .column(entity::listings::Column::MinVerificationLevel)
+        .column(entity::listings::Column::OpenedAt)
+        .column(entity::listings::Column::ClosedAt)
+        .column(entity::listings::Column::SellerId)
         .column(entity::listings::Column::UpdatedAt)
         .into_model::<AuctionResult>()
         .all(&app_state.db)
         .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let auctions: HashMap<i32, entity::auctions::Model> = entity::auctions::Entity::find()
+        .filter(entity::auctions::Column::Id.is_in(rows.iter().map(|row| row.auction_id).collect::<Vec<_>>()))
+        .all(&app_state.db)
+        .await
         .map_err(|err| {
             ApiResponse::new(500, response(
                 json!({
@@ -167,14 +334,41 @@ pub async fn get_all_auction_listings(
                 })
             ))
         })?
+        .into_iter()
+        .map(|auction| (auction.id, auction))
+        .collect();
+
+    let best_bids = best_bids(&app_state.db, rows.iter().map(|row| row.id).collect()).await?;
+
+    let listing_model = rows
         .into_iter()
         .map(|row| {
+            // A withdrawn offer is hidden rather than shown at a price no
+            // one can buy at any more.
+            let buy_now_available = auctions.get(&row.auction_id).is_some_and(supports_buy_now)
+                && buy_now_available(row.buy_now_price, row.buy_now_threshold, row.closed_at, best_bids.get(&row.id).copied());
+
             json!({
                 "id": row.id,
                 "title": row.title,
                 "description": row.description,
                 "base_price": row.base_price,
                 "available_volume": row.available_volume,
+                "clearing_rule": row.clearing_rule,
+                "unit_of_measure": row.unit_of_measure,
+                "min_quantity": row.min_quantity,
+                "max_quantity": row.max_quantity,
+                "start_price": row.start_price,
+                "price_step": row.price_step,
+                "price_step_seconds": row.price_step_seconds,
+                "buy_now_price": row.buy_now_price.filter(|_| buy_now_available),
+                "buy_now_threshold": row.buy_now_threshold,
+                "buy_now_available": buy_now_available,
+                "lot_number": row.lot_number,
+                "min_verification_level": row.min_verification_level,
+                "opened_at": row.opened_at,
+                "closed_at": row.closed_at,
+                "seller_id": row.seller_id,
                 "auction_name": row.name,
                 "start_time": row.start_time,
                 "end_time": row.end_time,
@@ -195,7 +389,7 @@ pub async fn get_all_auction_listings(
 pub async fn get_all_listings(
     app_state: web::Data<AppState>,
 ) -> Result<ApiResponse, ApiResponse> {
-    let listing_model = entity::listings::Entity::find()
+    let rows = entity::listings::Entity::find()
         .inner_join(entity::auctions::Entity)
         .filter(entity::listings::Column::DeletedAt.is_null())
         .select_only()
@@ -203,14 +397,41 @@ pub async fn get_all_listings(
         .column(entity::auctions::Column::StartTime)
         .column(entity::auctions::Column::EndTime)
         .column(entity::listings::Column::Id)
+        .column(entity::listings::Column::AuctionId)
         .column(entity::listings::Column::Title)
         .column(entity::listings::Column::Description)
         .column(entity::listings::Column::BasePrice)
         .column(entity::listings::Column::AvailableVolume)
+        .column(entity::listings::Column::ClearingRule)
+        .column(entity::listings::Column::UnitOfMeasure)
+        .column(entity::listings::Column::MinQuantity)
+        .column(entity::listings::Column::MaxQuantity)
+        .column(entity::listings::Column::StartPrice)
+        .column(entity::listings::Column::PriceStep)
+        .column(entity::listings::Column::PriceStepSeconds)
+        .column(entity::listings::Column::BuyNowPrice)
+        .column(entity::listings::Column::BuyNowThreshold)
+        .column(entity::listings::Column::LotNumber)
+        .column(entity::listings::Column::MinVerificationLevel)
+        .column(entity::listings::Column::OpenedAt)
+        .column(entity::listings::Column::ClosedAt)
+        .column(entity::listings::Column::SellerId)
         .column(entity::listings::Column::UpdatedAt)
         .into_model::<AuctionResult>()
         .all(&app_state.db)
         .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let auctions: HashMap<i32, entity::auctions::Model> = entity::auctions::Entity::find()
+        .filter(entity::auctions::Column::Id.is_in(rows.iter().map(|row| row.auction_id).collect::<Vec<_>>()))
+        .all(&app_state.db)
+        .await
         .map_err(|err| {
             ApiResponse::new(500, response(
                 json!({
@@ -218,14 +439,41 @@ pub async fn get_all_listings(
                 })
             ))
         })?
+        .into_iter()
+        .map(|auction| (auction.id, auction))
+        .collect();
+
+    let best_bids = best_bids(&app_state.db, rows.iter().map(|row| row.id).collect()).await?;
+
+    let listing_model = rows
         .into_iter()
         .map(|row| {
+            // A withdrawn offer is hidden rather than shown at a price no
+            // one can buy at any more.
+            let buy_now_available = auctions.get(&row.auction_id).is_some_and(supports_buy_now)
+                && buy_now_available(row.buy_now_price, row.buy_now_threshold, row.closed_at, best_bids.get(&row.id).copied());
+
             json!({
                 "id": row.id,
                 "title": row.title,
                 "description": row.description,
                 "base_price": row.base_price,
                 "available_volume": row.available_volume,
+                "clearing_rule": row.clearing_rule,
+                "unit_of_measure": row.unit_of_measure,
+                "min_quantity": row.min_quantity,
+                "max_quantity": row.max_quantity,
+                "start_price": row.start_price,
+                "price_step": row.price_step,
+                "price_step_seconds": row.price_step_seconds,
+                "buy_now_price": row.buy_now_price.filter(|_| buy_now_available),
+                "buy_now_threshold": row.buy_now_threshold,
+                "buy_now_available": buy_now_available,
+                "lot_number": row.lot_number,
+                "min_verification_level": row.min_verification_level,
+                "opened_at": row.opened_at,
+                "closed_at": row.closed_at,
+                "seller_id": row.seller_id,
                 "auction_name": row.name,
                 "start_time": row.start_time,
                 "end_time": row.end_time,
@@ -265,4 +513,147 @@ pub async fn delete_listing(
             "message": "Listing deleted successfully".to_string()
         })
     )))
-}
\ No newline at end of file
+}
+#[post("/listing/buy_now/{id}")]
+pub async fn buy_now(
+    path: web::Path<i32>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_id = session_user_id(&session)?;
+    let listing_id = path.into_inner();
+
+    let (listing_model, auction_model) = entity::listings::Entity::find_by_id(listing_id)
+        .find_also_related(entity::auctions::Entity)
+        .filter(entity::listings::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Listing not found".to_string()
+                })
+            ))
+        })?;
+
+    let auction_model = auction_model.ok_or_else(|| {
+        ApiResponse::new(404, response(
+            json!({
+                "error": "Auction not found".to_string()
+            })
+        ))
+    })?;
+
+    if !supports_buy_now(&auction_model) {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Buy now is not available on this listing".to_string()
+            })
+        )))
+    }
+
+    let now = Utc::now().naive_utc();
+
+    if auction_model.start_time > now || auction_model.end_time < now {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Listings can only be bought during the trading window".to_string()
+            })
+        )))
+    }
+
+    check_registered(&app_state.db, &auction_model, user_id).await?;
+    check_verification(&app_state.db, &auction_model, &listing_model, user_id).await?;
+
+    let (listing_model, auction_result) =
+        buy_listing_now(&app_state.db, &auction_model, listing_model.id, user_id).await?;
+
+    // Everyone who bid on the listing hears that it has gone, along with the platform
+    let bidder_ids = entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .filter(entity::bids::Column::ListingId.eq(listing_model.id))
+        .select_only()
+        .column(entity::bids::Column::UserId)
+        .distinct()
+        .into_tuple::<i32>()
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let participants = entity::users::Entity::find()
+        .filter(entity::users::Column::Id.is_in(bidder_ids))
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let message = format!(
+        "Auction Result:\nAuction ID: {}\nListing: {}\nBought now: {} {} at {} per {}\nBidding on this listing has closed.",
+        auction_model.id,
+        listing_model.title,
+        auction_result.quantity,
+        listing_model.unit_of_measure,
+        auction_result.unit_price,
+        listing_model.unit_of_measure
+    );
+
+    if let Err(err) = send_whatsapp_message(&constants::WHATSAPP_PHONE_NUMBER, &message).await {
+        eprintln!("Error sending buy now notification: {:?}", err);
+    }
+
+    for participant in &participants {
+        let notified = notify_user(&app_state.db, participant, NotificationCategory::Auction, |language| match language {
+            PreferredLanguage::En => message.clone(),
+            PreferredLanguage::Sw => format!(
+                "Matokeo ya Mnada:\nNambari ya Mnada: {}\nBidhaa: {}\nImenunuliwa moja kwa moja: {} {} kwa {} kila {}\nZabuni kwa bidhaa hii zimefungwa.",
+                auction_model.id,
+                listing_model.title,
+                auction_result.quantity,
+                listing_model.unit_of_measure,
+                auction_result.unit_price,
+                listing_model.unit_of_measure
+            ),
+        })
+        .await;
+
+        if let Err(err) = notified {
+            eprintln!("Error sending buy now notification: {:?}", err);
+        }
+    }
+
+    app_state.live_feed.publish(auction_model.id, json!({
+        "type": "buy_now",
+        "auction_id": auction_model.id,
+        "listing_id": listing_model.id,
+        "quantity": auction_result.quantity,
+        "unit_price": auction_result.unit_price,
+    }));
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "auction_result_id": auction_result.id,
+            "quantity": auction_result.quantity,
+            "unit_price": auction_result.unit_price,
+            "amount": auction_result.hammer_price,
+            "message": "Listing bought successfully".to_string()
+        })
+    )))
+}
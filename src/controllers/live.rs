@@ -0,0 +1,311 @@
+use std::pin::pin;
+
+use actix_session::Session;
+use actix_web::{get, rt, web, HttpRequest, HttpResponse};
+use actix_ws::Message;
+use futures_util::{future::{select, Either}, StreamExt};
+use rust_decimal::Decimal;
+use entity::sea_orm_active_enums::BidChannel;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    controllers::bids::{default_quantity, place_bid, publish_live_bids, BidData, BidEntry},
+    helpers::{
+        buy_now::best_bid,
+        dutch::price_snapshot,
+        live_auction::{current_lot, hammer_lot, lot_json, open_next_lot},
+        pseudonyms::{bidder_label, bidder_numbers},
+        staff::require_staff,
+    },
+    utils::{api_response::ApiResponse, app_state::AppState, json_response::response},
+};
+
+/// Streams an auction's live events (clock prices, accepted bids, lots on
+/// the block) over a WebSocket. Dutch auctions send the current prices and
+/// live auctions the lot on the block as soon as the client connects.
+#[get("/live/auction/{id}")]
+pub async fn auction_feed(
+    req: HttpRequest,
+    body: web::Payload,
+    path: web::Path<i32>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let auction_id = path.into_inner();
+
+    let auction_model = entity::auctions::Entity::find_by_id(auction_id)
+        .filter(entity::auctions::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Auction not found".to_string()
+                })
+            ))
+        })?;
+
+    let mut snapshot = price_snapshot(&app_state.db, &auction_model).await?;
+
+    // Anyone joining a live sale mid-lot sees what is on the block
+    if auction_model.live {
+        if let Some(lot) = current_lot(&app_state.db, auction_id).await? {
+            let best_bid = best_bid(&app_state.db, lot.id).await?;
+            snapshot.push(json!({
+                "type": "lot_opened",
+                "lot": lot_json(&lot, best_bid),
+            }));
+        }
+    }
+
+    let (http_response, mut session, mut msg_stream) = actix_ws::handle(&req, body)?;
+    let mut events = app_state.live_feed.subscribe();
+
+    rt::spawn(async move {
+        for payload in snapshot {
+            if session.text(payload.to_string()).await.is_err() {
+                return;
+            }
+        }
+
+        loop {
+            match select(pin!(events.recv()), msg_stream.next()).await {
+                Either::Left((Ok(event), _)) => {
+                    if event.auction_id == auction_id && session.text(event.payload.to_string()).await.is_err() {
+                        return;
+                    }
+                }
+                // A client that fell behind misses the oldest events but stays connected
+                Either::Left((Err(RecvError::Lagged(_)), _)) => {}
+                Either::Left((Err(RecvError::Closed), _)) => break,
+                Either::Right((Some(Ok(Message::Ping(bytes))), _)) => {
+                    if session.pong(&bytes).await.is_err() {
+                        return;
+                    }
+                }
+                Either::Right((Some(Ok(Message::Close(reason))), _)) => {
+                    let _ = session.close(reason).await;
+                    return;
+                }
+                Either::Right((Some(Ok(_)), _)) => {}
+                Either::Right((Some(Err(_)) | None, _)) => break,
+            }
+        }
+
+        let _ = session.close(None).await;
+    });
+
+    Ok(http_response)
+}
+
+/// What the auctioneer can do from the console. Commands arrive as JSON text
+/// messages tagged by `action`, e.g. `{"action": "hammer"}`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum ConsoleCommand {
+    OpenLot,
+    FloorBid {
+        user_id: i32,
+        amount: Decimal,
+        #[serde(default = "default_quantity")]
+        quantity: i32,
+        note: Option<String>,
+    },
+    GoingOnce,
+    GoingTwice,
+    Hammer,
+}
+
+/// Puts the next lot on the block and tells the room, or announces the end
+/// of the sale when no lots are left.
+async fn announce_next_lot(app_state: &AppState, auction_id: i32) -> Result<(), ApiResponse> {
+    let payload = match open_next_lot(&app_state.db, auction_id).await? {
+        Some(lot) => json!({
+            "type": "lot_opened",
+            "lot": lot_json(&lot, None),
+        }),
+        None => json!({
+            "type": "sale_complete",
+            "auction_id": auction_id,
+        }),
+    };
+
+    app_state.live_feed.publish(auction_id, payload);
+
+    Ok(())
+}
+
+async fn lot_on_block(app_state: &AppState, auction_id: i32) -> Result<entity::listings::Model, ApiResponse> {
+    current_lot(&app_state.db, auction_id)
+        .await?
+        .ok_or_else(|| ApiResponse::new(400, response(json!({ "error": "No lot is on the block" }))))
+}
+
+/// Carries out a console command. Its outcome goes out on the auction's
+/// live feed, which the console itself is watching too.
+async fn run_console_command(
+    app_state: &AppState,
+    auction_id: i32,
+    staff_id: i32,
+    command: ConsoleCommand,
+) -> Result<(), ApiResponse> {
+    match command {
+        ConsoleCommand::OpenLot => announce_next_lot(app_state, auction_id).await,
+        ConsoleCommand::FloorBid { user_id, amount, quantity, note } => {
+            let lot = lot_on_block(app_state, auction_id).await?;
+            let bid_data = BidData { listing_id: lot.id, user_id, amount, quantity };
+            let bid_entry = BidEntry {
+                channel: BidChannel::Floor,
+                placed_by_id: Some(staff_id),
+                note,
+                max_amount: None,
+            };
+            let (auction_model, bid_models) = place_bid(app_state, &bid_data, bid_entry).await?;
+
+            publish_live_bids(app_state, &auction_model, &bid_models).await?;
+
+            Ok(())
+        }
+        ConsoleCommand::GoingOnce | ConsoleCommand::GoingTwice => {
+            let lot = lot_on_block(app_state, auction_id).await?;
+            let call = if matches!(command, ConsoleCommand::GoingOnce) { "going_once" } else { "going_twice" };
+
+            app_state.live_feed.publish(auction_id, json!({
+                "type": "call",
+                "call": call,
+                "listing_id": lot.id,
+                "best_bid": best_bid(&app_state.db, lot.id).await?,
+            }));
+
+            Ok(())
+        }
+        ConsoleCommand::Hammer => {
+            let (lot, auction_results) = hammer_lot(&app_state.db, auction_id).await?;
+            let numbers = bidder_numbers(&app_state.db, vec![auction_id]).await?;
+
+            app_state.live_feed.publish(auction_id, json!({
+                "type": "hammer",
+                "listing_id": lot.id,
+                "title": lot.title,
+                "sold": !auction_results.is_empty(),
+                "results": auction_results
+                    .iter()
+                    .map(|auction_result| json!({
+                        "auction_result_id": auction_result.id,
+                        "bidder": bidder_label(&numbers, auction_id, auction_result.winning_user_id),
+                        "quantity": auction_result.quantity,
+                        "unit_price": auction_result.unit_price,
+                        "hammer_price": auction_result.hammer_price,
+                    }))
+                    .collect::<Vec<_>>(),
+            }));
+
+            announce_next_lot(app_state, auction_id).await
+        }
+    }
+}
+
+/// The auctioneer's console for a live auction, opened by a signed-in staff
+/// member who is recorded against the floor bids they enter. It receives everything
+/// on the auction's live feed, starting with the lot on the block, and sends
+/// commands to open lots, enter floor bids, call the lot and hammer it.
+/// A command that fails is answered with its error on this socket only.
+#[get("/live/auctioneer/{id}")]
+pub async fn auctioneer_console(
+    req: HttpRequest,
+    body: web::Payload,
+    path: web::Path<i32>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<HttpResponse, actix_web::Error> {
+    let auction_id = path.into_inner();
+    let staff_model = require_staff(&app_state.db, &session).await?;
+
+    entity::auctions::Entity::find_by_id(auction_id)
+        .filter(entity::auctions::Column::DeletedAt.is_null())
+        .filter(entity::auctions::Column::Live.eq(true))
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Live auction not found".to_string()
+                })
+            ))
+        })?;
+
+    let lot = match current_lot(&app_state.db, auction_id).await? {
+        Some(lot) => Some(lot_json(&lot, best_bid(&app_state.db, lot.id).await?)),
+        None => None,
+    };
+
+    let (http_response, mut session, mut msg_stream) = actix_ws::handle(&req, body)?;
+    let mut events = app_state.live_feed.subscribe();
+
+    rt::spawn(async move {
+        let snapshot = json!({
+            "type": "console",
+            "auction_id": auction_id,
+            "lot": lot,
+        });
+
+        if session.text(snapshot.to_string()).await.is_err() {
+            return;
+        }
+
+        loop {
+            match select(pin!(events.recv()), msg_stream.next()).await {
+                Either::Left((Ok(event), _)) => {
+                    if event.auction_id == auction_id && session.text(event.payload.to_string()).await.is_err() {
+                        return;
+                    }
+                }
+                Either::Left((Err(RecvError::Lagged(_)), _)) => {}
+                Either::Left((Err(RecvError::Closed), _)) => break,
+                Either::Right((Some(Ok(Message::Text(text))), _)) => {
+                    let outcome = match serde_json::from_str::<ConsoleCommand>(&text) {
+                        Ok(command) => run_console_command(&app_state, auction_id, staff_model.id, command).await,
+                        Err(err) => Err(ApiResponse::new(400, response(json!({ "error": err.to_string() })))),
+                    };
+
+                    if let Err(err) = outcome {
+                        if session.text(err.body).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                Either::Right((Some(Ok(Message::Ping(bytes))), _)) => {
+                    if session.pong(&bytes).await.is_err() {
+                        return;
+                    }
+                }
+                Either::Right((Some(Ok(Message::Close(reason))), _)) => {
+                    let _ = session.close(reason).await;
+                    return;
+                }
+                Either::Right((Some(Ok(_)), _)) => {}
+                Either::Right((Some(Err(_)) | None, _)) => break,
+            }
+        }
+
+        let _ = session.close(None).await;
+    });
+
+    Ok(http_response)
+}
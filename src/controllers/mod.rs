@@ -2,4 +2,17 @@ pub mod listings;
 pub mod user;
 pub mod auction;
 pub mod bids;
-pub mod auction_result;
\ No newline at end of file
+pub mod auction_result;
+pub mod fees;
+pub mod invoices;
+pub mod ledger;
+pub mod bidder_limits;
+pub mod sellers;
+pub mod payouts;
+pub mod live;
+pub mod orders;
+pub mod registrations;
+pub mod kyc;
+pub mod notifications;
+pub mod phone_changes;
+pub mod privacy;
\ No newline at end of file
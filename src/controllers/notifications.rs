@@ -0,0 +1,135 @@
+use actix_session::Session;
+use actix_web::{get, post, web};
+use chrono::{Duration, Utc};
+use entity::sea_orm_active_enums::{NotificationCategory, PreferredLanguage};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    helpers::{
+        notifications::{notification_json, notify_user},
+        staff::require_staff,
+    },
+    utils::{api_response::ApiResponse, app_state::AppState, json_response::response, session::session_user_id},
+};
+
+/// Notifications shown to a user unless the request asks for more.
+const DEFAULT_NOTIFICATION_LIMIT: u64 = 50;
+
+#[derive(Debug, Deserialize)]
+struct NotificationsQuery {
+    limit: Option<u64>,
+}
+
+/// The signed-in user's notifications, newest first, including any still
+/// waiting for their quiet hours to end.
+#[get("/user/notifications")]
+pub async fn get_user_notifications(
+    app_state: web::Data<AppState>,
+    session: Session,
+    query: web::Query<NotificationsQuery>,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_id = session_user_id(&session)?;
+
+    let notifications = entity::notifications::Entity::find()
+        .filter(entity::notifications::Column::UserId.eq(user_id))
+        .order_by_desc(entity::notifications::Column::Id)
+        .limit(query.limit.unwrap_or(DEFAULT_NOTIFICATION_LIMIT))
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .iter()
+        .map(notification_json)
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "notifications": notifications,
+            "message": "Notifications fetched successfully".to_string()
+        })
+    )))
+}
+
+/// Tells every user who agreed to marketing about an upcoming auction.
+#[post("/notifications/announce/{auction_id}")]
+pub async fn announce_auction(
+    path: web::Path<i32>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    require_staff(&app_state.db, &session).await?;
+
+    let auction_model = entity::auctions::Entity::find_by_id(path.into_inner())
+        .filter(entity::auctions::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Auction not found".to_string()
+                })
+            ))
+        })?;
+
+    if auction_model.end_time < Utc::now().naive_utc() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "The auction has already ended".to_string()
+            })
+        )));
+    }
+
+    let users = entity::users::Entity::find()
+        .filter(entity::users::Column::DeletedAt.is_null())
+        .filter(entity::users::Column::MarketingConsent.eq(true))
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    for user in &users {
+        // Start times are given in the user's own time
+        let starts_at = auction_model.start_time + Duration::minutes(user.utc_offset_minutes as i64);
+
+        notify_user(&app_state.db, user, NotificationCategory::Marketing, |language| match language {
+            PreferredLanguage::En => format!(
+                "{} opens for bidding on {}.",
+                auction_model.name,
+                starts_at.format("%d %b %Y at %H:%M")
+            ),
+            PreferredLanguage::Sw => format!(
+                "Mnada wa {} utafunguliwa tarehe {} saa {}.",
+                auction_model.name,
+                starts_at.format("%d/%m/%Y"),
+                starts_at.format("%H:%M")
+            ),
+        })
+        .await?;
+    }
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "recipients": users.len(),
+            "message": "Auction announced successfully".to_string()
+        })
+    )))
+}
@@ -0,0 +1,297 @@
+use actix_session::Session;
+use actix_web::{get, post, put, web};
+use chrono::Utc;
+use entity::sea_orm_active_enums::{AuctionFormat, OrderSide};
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    helpers::{
+        order_book::{cancel_order, market_depth, place_order, unfilled_quantity, PriceLevel},
+        kyc::check_verification,
+        registrations::check_registered,
+    },
+    utils::{api_response::ApiResponse, app_state::AppState, json_response::response, session::session_user_id},
+};
+
+/// Price levels shown on each side of the book unless the request asks for more.
+const DEFAULT_DEPTH_LEVELS: usize = 10;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OrderData {
+    listing_id: i32,
+    side: OrderSide,
+    /// Limit price per unit of the listing.
+    price: Decimal,
+    quantity: i32,
+}
+
+impl OrderData {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.listing_id <= 0 {
+            return Err("Invalid Listing ID".to_string());
+        }
+
+        if self.price <= Decimal::ZERO {
+            return Err("Order price must be greater than zero".to_string());
+        }
+
+        if self.quantity <= 0 {
+            return Err("Order quantity must be at least one".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DepthQuery {
+    levels: Option<usize>,
+}
+
+fn order_json(order: &entity::orders::Model) -> serde_json::Value {
+    json!({
+        "id": order.id,
+        "listing_id": order.listing_id,
+        "user_id": order.user_id,
+        "side": order.side,
+        "price": order.price,
+        "quantity": order.quantity,
+        "filled_quantity": order.filled_quantity,
+        "unfilled_quantity": unfilled_quantity(order),
+        "status": order.status,
+        "cancelled_at": order.cancelled_at,
+        "created_at": order.created_at,
+    })
+}
+
+fn trade_json(trade: &entity::trades::Model) -> serde_json::Value {
+    json!({
+        "id": trade.id,
+        "listing_id": trade.listing_id,
+        "buy_order_id": trade.buy_order_id,
+        "sell_order_id": trade.sell_order_id,
+        "price": trade.price,
+        "quantity": trade.quantity,
+        "created_at": trade.created_at,
+    })
+}
+
+fn level_json(level: &PriceLevel) -> serde_json::Value {
+    json!({
+        "price": level.price,
+        "quantity": level.quantity,
+        "orders": level.orders,
+    })
+}
+
+async fn latest_trades(app_state: &AppState, listing_id: i32, limit: u64) -> Result<Vec<entity::trades::Model>, ApiResponse> {
+    entity::trades::Entity::find()
+        .filter(entity::trades::Column::DeletedAt.is_null())
+        .filter(entity::trades::Column::ListingId.eq(listing_id))
+        .order_by_desc(entity::trades::Column::Id)
+        .limit(limit)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })
+}
+
+#[post("/order/create")]
+pub async fn create_order(
+    order_data: web::Json<OrderData>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_id = session_user_id(&session)?;
+
+    if let Err(err) = order_data.validate() {
+        return Err(ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        )));
+    }
+
+    let (listing_model, auction_model) = entity::listings::Entity::find_by_id(order_data.listing_id)
+        .find_also_related(entity::auctions::Entity)
+        .filter(entity::listings::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Listing not found".to_string()
+                })
+            ))
+        })?;
+
+    let auction_model = auction_model.ok_or_else(|| {
+        ApiResponse::new(404, response(
+            json!({
+                "error": "Auction not found".to_string()
+            })
+        ))
+    })?;
+
+    if auction_model.format != AuctionFormat::DoubleAuction {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Only listings in a double auction are traded through the order book".to_string()
+            })
+        )))
+    }
+
+    let now = Utc::now().naive_utc();
+
+    if auction_model.start_time > now {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Trading has not opened yet".to_string()
+            })
+        )))
+    }
+
+    if auction_model.end_time < now {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Trading is closed. The auction has already ended.".to_string()
+            })
+        )))
+    }
+
+    check_registered(&app_state.db, &auction_model, user_id).await?;
+    check_verification(&app_state.db, &auction_model, &listing_model, user_id).await?;
+
+    let (order_model, trades) = place_order(
+        &app_state.db,
+        listing_model.id,
+        user_id,
+        order_data.side.clone(),
+        order_data.price,
+        order_data.quantity,
+    )
+    .await?;
+
+    for trade in &trades {
+        let mut event = trade_json(trade);
+        event["type"] = json!("trade");
+        event["auction_id"] = json!(auction_model.id);
+        app_state.live_feed.publish(auction_model.id, event);
+    }
+
+    Ok(ApiResponse::new(201, response(
+        json!({
+            "order": order_json(&order_model),
+            "trades": trades.iter().map(trade_json).collect::<Vec<_>>(),
+            "message": "Order placed successfully".to_string()
+        })
+    )))
+}
+
+#[put("/order/cancel/{id}")]
+pub async fn cancel_user_order(
+    path: web::Path<i32>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_id = session_user_id(&session)?;
+    let order_id = path.into_inner();
+
+    cancel_order(&app_state.db, order_id, user_id).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "message": "Order cancelled successfully".to_string()
+        })
+    )))
+}
+
+#[get("/orders/user/{id}")]
+pub async fn get_user_orders(
+    path: web::Path<i32>,
+    app_state: web::Data<AppState>,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_id = path.into_inner();
+
+    let orders = entity::orders::Entity::find()
+        .filter(entity::orders::Column::DeletedAt.is_null())
+        .filter(entity::orders::Column::UserId.eq(user_id))
+        .order_by_desc(entity::orders::Column::CreatedAt)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .iter()
+        .map(order_json)
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "orders": orders,
+            "message": "Orders fetched successfully".to_string()
+        })
+    )))
+}
+
+#[get("/market/depth/{listing_id}")]
+pub async fn get_market_depth(
+    path: web::Path<i32>,
+    query: web::Query<DepthQuery>,
+    app_state: web::Data<AppState>,
+) -> Result<ApiResponse, ApiResponse> {
+    let listing_id = path.into_inner();
+    let levels = query.levels.unwrap_or(DEFAULT_DEPTH_LEVELS).max(1);
+
+    let (bids, asks) = market_depth(&app_state.db, listing_id, levels).await?;
+    let last_trade = latest_trades(&app_state, listing_id, 1).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "listing_id": listing_id,
+            "bids": bids.iter().map(level_json).collect::<Vec<_>>(),
+            "asks": asks.iter().map(level_json).collect::<Vec<_>>(),
+            "best_bid": bids.first().map(|level| level.price),
+            "best_ask": asks.first().map(|level| level.price),
+            "last_trade": last_trade.first().map(trade_json),
+            "message": "Market depth fetched successfully".to_string()
+        })
+    )))
+}
+
+#[get("/market/trades/{listing_id}")]
+pub async fn get_market_trades(
+    path: web::Path<i32>,
+    app_state: web::Data<AppState>,
+) -> Result<ApiResponse, ApiResponse> {
+    let listing_id = path.into_inner();
+
+    let trades = latest_trades(&app_state, listing_id, 100).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "trades": trades.iter().map(trade_json).collect::<Vec<_>>(),
+            "last_trade": trades.first().map(trade_json),
+            "message": "Trades fetched successfully".to_string()
+        })
+    )))
+}
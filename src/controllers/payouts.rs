@@ -0,0 +1,309 @@
+use actix_session::Session;
+use actix_web::{get, http::header::{ContentDisposition, DispositionParam, DispositionType}, post, put, web, HttpResponse};
+use entity::sea_orm_active_enums::PayoutStatus;
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    helpers::{
+        payouts::{approve_payout_batch, create_payout_batch, send_payout_batch},
+        settlements::SellerStatement,
+        staff::require_admin,
+    },
+    utils::{api_response::ApiResponse, app_state::AppState, disbursement::disbursement_provider, json_response::response},
+};
+
+async fn find_payout_batch(
+    app_state: &AppState,
+    batch_id: i32,
+) -> Result<entity::payout_batches::Model, ApiResponse> {
+    entity::payout_batches::Entity::find_by_id(batch_id)
+        .filter(entity::payout_batches::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Payout batch not found".to_string()
+                })
+            ))
+        })
+}
+
+fn batch_json(batch: &entity::payout_batches::Model) -> serde_json::Value {
+    json!({
+        "id": batch.id,
+        "status": batch.status,
+        "total": batch.total,
+        "approved_at": batch.approved_at,
+        "approved_by": batch.approved_by,
+        "sent_at": batch.sent_at,
+        "created_at": batch.created_at,
+    })
+}
+
+fn payout_json(payout: &entity::payouts::Model) -> serde_json::Value {
+    json!({
+        "id": payout.id,
+        "payout_batch_id": payout.payout_batch_id,
+        "seller_id": payout.seller_id,
+        "status": payout.status,
+        "hammer_total": payout.hammer_total,
+        "commission": payout.commission_total,
+        "tax": payout.tax_total,
+        "amount": payout.amount,
+        "carried_forward": payout.carried_forward,
+        "provider_reference": payout.provider_reference,
+        "failure_reason": payout.failure_reason,
+        "sent_at": payout.sent_at,
+    })
+}
+
+fn payout_item_json(item: &entity::payout_items::Model) -> serde_json::Value {
+    json!({
+        "auction_result_id": item.auction_result_id,
+        "hammer_price": item.hammer_price,
+        "commission": item.commission,
+        "tax": item.tax,
+        "net": item.net,
+    })
+}
+
+#[post("/payouts/batch/create")]
+pub async fn create_batch(
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    require_admin(&app_state.db, &session).await?;
+
+    let (batch, payouts) = create_payout_batch(&app_state.db)
+        .await?
+        .ok_or_else(|| {
+            ApiResponse::new(400, response(
+                json!({
+                    "error": "No paid results awaiting payout".to_string()
+                })
+            ))
+        })?;
+
+    Ok(ApiResponse::new(201, response(
+        json!({
+            "batch": batch_json(&batch),
+            "payouts": payouts.iter().map(payout_json).collect::<Vec<_>>(),
+            "message": "Payout batch created successfully".to_string()
+        })
+    )))
+}
+
+#[get("/payouts/batches")]
+pub async fn get_all_batches(
+    app_state: web::Data<AppState>,
+) -> Result<ApiResponse, ApiResponse> {
+    let batches = entity::payout_batches::Entity::find()
+        .filter(entity::payout_batches::Column::DeletedAt.is_null())
+        .order_by_desc(entity::payout_batches::Column::Id)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "batches": batches.iter().map(batch_json).collect::<Vec<_>>(),
+            "message": "Payout batches fetched successfully".to_string()
+        })
+    )))
+}
+
+#[get("/payouts/batch/get/{id}")]
+pub async fn get_batch(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+) -> Result<ApiResponse, ApiResponse> {
+    let batch = find_payout_batch(&app_state, path.into_inner()).await?;
+
+    let payouts = entity::payouts::Entity::find()
+        .filter(entity::payouts::Column::DeletedAt.is_null())
+        .filter(entity::payouts::Column::PayoutBatchId.eq(batch.id))
+        .find_with_related(entity::payout_items::Entity)
+        .order_by_asc(entity::payouts::Column::Id)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .into_iter()
+        .map(|(payout, items)| {
+            json!({
+                "payout": payout_json(&payout),
+                "items": items.iter().map(payout_item_json).collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "batch": batch_json(&batch),
+            "payouts": payouts,
+            "message": "Payout batch fetched successfully".to_string()
+        })
+    )))
+}
+
+#[put("/payouts/batch/approve/{id}")]
+pub async fn approve_batch(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let admin = require_admin(&app_state.db, &session).await?;
+    let batch = find_payout_batch(&app_state, path.into_inner()).await?;
+
+    approve_payout_batch(&app_state.db, batch.id, admin.id).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "message": "Payout batch approved successfully".to_string()
+        })
+    )))
+}
+
+#[post("/payouts/batch/send/{id}")]
+pub async fn send_batch(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    require_admin(&app_state.db, &session).await?;
+    let batch = find_payout_batch(&app_state, path.into_inner()).await?;
+    let provider = disbursement_provider()?;
+
+    let batch = send_payout_batch(&app_state.db, provider.as_ref(), batch.id).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "batch": batch_json(&batch),
+            "message": if batch.status == PayoutStatus::Sending {
+                "Some payouts in this batch are still waiting on the provider"
+            } else {
+                "Payout batch sent"
+            }
+        })
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+struct StatementFormatQuery {
+    format: Option<String>,
+}
+
+#[get("/payouts/statement/{id}")]
+pub async fn get_payout_statement(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    query: web::Query<StatementFormatQuery>,
+) -> Result<HttpResponse, ApiResponse> {
+    let payout_id = path.into_inner();
+
+    let (payout, items) = entity::payouts::Entity::find_by_id(payout_id)
+        .filter(entity::payouts::Column::DeletedAt.is_null())
+        .find_with_related(entity::payout_items::Entity)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .pop()
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Payout not found".to_string()
+                })
+            ))
+        })?;
+
+    let seller = entity::users::Entity::find_by_id(payout.seller_id)
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let auction_result_ids = items.iter().map(|item| item.auction_result_id).collect();
+    let statement = SellerStatement::load(&app_state.db, payout.seller_id, None, Some(auction_result_ids)).await?;
+    let file_name = format!("payout-{:06}", payout.id);
+
+    match query.format.as_deref().unwrap_or("json") {
+        "json" => Ok(HttpResponse::Ok().json(json!({
+            "payout": payout_json(&payout),
+            "statement": statement.to_json(),
+            "message": "Payout statement fetched successfully".to_string()
+        }))),
+        "csv" => Ok(HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(format!("{}.csv", file_name))],
+            })
+            .body(statement.render_csv())),
+        "pdf" => {
+            let mut heading = vec![
+                format!("Payout statement {}", file_name.to_uppercase()),
+                format!("Batch: {}", payout.payout_batch_id),
+                format!("Status: {:?}", payout.status),
+                format!("Amount: {}", payout.amount),
+            ];
+
+            if payout.carried_forward > Decimal::ZERO {
+                heading.push(format!("Carried forward to the next payout: {}", payout.carried_forward));
+            }
+
+            if let Some(seller) = seller {
+                heading.push(format!("Consignor: {} ({})", seller.name, seller.phone_number));
+            }
+
+            if let Some(sent_at) = payout.sent_at {
+                heading.push(format!("Sent: {}", sent_at.format("%Y-%m-%d %H:%M")));
+            }
+
+            Ok(HttpResponse::Ok()
+                .content_type("application/pdf")
+                .insert_header(ContentDisposition {
+                    disposition: DispositionType::Attachment,
+                    parameters: vec![DispositionParam::Filename(format!("{}.pdf", file_name))],
+                })
+                .body(statement.render_pdf(&file_name, heading)?))
+        }
+        _ => Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Format must be json, csv or pdf".to_string()
+            })
+        ))),
+    }
+}
@@ -0,0 +1,396 @@
+use actix_session::Session;
+use actix_web::{get, post, web};
+use chrono::{Duration, Utc};
+use entity::sea_orm_active_enums::{NotificationCategory, PhoneChangeStatus, PreferredLanguage};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    controllers::user::find_session_user,
+    helpers::{
+        notifications::notify_user,
+        phone_changes::{
+            cancel_change, change_json, check_number_available, code_hash, code_matches, effective_at, generate_code,
+            history_json, open_change_condition, send_to_number, MAX_CODE_ATTEMPTS,
+        },
+    },
+    utils::{api_response::ApiResponse, app_state::AppState, constants, json_response::response, phone::normalize_phone_number},
+};
+
+async fn find_open_change(
+    app_state: &AppState,
+    user_id: i32,
+) -> Result<Option<entity::phone_number_changes::Model>, ApiResponse> {
+    entity::phone_number_changes::Entity::find()
+        .filter(entity::phone_number_changes::Column::UserId.eq(user_id))
+        .filter(entity::phone_number_changes::Column::Status.is_in([
+            PhoneChangeStatus::AwaitingVerification,
+            PhoneChangeStatus::CoolingOff,
+        ]))
+        .order_by_desc(entity::phone_number_changes::Column::Id)
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PhoneChangeData {
+    new_phone_number: String,
+}
+
+/// Starts moving the signed-in account to a new number. A code goes to the
+/// new number, which must confirm the change, and another to the current
+/// number, which may. Any earlier request that is still open is replaced.
+#[post("/user/phone/change")]
+pub async fn request_phone_change(
+    app_state: web::Data<AppState>,
+    session: Session,
+    phone_change_data: web::Json<PhoneChangeData>,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_model = find_session_user(&app_state, &session).await?;
+    let new_phone_number = normalize_phone_number(&phone_change_data.new_phone_number).map_err(|err| {
+        ApiResponse::new(400, response(
+            json!({
+                "error": err
+            })
+        ))
+    })?;
+
+    if new_phone_number == user_model.phone_number {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "This is already your phone number".to_string()
+            })
+        )));
+    }
+
+    check_number_available(&app_state.db, &new_phone_number, Some(user_model.id)).await?;
+
+    if let Some(change) = find_open_change(&app_state, user_model.id).await? {
+        cancel_change(&app_state.db, change, "Replaced by a newer request").await?;
+    }
+
+    let old_code = generate_code();
+    let new_code = generate_code();
+    let ttl_minutes = *constants::PHONE_CHANGE_CODE_TTL_MINUTES;
+
+    let change_model = entity::phone_number_changes::ActiveModel {
+        user_id: Set(user_model.id),
+        old_phone_number: Set(user_model.phone_number.clone()),
+        new_phone_number: Set(new_phone_number.clone()),
+        status: Set(PhoneChangeStatus::AwaitingVerification),
+        old_code_hash: Set(code_hash(user_model.id, &user_model.phone_number, &old_code)),
+        new_code_hash: Set(code_hash(user_model.id, &new_phone_number, &new_code)),
+        codes_expire_at: Set(Utc::now().naive_utc() + Duration::minutes(ttl_minutes)),
+        ..Default::default()
+    }
+    .insert(&app_state.db)
+    .await
+    .map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    let old_sent = send_to_number(
+        &user_model.phone_number,
+        &format!(
+            "{} is your code to move your auction account to {}. It expires in {} minutes. If you did not ask for this, do not share it.",
+            old_code, new_phone_number, ttl_minutes
+        ),
+    )
+    .await;
+
+    let new_sent = send_to_number(
+        &new_phone_number,
+        &format!(
+            "{} is your code to use this number for your auction account. It expires in {} minutes.",
+            new_code, ttl_minutes
+        ),
+    )
+    .await;
+
+    Ok(ApiResponse::new(201, response(
+        json!({
+            "change": change_json(&change_model),
+            "codes_sent": {
+                "old_phone_number": old_sent,
+                "new_phone_number": new_sent,
+            },
+            "message": "Enter the codes sent to your old and new numbers".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum PhoneChangeTarget {
+    Old,
+    New,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct VerificationData {
+    number: PhoneChangeTarget,
+    code: String,
+}
+
+/// Confirms one side of a number change. Once the new number is confirmed
+/// the cooling-off period starts, shorter when the old number confirmed too,
+/// and the account is told how to cancel if the change was not theirs.
+#[post("/user/phone/verify")]
+pub async fn verify_phone_change(
+    app_state: web::Data<AppState>,
+    session: Session,
+    verification_data: web::Json<VerificationData>,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_model = find_session_user(&app_state, &session).await?;
+
+    let change_model = find_open_change(&app_state, user_model.id).await?.ok_or_else(|| {
+        ApiResponse::new(404, response(
+            json!({
+                "error": "No phone number change is in progress".to_string()
+            })
+        ))
+    })?;
+
+    let now = Utc::now().naive_utc();
+    let (phone_number, hash, verified_at) = match verification_data.number {
+        PhoneChangeTarget::Old => (&change_model.old_phone_number, &change_model.old_code_hash, change_model.old_verified_at),
+        PhoneChangeTarget::New => (&change_model.new_phone_number, &change_model.new_code_hash, change_model.new_verified_at),
+    };
+
+    if verified_at.is_some() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "This number is already confirmed".to_string()
+            })
+        )));
+    }
+
+    if change_model.codes_expire_at <= now {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "The code has expired".to_string()
+            })
+        )));
+    }
+
+    if !code_matches(user_model.id, phone_number, verification_data.code.trim(), hash) {
+        let failed_attempts = change_model.failed_attempts + 1;
+
+        if failed_attempts >= MAX_CODE_ATTEMPTS {
+            cancel_change(&app_state.db, change_model, "Too many wrong codes").await?;
+
+            return Err(ApiResponse::new(400, response(
+                json!({
+                    "error": "Too many wrong codes. The change was cancelled".to_string()
+                })
+            )));
+        }
+
+        let mut update_change_model: entity::phone_number_changes::ActiveModel = change_model.into();
+        update_change_model.failed_attempts = Set(failed_attempts);
+        update_change_model.updated_at = Set(now);
+        update_change_model
+            .update(&app_state.db)
+            .await
+            .map_err(|err| {
+                ApiResponse::new(500, response(
+                    json!({
+                        "error": err.to_string()
+                    })
+                ))
+            })?;
+
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "The code is not correct".to_string(),
+                "attempts_remaining": MAX_CODE_ATTEMPTS - failed_attempts
+            })
+        )));
+    }
+
+    let starts_cooling_off = matches!(verification_data.number, PhoneChangeTarget::New);
+
+    let mut change = change_model.clone();
+    match verification_data.number {
+        PhoneChangeTarget::Old => change.old_verified_at = Some(now),
+        PhoneChangeTarget::New => change.new_verified_at = Some(now),
+    }
+    change.effective_at = effective_at(&change);
+    if change.effective_at.is_some() {
+        change.status = PhoneChangeStatus::CoolingOff;
+    }
+
+    // Both numbers must still be free when the change moves on
+    if starts_cooling_off {
+        if let Err(err) = check_number_available(&app_state.db, &change.new_phone_number, Some(user_model.id)).await {
+            cancel_change(&app_state.db, change_model, "The new number belongs to another account").await?;
+            return Err(err);
+        }
+    }
+
+    let mut update_change_model: entity::phone_number_changes::ActiveModel = change_model.into();
+    update_change_model.status = Set(change.status.clone());
+    update_change_model.old_verified_at = Set(change.old_verified_at);
+    update_change_model.new_verified_at = Set(change.new_verified_at);
+    update_change_model.effective_at = Set(change.effective_at);
+    update_change_model.updated_at = Set(now);
+    let change_model = update_change_model
+        .update(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    if let Some(effective_at) = change_model.effective_at {
+        let local_effective_at = effective_at + Duration::minutes(user_model.utc_offset_minutes as i64);
+
+        let notified = notify_user(&app_state.db, &user_model, NotificationCategory::Account, |language| match language {
+            PreferredLanguage::En => format!(
+                "Your account will move to {} on {}. If you did not ask for this, sign in and cancel the change before then.",
+                change_model.new_phone_number,
+                local_effective_at.format("%d %b %Y at %H:%M")
+            ),
+            PreferredLanguage::Sw => format!(
+                "Akaunti yako itahamia {} tarehe {} saa {}. Ikiwa hukuomba hili, ingia na ughairi kabla ya hapo.",
+                change_model.new_phone_number,
+                local_effective_at.format("%d/%m/%Y"),
+                local_effective_at.format("%H:%M")
+            ),
+        })
+        .await;
+
+        if let Err(err) = notified {
+            eprintln!("Error sending phone change notification: {:?}", err);
+        }
+    }
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "change": change_json(&change_model),
+            "message": match change_model.status {
+                PhoneChangeStatus::CoolingOff => "Your new number takes effect once the cooling-off period is over",
+                _ => "Now enter the code sent to your new number",
+            }
+        })
+    )))
+}
+
+#[post("/user/phone/change/cancel")]
+pub async fn cancel_phone_change(
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_model = find_session_user(&app_state, &session).await?;
+
+    let change_model = find_open_change(&app_state, user_model.id).await?.ok_or_else(|| {
+        ApiResponse::new(404, response(
+            json!({
+                "error": "No phone number change is in progress".to_string()
+            })
+        ))
+    })?;
+
+    let change_model = cancel_change(&app_state.db, change_model, "Cancelled by the user").await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "change": change_json(&change_model),
+            "message": "Phone number change cancelled".to_string()
+        })
+    )))
+}
+
+/// The signed-in user's latest number change, whatever its state.
+#[get("/user/phone/change")]
+pub async fn get_phone_change(
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_model = find_session_user(&app_state, &session).await?;
+
+    let change_model = entity::phone_number_changes::Entity::find()
+        .filter(entity::phone_number_changes::Column::UserId.eq(user_model.id))
+        .order_by_desc(entity::phone_number_changes::Column::Id)
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let open = match &change_model {
+        Some(change) => entity::phone_number_changes::Entity::find_by_id(change.id)
+            .filter(open_change_condition(Utc::now().naive_utc()))
+            .one(&app_state.db)
+            .await
+            .map_err(|err| {
+                ApiResponse::new(500, response(
+                    json!({
+                        "error": err.to_string()
+                    })
+                ))
+            })?
+            .is_some(),
+        None => false,
+    };
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "change": change_model.as_ref().map(change_json),
+            "open": open,
+            "message": "Phone number change fetched successfully".to_string()
+        })
+    )))
+}
+
+/// The numbers the signed-in account has used, newest first.
+#[get("/user/phone/history")]
+pub async fn get_phone_history(
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_model = find_session_user(&app_state, &session).await?;
+
+    let history = entity::phone_number_history::Entity::find()
+        .filter(entity::phone_number_history::Column::UserId.eq(user_model.id))
+        .order_by_desc(entity::phone_number_history::Column::Id)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .iter()
+        .map(history_json)
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "phone_number": user_model.phone_number,
+            "history": history,
+            "message": "Phone number history fetched successfully".to_string()
+        })
+    )))
+}
@@ -0,0 +1,158 @@
+use actix_session::Session;
+use actix_web::{
+    http::header::{ContentDisposition, DispositionParam, DispositionType},
+    post, web, HttpResponse,
+};
+use entity::sea_orm_active_enums::OneTimeCodePurpose;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    controllers::user::find_session_user,
+    helpers::{
+        one_time_codes::{issue_code, verify_code},
+        privacy::{build_archive, collect_user_data, erase_user},
+    },
+    utils::{api_response::ApiResponse, app_state::AppState, json_response::response},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DataAction {
+    Export,
+    Erase,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DataCodeData {
+    action: DataAction,
+}
+
+/// Sends a code to the signed-in user's number. A session alone is not
+/// enough to take away or destroy an account's data, so exporting and
+/// erasing each need a fresh code from the phone as well.
+#[post("/user/data/code")]
+pub async fn request_data_code(
+    app_state: web::Data<AppState>,
+    session: Session,
+    data_code_data: web::Json<DataCodeData>,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_model = find_session_user(&app_state, &session).await?;
+    let purpose = match data_code_data.action {
+        DataAction::Export => OneTimeCodePurpose::DataExport,
+        DataAction::Erase => OneTimeCodePurpose::AccountErasure,
+    };
+
+    let (code_model, code_sent) = issue_code(&app_state.db, &user_model.phone_number, purpose, Some(user_model.id), None).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "code_sent": code_sent,
+            "expires_at": code_model.expires_at,
+            "message": "Enter the code sent to your phone to continue".to_string()
+        })
+    )))
+}
+
+/// Spends a code sent to the signed-in user's own number for this purpose.
+async fn verify_account_code(
+    app_state: &AppState,
+    user_model: &entity::users::Model,
+    purpose: OneTimeCodePurpose,
+    code: &str,
+) -> Result<(), ApiResponse> {
+    let code_model = verify_code(&app_state.db, &user_model.phone_number, purpose, code).await?;
+
+    if code_model.user_id != Some(user_model.id) {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "No code is waiting for this number. Ask for a new one".to_string()
+            })
+        )));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ExportFormat {
+    #[default]
+    Json,
+    Zip,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportData {
+    #[serde(default)]
+    format: ExportFormat,
+    code: String,
+}
+
+/// Downloads everything held about the signed-in user, either as a single
+/// JSON file or as a ZIP archive that also carries their identity documents.
+#[post("/user/data/export")]
+pub async fn export_user_data(
+    app_state: web::Data<AppState>,
+    session: Session,
+    export_data: web::Json<ExportData>,
+) -> Result<HttpResponse, ApiResponse> {
+    let user_model = find_session_user(&app_state, &session).await?;
+    let file_name = format!("user-{:06}-data", user_model.id);
+
+    verify_account_code(&app_state, &user_model, OneTimeCodePurpose::DataExport, &export_data.code).await?;
+
+    match export_data.format {
+        ExportFormat::Json => {
+            let (data, _) = collect_user_data(&app_state.db, &user_model).await?;
+
+            Ok(HttpResponse::Ok()
+                .content_type("application/json")
+                .insert_header(ContentDisposition {
+                    disposition: DispositionType::Attachment,
+                    parameters: vec![DispositionParam::Filename(format!("{}.json", file_name))],
+                })
+                .body(data.to_string()))
+        }
+        ExportFormat::Zip => {
+            let (data, documents) = collect_user_data(&app_state.db, &user_model).await?;
+
+            Ok(HttpResponse::Ok()
+                .content_type("application/zip")
+                .insert_header(ContentDisposition {
+                    disposition: DispositionType::Attachment,
+                    parameters: vec![DispositionParam::Filename(format!("{}.zip", file_name))],
+                })
+                .body(build_archive(data, documents).await?))
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ErasureData {
+    code: String,
+}
+
+/// Erases the signed-in user's account once they enter a code sent to its
+/// number. Their bids and payments are kept under a pseudonym and they are
+/// signed out for good.
+#[post("/user/erase")]
+pub async fn erase_account(
+    app_state: web::Data<AppState>,
+    session: Session,
+    erasure_data: web::Json<ErasureData>,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_model = find_session_user(&app_state, &session).await?;
+    verify_account_code(&app_state, &user_model, OneTimeCodePurpose::AccountErasure, &erasure_data.code).await?;
+
+    let user_model = erase_user(&app_state.db, user_model).await?;
+    session.purge();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "user_id": user_model.id,
+            "erased_at": user_model.erased_at,
+            "message": "Account erased successfully".to_string()
+        })
+    )))
+}
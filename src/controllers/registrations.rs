@@ -0,0 +1,358 @@
+use actix_session::Session;
+use actix_web::{get, post, put, web};
+use chrono::Utc;
+use entity::sea_orm_active_enums::{NotificationCategory, PreferredLanguage, RegistrationStatus};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    helpers::{
+        clearing::bidding_closes_at,
+        notifications::notify_user,
+        registrations::{registration_json, terms_hash},
+        staff::{require_admin, require_staff},
+    },
+    utils::{api_response::ApiResponse, app_state::AppState, json_response::response},
+};
+
+async fn find_auction(app_state: &AppState, auction_id: i32) -> Result<entity::auctions::Model, ApiResponse> {
+    entity::auctions::Entity::find_by_id(auction_id)
+        .filter(entity::auctions::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Auction not found".to_string()
+                })
+            ))
+        })
+}
+
+async fn find_registration(
+    app_state: &AppState,
+    auction_id: i32,
+    user_id: i32,
+) -> Result<Option<entity::auction_registrations::Model>, ApiResponse> {
+    entity::auction_registrations::Entity::find()
+        .filter(entity::auction_registrations::Column::DeletedAt.is_null())
+        .filter(entity::auction_registrations::Column::AuctionId.eq(auction_id))
+        .filter(entity::auction_registrations::Column::UserId.eq(user_id))
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RegistrationData {
+    user_id: i32,
+    #[serde(default)]
+    accept_terms: bool,
+}
+
+/// Applies to bid in a registration-required auction. The bidder accepts the
+/// auction's terms as they stand and waits for an admin to review them. A
+/// rejected bidder may apply again.
+#[post("/auction/register/{id}")]
+pub async fn register_for_auction(
+    path: web::Path<i32>,
+    registration_data: web::Json<RegistrationData>,
+    app_state: web::Data<AppState>,
+) -> Result<ApiResponse, ApiResponse> {
+    let auction_model = find_auction(&app_state, path.into_inner()).await?;
+
+    if !auction_model.requires_registration {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "This auction is open to all bidders".to_string()
+            })
+        )));
+    }
+
+    if bidding_closes_at(&auction_model) < Utc::now().naive_utc() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "The auction has already ended".to_string()
+            })
+        )));
+    }
+
+    if auction_model.terms.is_some() && !registration_data.accept_terms {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "You must accept the auction's terms to register".to_string()
+            })
+        )));
+    }
+
+    entity::users::Entity::find_by_id(registration_data.user_id)
+        .filter(entity::users::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "User not found".to_string()
+                })
+            ))
+        })?;
+
+    let registration_model = find_registration(&app_state, auction_model.id, registration_data.user_id).await?;
+
+    let now = Utc::now().naive_utc();
+    let registration_model = match registration_model {
+        Some(model) if model.status == RegistrationStatus::Approved => {
+            return Err(ApiResponse::new(400, response(
+                json!({
+                    "error": "You are already approved to bid in this auction".to_string()
+                })
+            )));
+        }
+        Some(model) if model.status == RegistrationStatus::Pending => {
+            return Err(ApiResponse::new(400, response(
+                json!({
+                    "error": "Your registration is already awaiting approval".to_string()
+                })
+            )));
+        }
+        Some(model) => {
+            let mut update_registration_model: entity::auction_registrations::ActiveModel = model.into();
+            update_registration_model.status = Set(RegistrationStatus::Pending);
+            update_registration_model.terms_hash = Set(terms_hash(&auction_model));
+            update_registration_model.terms_accepted_at = Set(now);
+            update_registration_model.reviewed_by_id = Set(None);
+            update_registration_model.reviewed_at = Set(None);
+            update_registration_model.review_note = Set(None);
+            update_registration_model.updated_at = Set(now);
+            update_registration_model.update(&app_state.db).await
+        }
+        None => {
+            entity::auction_registrations::ActiveModel {
+                auction_id: Set(auction_model.id),
+                user_id: Set(registration_data.user_id),
+                status: Set(RegistrationStatus::Pending),
+                terms_hash: Set(terms_hash(&auction_model)),
+                terms_accepted_at: Set(now),
+                ..Default::default()
+            }
+            .insert(&app_state.db)
+            .await
+        }
+    }
+    .map_err(|err| {
+        ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        ))
+    })?;
+
+    Ok(ApiResponse::new(201, response(
+        json!({
+            "registration": registration_json(&registration_model),
+            "message": "Registration submitted for approval".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ReviewData {
+    status: RegistrationStatus,
+    note: Option<String>,
+}
+
+/// Approves or rejects a registration. An approval can later be revoked by
+/// rejecting the registration, which stops any further bids from the bidder.
+#[put("/registration/review/{id}")]
+pub async fn review_registration(
+    path: web::Path<i32>,
+    review_data: web::Json<ReviewData>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    if review_data.status == RegistrationStatus::Pending {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "A registration can only be approved or rejected".to_string()
+            })
+        )));
+    }
+
+    let admin_model = require_admin(&app_state.db, &session).await?;
+
+    let registration_model = entity::auction_registrations::Entity::find_by_id(path.into_inner())
+        .filter(entity::auction_registrations::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Registration not found".to_string()
+                })
+            ))
+        })?;
+
+    let auction_model = find_auction(&app_state, registration_model.auction_id).await?;
+
+    let user_model = entity::users::Entity::find_by_id(registration_model.user_id)
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "User not found".to_string()
+                })
+            ))
+        })?;
+
+    let now = Utc::now().naive_utc();
+    let mut update_registration_model: entity::auction_registrations::ActiveModel = registration_model.into();
+    update_registration_model.status = Set(review_data.status.clone());
+    update_registration_model.reviewed_by_id = Set(Some(admin_model.id));
+    update_registration_model.reviewed_at = Set(Some(now));
+    update_registration_model.review_note = Set(review_data.note.clone());
+    update_registration_model.updated_at = Set(now);
+    let registration_model = update_registration_model
+        .update(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let notified = notify_user(&app_state.db, &user_model, NotificationCategory::Auction, |language| {
+        let reason = registration_model.review_note.as_ref();
+        match (&registration_model.status, language) {
+            (RegistrationStatus::Approved, PreferredLanguage::En) => format!("You are approved to bid in {}.", auction_model.name),
+            (RegistrationStatus::Approved, PreferredLanguage::Sw) => format!("Umeidhinishwa kutoa zabuni katika {}.", auction_model.name),
+            (_, PreferredLanguage::En) => format!(
+                "Your registration for {} was not approved.{}",
+                auction_model.name,
+                reason.map(|note| format!("\nReason: {}", note)).unwrap_or_default()
+            ),
+            (_, PreferredLanguage::Sw) => format!(
+                "Usajili wako wa {} haukuidhinishwa.{}",
+                auction_model.name,
+                reason.map(|note| format!("\nSababu: {}", note)).unwrap_or_default()
+            ),
+        }
+    })
+    .await;
+
+    if let Err(err) = notified {
+        eprintln!("Error sending registration notification: {:?}", err);
+    }
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "registration": registration_json(&registration_model),
+            "message": "Registration reviewed successfully".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+struct RegistrationsQuery {
+    status: Option<RegistrationStatus>,
+}
+
+#[get("/registrations/auction/{id}")]
+pub async fn get_auction_registrations(
+    path: web::Path<i32>,
+    query: web::Query<RegistrationsQuery>,
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let auction_id = path.into_inner();
+
+    require_staff(&app_state.db, &session).await?;
+
+    let mut select = entity::auction_registrations::Entity::find()
+        .filter(entity::auction_registrations::Column::DeletedAt.is_null())
+        .filter(entity::auction_registrations::Column::AuctionId.eq(auction_id));
+
+    if let Some(status) = query.status.clone() {
+        select = select.filter(entity::auction_registrations::Column::Status.eq(status));
+    }
+
+    let registrations = select
+        .order_by_asc(entity::auction_registrations::Column::Id)
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .iter()
+        .map(registration_json)
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "registrations": registrations,
+            "message": "Registrations fetched successfully".to_string()
+        })
+    )))
+}
+
+#[get("/registration/user/{id}/{auction_id}")]
+pub async fn get_user_registration(
+    path: web::Path<(i32, i32)>,
+    app_state: web::Data<AppState>,
+) -> Result<ApiResponse, ApiResponse> {
+    let (user_id, auction_id) = path.into_inner();
+
+    let auction_model = find_auction(&app_state, auction_id).await?;
+    let registration_model = find_registration(&app_state, auction_id, user_id).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "requires_registration": auction_model.requires_registration,
+            "terms": auction_model.terms,
+            "terms_hash": terms_hash(&auction_model),
+            "registration": registration_model.as_ref().map(registration_json),
+            "message": "Registration fetched successfully".to_string()
+        })
+    )))
+}
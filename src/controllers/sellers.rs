@@ -0,0 +1,385 @@
+use std::collections::{HashMap, HashSet};
+
+use actix_session::Session;
+use actix_web::{delete, get, post, put, web};
+use chrono::{NaiveDateTime, Utc};
+use entity::sea_orm_active_enums::{AuctionFormat, ClearingRule};
+use migration::Expr;
+use rust_decimal::Decimal;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, FromQueryResult, QueryFilter, QueryOrder, QuerySelect, Set};
+use serde_json::json;
+
+use crate::{
+    controllers::listings::ListingData,
+    helpers::{clearing::{bids_hidden, is_reverse}, settlements::SellerStatement},
+    utils::{api_response::ApiResponse, app_state::AppState, json_response::response, session::session_user_id},
+};
+
+/// Finds a listing the seller consigned, treating anyone else's listing as missing.
+async fn find_seller_listing(
+    app_state: &AppState,
+    seller_id: i32,
+    listing_id: i32,
+) -> Result<entity::listings::Model, ApiResponse> {
+    entity::listings::Entity::find_by_id(listing_id)
+        .filter(entity::listings::Column::DeletedAt.is_null())
+        .filter(entity::listings::Column::SellerId.eq(seller_id))
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "Listing not found".to_string()
+                })
+            ))
+        })
+}
+
+/// Sellers may only change a lot before anyone has bid on it.
+async fn ensure_no_bids(app_state: &AppState, listing_id: i32) -> Result<(), ApiResponse> {
+    let bid_count = entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .filter(entity::bids::Column::ListingId.eq(listing_id))
+        .select_only()
+        .column_as(Expr::col(entity::bids::Column::Id).count(), "bid_count")
+        .into_tuple::<i64>()
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .unwrap_or_default();
+
+    if bid_count > 0 {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": "Listings that have received bids can no longer be changed".to_string()
+            })
+        )));
+    }
+
+    Ok(())
+}
+
+#[post("/seller/listing/create")]
+pub async fn create_seller_listing(
+    app_state: web::Data<AppState>,
+    listing_data: web::Json<ListingData>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let seller_id = session_user_id(&session)?;
+
+    if let Err(err) = listing_data.validate() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": err.to_string()
+            })
+        )));
+    }
+
+    let mut listing_model = entity::listings::ActiveModel {
+        seller_id: Set(Some(seller_id)),
+        ..Default::default()
+    };
+    listing_data.apply_to(&mut listing_model);
+    let listing_model = listing_model
+        .insert(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "listing_id": listing_model.id,
+            "message": "Listing created successfully".to_string()
+        })
+    )))
+}
+
+#[put("/seller/listing/update/{listing_id}")]
+pub async fn update_seller_listing(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    listing_data: web::Json<ListingData>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let seller_id = session_user_id(&session)?;
+    let listing_id = path.into_inner();
+
+    if let Err(err) = listing_data.validate() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": err.to_string()
+            })
+        )));
+    }
+
+    let listing_model = find_seller_listing(&app_state, seller_id, listing_id).await?;
+    ensure_no_bids(&app_state, listing_id).await?;
+
+    let mut update_listing_model: entity::listings::ActiveModel = listing_model.into();
+    listing_data.apply_to(&mut update_listing_model);
+    update_listing_model.updated_at = Set(Utc::now().naive_utc());
+    update_listing_model
+        .update(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "message": "Listing updated successfully".to_string()
+        })
+    )))
+}
+
+#[delete("/seller/listing/delete/{listing_id}")]
+pub async fn delete_seller_listing(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let seller_id = session_user_id(&session)?;
+    let listing_id = path.into_inner();
+
+    find_seller_listing(&app_state, seller_id, listing_id).await?;
+    ensure_no_bids(&app_state, listing_id).await?;
+
+    entity::listings::Entity::delete_by_id(listing_id)
+        .exec(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "message": "Listing deleted successfully".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, FromQueryResult)]
+struct SellerListingResult {
+    id: i32,
+    title: String,
+    description: Option<String>,
+    base_price: Decimal,
+    available_volume: i32,
+    clearing_rule: ClearingRule,
+    unit_of_measure: String,
+    min_quantity: i32,
+    max_quantity: Option<i32>,
+    auction_id: i32,
+    name: String,
+    format: AuctionFormat,
+    start_time: NaiveDateTime,
+    end_time: NaiveDateTime,
+}
+
+#[get("/seller/listings")]
+pub async fn get_seller_listings(
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let seller_id = session_user_id(&session)?;
+    let now = Utc::now().naive_utc();
+
+    let listings = entity::listings::Entity::find()
+        .inner_join(entity::auctions::Entity)
+        .filter(entity::listings::Column::DeletedAt.is_null())
+        .filter(entity::listings::Column::SellerId.eq(seller_id))
+        .order_by_asc(entity::listings::Column::Id)
+        .select_only()
+        .column(entity::listings::Column::Id)
+        .column(entity::listings::Column::Title)
+        .column(entity::listings::Column::Description)
+        .column(entity::listings::Column::BasePrice)
+        .column(entity::listings::Column::AvailableVolume)
+        .column(entity::listings::Column::ClearingRule)
+        .column(entity::listings::Column::UnitOfMeasure)
+        .column(entity::listings::Column::MinQuantity)
+        .column(entity::listings::Column::MaxQuantity)
+        .column(entity::listings::Column::AuctionId)
+        .column(entity::auctions::Column::Name)
+        .column(entity::auctions::Column::Format)
+        .column(entity::auctions::Column::StartTime)
+        .column(entity::auctions::Column::EndTime)
+        .into_model::<SellerListingResult>()
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    let listing_ids = listings.iter().map(|listing| listing.id).collect::<Vec<_>>();
+
+    let auctions: HashMap<i32, entity::auctions::Model> = entity::auctions::Entity::find()
+        .filter(entity::auctions::Column::Id.is_in(listings.iter().map(|listing| listing.auction_id).collect::<Vec<_>>()))
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .into_iter()
+        .map(|auction| (auction.id, auction))
+        .collect();
+
+    let bid_stats: HashMap<i32, (i64, Option<Decimal>, Option<Decimal>)> = entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .filter(entity::bids::Column::ListingId.is_in(listing_ids.clone()))
+        .group_by(entity::bids::Column::ListingId)
+        .select_only()
+        .column(entity::bids::Column::ListingId)
+        .column_as(Expr::col(entity::bids::Column::Id).count(), "total_bids")
+        .column_as(Expr::col(entity::bids::Column::Amount).max(), "highest_bid")
+        .column_as(Expr::col(entity::bids::Column::Amount).min(), "lowest_bid")
+        .into_tuple::<(i32, i64, Option<Decimal>, Option<Decimal>)>()
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .into_iter()
+        .map(|(listing_id, total_bids, highest_bid, lowest_bid)| (listing_id, (total_bids, highest_bid, lowest_bid)))
+        .collect();
+
+    let sold_listing_ids: HashSet<i32> = entity::auction_results::Entity::find()
+        .filter(entity::auction_results::Column::DeletedAt.is_null())
+        .filter(entity::auction_results::Column::ListingId.is_in(listing_ids))
+        .select_only()
+        .column(entity::auction_results::Column::ListingId)
+        .into_tuple::<i32>()
+        .all(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .into_iter()
+        .collect();
+
+    let listings = listings
+        .into_iter()
+        .map(|row| {
+            let (total_bids, highest_bid, lowest_bid) = bid_stats.get(&row.id).cloned().unwrap_or_default();
+            let auction = auctions.get(&row.auction_id);
+            let sealed = auction.is_some_and(|auction| bids_hidden(auction, now));
+            // Reverse auctions buy, so the lowest offer is the best one
+            let best_bid = if auction.is_some_and(is_reverse) { lowest_bid } else { highest_bid };
+            let status = if row.start_time > now {
+                "Not Started"
+            } else if row.end_time >= now {
+                "Active"
+            } else if sold_listing_ids.contains(&row.id) {
+                "Sold"
+            } else {
+                "Unsold"
+            };
+
+            json!({
+                "id": row.id,
+                "title": row.title,
+                "description": row.description,
+                "base_price": row.base_price,
+                "available_volume": row.available_volume,
+                "clearing_rule": row.clearing_rule,
+                "unit_of_measure": row.unit_of_measure,
+                "min_quantity": row.min_quantity,
+                "max_quantity": row.max_quantity,
+                "auction_id": row.auction_id,
+                "auction_name": row.name,
+                "format": row.format,
+                "start_time": row.start_time,
+                "end_time": row.end_time,
+                "status": status,
+                "total_bids": (!sealed).then_some(total_bids),
+                "best_bid": (!sealed).then(|| best_bid.unwrap_or(Decimal::ZERO)),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "listings": listings,
+            "message": "Seller listings fetched successfully".to_string()
+        })
+    )))
+}
+
+#[get("/seller/results")]
+pub async fn get_seller_results(
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let seller_id = session_user_id(&session)?;
+
+    let statement = SellerStatement::load(&app_state.db, seller_id, None, None).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "results": statement.lines.iter().map(|line| line.to_json()).collect::<Vec<_>>(),
+            "message": "Seller results fetched successfully".to_string()
+        })
+    )))
+}
+
+#[get("/seller/statement/{auction_id}")]
+pub async fn get_seller_statement(
+    app_state: web::Data<AppState>,
+    path: web::Path<i32>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let seller_id = session_user_id(&session)?;
+    let auction_id = path.into_inner();
+
+    let statement = SellerStatement::load(&app_state.db, seller_id, Some(auction_id), None).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "auction_id": auction_id,
+            "statement": statement.to_json(),
+            "message": "Settlement statement fetched successfully".to_string()
+        })
+    )))
+}
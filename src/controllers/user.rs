@@ -1,18 +1,34 @@
-use actix_web::{delete, get, post, web::{self}};
-use chrono::Utc;
+use actix_web::{delete, get, post, put, web::{self}};
+use chrono::{NaiveTime, Utc};
+use actix_session::Session;
+use entity::sea_orm_active_enums::{NotificationCategory, OneTimeCodePurpose, PreferredLanguage, UserRole};
 use regex::Regex;
-use sea_orm::{ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter, Set, ActiveModelTrait};
+use sea_orm::{ColumnTrait, EntityTrait, IntoActiveModel, PaginatorTrait, QueryFilter, Set, ActiveModelTrait};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::utils::{api_response::ApiResponse, app_state::AppState, json_response::response, whatsapp::send_whatsapp_message};
+use crate::{
+    helpers::{
+        notifications::notify_user,
+        one_time_codes::{issue_code, verify_code},
+        phone_changes::check_number_available,
+        privacy::erase_user,
+        staff::require_admin,
+    },
+    utils::{
+        api_response::ApiResponse, app_state::AppState, json_response::response, phone::normalize_phone_number,
+        session::{session_user_id, sign_in},
+    },
+};
 
 #[get("/user/get/{phone_number}")]
 pub async fn get_user(
     app_state: web::Data<AppState>,
     path: web::Path<String>,
 ) -> Result<ApiResponse, ApiResponse> {
+    // Numbers are looked up in E.164 whichever form they were given in
     let phone_number = path.into_inner();
+    let phone_number = normalize_phone_number(&phone_number).unwrap_or(phone_number);
 
     let users_model = entity::users::Entity::find()
         .filter(entity::users::Column::DeletedAt.is_null())
@@ -38,6 +54,7 @@ pub async fn get_user(
         json!({
             "phone_number": users_model.phone_number,
             "name": users_model.name,
+            "role": users_model.role,
             "message": "User data retrieved successfully".to_string()
         })
     )))
@@ -50,7 +67,8 @@ struct UserData {
 }
 
 impl UserData {
-    pub fn validate(&self) -> Result<(), String> {
+    /// Checks the details and rewrites the phone number in E.164.
+    pub fn validate(&mut self) -> Result<(), String> {
         if self.name.is_empty() {
             return Err("Name is required".to_string());
         }
@@ -59,24 +77,21 @@ impl UserData {
             return Err("Phone number is required".to_string());
         }
 
-        if !Self::validate_phone_number(&self.phone_number) {
-            return Err("Phone number is not valid.".to_string());
-        }
+        self.phone_number = normalize_phone_number(&self.phone_number)?;
 
         Ok(())
     }
-
-    fn validate_phone_number(phone_number: &str) -> bool {
-        let phone_regex = Regex::new(r"^\+254\d{9}$").unwrap();
-        phone_regex.is_match(phone_number)
-    }
 }
 
+/// Sends a sign-in code to the number. Nothing is signed in or created until
+/// the code comes back through `/user/verify`, so holding the number is what
+/// proves the account is theirs.
 #[post("/user/create")]
 pub async fn create_user(
     app_state: web::Data<AppState>,
     user_data: web::Json<UserData>
 ) -> Result<ApiResponse, ApiResponse> {
+    let mut user_data = user_data.into_inner();
     if let Err(err) = user_data.validate() {
         return Err(ApiResponse::new(500, response(
             json!({
@@ -98,15 +113,56 @@ pub async fn create_user(
             ))
         })?;
 
-    if let Some(user) = user_model {
-        let mut update_user_model: entity::users::ActiveModel = user.to_owned().into_active_model();
-        update_user_model.name = Set(user_data.name.clone());
-        update_user_model.phone_number = Set(user_data.phone_number.clone());
-        if user.name.clone() != user_data.name && user.phone_number.clone() != user_data.phone_number {
-            update_user_model.updated_at = Set(Utc::now().naive_utc());
-        } 
-        update_user_model
-            .update(&app_state.db)
+    // A known number signs its account in as it stands. Names are changed
+    // through the profile and numbers through the phone change flow.
+    let (code_model, code_sent) = match user_model {
+        Some(user) => {
+            issue_code(&app_state.db, &user.phone_number, OneTimeCodePurpose::SignIn, Some(user.id), None).await?
+        }
+        None => {
+            check_number_available(&app_state.db, &user_data.phone_number, None).await?;
+            issue_code(&app_state.db, &user_data.phone_number, OneTimeCodePurpose::SignIn, None, Some(user_data.name.clone())).await?
+        }
+    };
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "code_sent": code_sent,
+            "expires_at": code_model.expires_at,
+            "message": "Enter the code sent to your phone to continue".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SignInVerificationData {
+    phone_number: String,
+    code: String,
+}
+
+/// Signs in the holder of a sign-in code, creating their account first when
+/// the number is new.
+#[post("/user/verify")]
+pub async fn verify_user(
+    app_state: web::Data<AppState>,
+    session: Session,
+    verification_data: web::Json<SignInVerificationData>
+) -> Result<ApiResponse, ApiResponse> {
+    let phone_number = normalize_phone_number(&verification_data.phone_number).map_err(|err| {
+        ApiResponse::new(400, response(
+            json!({
+                "error": err
+            })
+        ))
+    })?;
+
+    let code_model = verify_code(&app_state.db, &phone_number, OneTimeCodePurpose::SignIn, &verification_data.code).await?;
+
+    if let Some(user_id) = code_model.user_id {
+        let user = entity::users::Entity::find_by_id(user_id)
+            .filter(entity::users::Column::DeletedAt.is_null())
+            .filter(entity::users::Column::PhoneNumber.eq(phone_number))
+            .one(&app_state.db)
             .await
             .map_err(|err| {
                 ApiResponse::new(500, response(
@@ -114,20 +170,21 @@ pub async fn create_user(
                         "error": err.to_string()
                     })
                 ))
+            })?
+            .ok_or_else(|| {
+                ApiResponse::new(404, response(
+                    json!({
+                        "error": "User not found.".to_string()
+                    })
+                ))
             })?;
 
-        if user.name.clone() != user_data.name && user.phone_number.clone() != user_data.phone_number {
-            send_whatsapp_message(&user.phone_number.trim_start_matches('+'), "User updated successfully. Continue to auction").await?;
-
-            return Ok(ApiResponse::new(200, response(
-                json!({
-                    "user_id": user.id,
-                    "message": "User updated successfully. Continue to auction".to_string()
-                })
-            )));
-        }
-
-        send_whatsapp_message(&user.phone_number.trim_start_matches('+'), "User exists, continue to auction").await?;
+        sign_in(&session, user.id)?;
+        notify_user(&app_state.db, &user, NotificationCategory::Account, |language| match language {
+            PreferredLanguage::En => "User exists, continue to auction".to_string(),
+            PreferredLanguage::Sw => "Karibu tena, endelea kwenye mnada".to_string(),
+        })
+        .await?;
 
         return Ok(ApiResponse::new(200, response(
             json!({
@@ -137,9 +194,12 @@ pub async fn create_user(
         )));
     }
 
+    // The number may have been taken while the code was on its way
+    check_number_available(&app_state.db, &phone_number, None).await?;
+
     let user_model_create = entity::users::ActiveModel {
-        name: Set(user_data.name.clone()),
-        phone_number: Set(user_data.phone_number.clone()),
+        name: Set(code_model.name.unwrap_or_default()),
+        phone_number: Set(phone_number),
         ..Default::default()
     }
         .insert(&app_state.db)
@@ -152,7 +212,12 @@ pub async fn create_user(
             ))
         })?;
 
-    send_whatsapp_message(&user_model_create.phone_number.trim_start_matches('+'), "User created successfully").await?;
+    sign_in(&session, user_model_create.id)?;
+    notify_user(&app_state.db, &user_model_create, NotificationCategory::Account, |language| match language {
+        PreferredLanguage::En => "User created successfully".to_string(),
+        PreferredLanguage::Sw => "Akaunti yako imeundwa".to_string(),
+    })
+    .await?;
 
     Ok(ApiResponse::new(200, response(
         json!({
@@ -183,6 +248,7 @@ pub async fn get_all_users(
                 "id": row.id,
                 "name": row.name,
                 "phone_number": row.phone_number,
+                "role": row.role,
                 "updated_at": row.updated_at,
             })
         })
@@ -196,15 +262,81 @@ pub async fn get_all_users(
     )))
 }
 
+#[derive(Debug, Deserialize)]
+struct UserRoleData {
+    role: UserRole,
+}
+
+/// Makes a user a bidder, staff member or admin. Only admins can change
+/// roles; the first admin is set up directly in the database.
+#[put("/user/role/{id}")]
+pub async fn update_user_role(
+    app_state: web::Data<AppState>,
+    session: Session,
+    path: web::Path<i32>,
+    role_data: web::Json<UserRoleData>,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_id = path.into_inner();
+
+    require_admin(&app_state.db, &session).await?;
+
+    let user_model = entity::users::Entity::find_by_id(user_id)
+        .filter(entity::users::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "User not found.".to_string()
+                })
+            ))
+        })?;
+
+    let mut update_user_model: entity::users::ActiveModel = user_model.into_active_model();
+    update_user_model.role = Set(role_data.role.clone());
+    update_user_model.updated_at = Set(Utc::now().naive_utc());
+    update_user_model
+        .update(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "user_id": user_id,
+            "role": role_data.role,
+            "message": "User role updated successfully".to_string()
+        })
+    )))
+}
+
+/// Erases a user on an admin's say-so. Accounts are never deleted outright:
+/// they are pseudonymised so their bids and financial records survive.
 #[delete("/user/delete/{id}")]
 pub async fn delete_user(
     app_state: web::Data<AppState>,
+    session: Session,
     path: web::Path<i32>,
 ) -> Result<ApiResponse, ApiResponse> {
     let user_id = path.into_inner();
 
-    let _users_model = entity::users::Entity::delete_by_id(user_id)
-        .exec(&app_state.db)
+    require_admin(&app_state.db, &session).await?;
+
+    let user_model = entity::users::Entity::find_by_id(user_id)
+        .filter(entity::users::Column::DeletedAt.is_null())
+        .one(&app_state.db)
         .await
         .map_err(|err| {
             ApiResponse::new(500, response(
@@ -212,11 +344,244 @@ pub async fn delete_user(
                     "error": err.to_string()
                 })
             ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(404, response(
+                json!({
+                    "error": "User not found.".to_string()
+                })
+            ))
         })?;
 
+    erase_user(&app_state.db, user_model).await?;
+
     Ok(ApiResponse::new(200, response(
         json!({
             "message": "User deleted successfully".to_string()
         })
     )))
-} 
\ No newline at end of file
+}
+
+fn profile_json(user: &entity::users::Model) -> serde_json::Value {
+    json!({
+        "id": user.id,
+        "name": user.name,
+        "phone_number": user.phone_number,
+        "email": user.email,
+        "county": user.county,
+        "location": user.location,
+        "preferred_language": user.preferred_language,
+        "notify_whatsapp": user.notify_whatsapp,
+        "notify_email": user.notify_email,
+        "quiet_hours_start": user.quiet_hours_start,
+        "quiet_hours_end": user.quiet_hours_end,
+        "utc_offset_minutes": user.utc_offset_minutes,
+        "marketing_consent": user.marketing_consent,
+        "marketing_consent_at": user.marketing_consent_at,
+        "updated_at": user.updated_at,
+    })
+}
+
+pub async fn find_session_user(app_state: &AppState, session: &Session) -> Result<entity::users::Model, ApiResponse> {
+    let user_id = session_user_id(session)?;
+
+    entity::users::Entity::find_by_id(user_id)
+        .filter(entity::users::Column::DeletedAt.is_null())
+        .one(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?
+        .ok_or_else(|| {
+            ApiResponse::new(401, response(
+                json!({
+                    "error": "Sign in to continue".to_string()
+                })
+            ))
+        })
+}
+
+#[get("/user/profile")]
+pub async fn get_profile(
+    app_state: web::Data<AppState>,
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    let user_model = find_session_user(&app_state, &session).await?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "profile": profile_json(&user_model),
+            "message": "Profile fetched successfully".to_string()
+        })
+    )))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ProfileData {
+    name: String,
+    email: Option<String>,
+    county: Option<String>,
+    location: Option<String>,
+    preferred_language: PreferredLanguage,
+    notify_whatsapp: bool,
+    notify_email: bool,
+    /// Local times between which only account messages are sent; the rest
+    /// wait until quiet hours end.
+    quiet_hours_start: Option<NaiveTime>,
+    quiet_hours_end: Option<NaiveTime>,
+    /// The user's offset from UTC, used to place their quiet hours.
+    #[serde(default = "default_utc_offset_minutes")]
+    utc_offset_minutes: i32,
+    marketing_consent: bool,
+}
+
+fn default_utc_offset_minutes() -> i32 {
+    180
+}
+
+impl ProfileData {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Name is required".to_string());
+        }
+
+        if let Some(email) = &self.email {
+            let email_regex = Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$").unwrap();
+            if !email_regex.is_match(email.trim()) {
+                return Err("Email is not valid".to_string());
+            }
+        }
+
+        if self.county.as_ref().is_some_and(|county| county.trim().is_empty()) {
+            return Err("County cannot be blank".to_string());
+        }
+
+        if self.location.as_ref().is_some_and(|location| location.trim().is_empty()) {
+            return Err("Location cannot be blank".to_string());
+        }
+
+        if self.notify_email && self.email.is_none() {
+            return Err("An email address is needed for email notifications".to_string());
+        }
+
+        if !self.notify_whatsapp && !self.notify_email {
+            return Err("Choose at least one notification channel".to_string());
+        }
+
+        match (self.quiet_hours_start, self.quiet_hours_end) {
+            (None, None) => {}
+            (Some(start), Some(end)) if start == end => {
+                return Err("Quiet hours must start and end at different times".to_string());
+            }
+            (Some(_), Some(_)) => {}
+            _ => return Err("Quiet hours need both a start and an end".to_string()),
+        }
+
+        if !(-720..=840).contains(&self.utc_offset_minutes) {
+            return Err("UTC offset must be between -720 and 840 minutes".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces the signed-in user's profile and notification preferences.
+#[put("/user/profile")]
+pub async fn update_profile(
+    app_state: web::Data<AppState>,
+    session: Session,
+    profile_data: web::Json<ProfileData>,
+) -> Result<ApiResponse, ApiResponse> {
+    if let Err(err) = profile_data.validate() {
+        return Err(ApiResponse::new(400, response(
+            json!({
+                "error": err.to_string()
+            })
+        )));
+    }
+
+    let user_model = find_session_user(&app_state, &session).await?;
+
+    let email = profile_data.email.as_ref().map(|email| email.trim().to_lowercase());
+
+    if let Some(email) = &email {
+        let taken = entity::users::Entity::find()
+            .filter(entity::users::Column::Email.eq(email.clone()))
+            .filter(entity::users::Column::Id.ne(user_model.id))
+            .count(&app_state.db)
+            .await
+            .map_err(|err| {
+                ApiResponse::new(500, response(
+                    json!({
+                        "error": err.to_string()
+                    })
+                ))
+            })?;
+
+        if taken > 0 {
+            return Err(ApiResponse::new(400, response(
+                json!({
+                    "error": "This email address is used by another account".to_string()
+                })
+            )));
+        }
+    }
+
+    let now = Utc::now().naive_utc();
+
+    // Consent is dated from when it was first given
+    let marketing_consent_at = match (profile_data.marketing_consent, user_model.marketing_consent_at) {
+        (false, _) => None,
+        (true, Some(consented_at)) => Some(consented_at),
+        (true, None) => Some(now),
+    };
+
+    let mut update_user_model: entity::users::ActiveModel = user_model.into_active_model();
+    update_user_model.name = Set(profile_data.name.trim().to_string());
+    update_user_model.email = Set(email);
+    update_user_model.county = Set(profile_data.county.as_ref().map(|county| county.trim().to_string()));
+    update_user_model.location = Set(profile_data.location.as_ref().map(|location| location.trim().to_string()));
+    update_user_model.preferred_language = Set(profile_data.preferred_language.clone());
+    update_user_model.notify_whatsapp = Set(profile_data.notify_whatsapp);
+    update_user_model.notify_email = Set(profile_data.notify_email);
+    update_user_model.quiet_hours_start = Set(profile_data.quiet_hours_start);
+    update_user_model.quiet_hours_end = Set(profile_data.quiet_hours_end);
+    update_user_model.utc_offset_minutes = Set(profile_data.utc_offset_minutes);
+    update_user_model.marketing_consent = Set(profile_data.marketing_consent);
+    update_user_model.marketing_consent_at = Set(marketing_consent_at);
+    update_user_model.updated_at = Set(now);
+    let user_model = update_user_model
+        .update(&app_state.db)
+        .await
+        .map_err(|err| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?;
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "profile": profile_json(&user_model),
+            "message": "Profile updated successfully".to_string()
+        })
+    )))
+}
+
+#[post("/user/logout")]
+pub async fn logout(
+    session: Session,
+) -> Result<ApiResponse, ApiResponse> {
+    session.purge();
+
+    Ok(ApiResponse::new(200, response(
+        json!({
+            "message": "Signed out successfully".to_string()
+        })
+    )))
+}
\ No newline at end of file
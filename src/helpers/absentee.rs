@@ -0,0 +1,213 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use entity::sea_orm_active_enums::BidChannel;
+use rust_decimal::Decimal;
+use sea_orm::{ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, Set};
+use serde_json::json;
+
+use crate::{
+    helpers::{
+        bidder_limits::check_bid_within_limit,
+        clearing::{allocate, is_better_offer, is_reverse, price_to_beat},
+    },
+    utils::{api_response::ApiResponse, json_response::response},
+};
+
+/// How far an absentee bid moves at a time when the auction sets no increment.
+const MIN_ABSENTEE_STEP: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+
+/// One increment better than `price`.
+fn step_past(auction: &entity::auctions::Model, price: Decimal) -> Decimal {
+    let step = auction.bid_increment.unwrap_or(MIN_ABSENTEE_STEP);
+
+    if is_reverse(auction) {
+        price - step
+    } else {
+        price + step
+    }
+}
+
+/// What an absentee bid should stand at to lead: one increment better than
+/// the offer it has to beat, or where it already stands (the base price for
+/// a new bidder) when nothing is in its way. Never beyond the bidder's
+/// written maximum.
+pub fn absentee_amount(
+    auction: &entity::auctions::Model,
+    listing: &entity::listings::Model,
+    bids: &[entity::bids::Model],
+    user_id: i32,
+    max_amount: Decimal,
+) -> Decimal {
+    let amount = match price_to_beat(auction, listing, bids, user_id) {
+        Some(price_to_beat) => step_past(auction, price_to_beat),
+        None => bids
+            .iter()
+            .filter(|bid| bid.listing_id == listing.id && bid.user_id == user_id && bid.deleted_at.is_none())
+            .max_by_key(|bid| bid.id)
+            .map_or(listing.base_price, |bid| bid.amount),
+    };
+
+    if is_better_offer(auction, amount, max_amount) {
+        max_amount
+    } else {
+        amount
+    }
+}
+
+async fn listing_bids<C: ConnectionTrait>(db: &C, listing_id: i32) -> Result<Vec<entity::bids::Model>, ApiResponse> {
+    entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .filter(entity::bids::Column::ListingId.eq(listing_id))
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))
+}
+
+/// Where each absentee bid comes to rest, worked out in one go rather than
+/// an increment at a time. Every absentee bidder is put at their ceiling to
+/// see who would still win: the rest are outbid whatever they do, so they
+/// go all the way to their ceiling, while each winner only goes one
+/// increment past the offer they then have to beat, and never beyond their
+/// own ceiling. For a single lot that is the second best maximum plus one
+/// increment. Ties between ceilings go to the earlier absentee bid.
+fn settle_absentee_bids(
+    auction: &entity::auctions::Model,
+    listing: &entity::listings::Model,
+    bids: &[entity::bids::Model],
+    absentee_bids: &[(&entity::bids::Model, Decimal)],
+) -> Vec<Decimal> {
+    let next_id = bids.iter().map(|bid| bid.id).max().unwrap_or_default() + 1;
+    let now = Utc::now().naive_utc();
+
+    let mut at_ceiling = bids.to_vec();
+    for (index, (bid, ceiling)) in absentee_bids.iter().enumerate() {
+        at_ceiling.push(entity::bids::Model {
+            id: next_id + index as i32,
+            amount: *ceiling,
+            created_at: now,
+            ..(*bid).clone()
+        });
+    }
+
+    let winners = allocate(auction, listing, &at_ceiling)
+        .into_iter()
+        .map(|award| award.user_id)
+        .collect::<HashSet<_>>();
+
+    absentee_bids
+        .iter()
+        .map(|(bid, ceiling)| {
+            if !winners.contains(&bid.user_id) {
+                return *ceiling;
+            }
+
+            let amount = match price_to_beat(auction, listing, &at_ceiling, bid.user_id) {
+                Some(price_to_beat) => step_past(auction, price_to_beat),
+                None => bid.amount,
+            };
+
+            if is_better_offer(auction, amount, *ceiling) {
+                *ceiling
+            } else if is_better_offer(auction, bid.amount, amount) {
+                bid.amount
+            } else {
+                amount
+            }
+        })
+        .collect()
+}
+
+/// Bids for absentee bidders the way staff would in the room: whenever an
+/// absentee bidder has been outbid and their written maximum still allows
+/// it, they are bid up until they lead by one increment or reach their
+/// maximum, so competing maximums settle one increment above the lower of
+/// them. The price is worked out directly and only each bidder's final bid
+/// is placed. Only a bidder's latest bid counts, so a later bid of their own
+/// without a maximum ends their absentee bidding, and a bidder whose final
+/// bid would go past their bidding limit stays where they are. The caller
+/// holds the listing lock. Returns the bids placed.
+pub async fn execute_absentee_bids<C: ConnectionTrait>(
+    db: &C,
+    auction: &entity::auctions::Model,
+    listing: &entity::listings::Model,
+) -> Result<Vec<entity::bids::Model>, ApiResponse> {
+    let bids = listing_bids(db, listing.id).await?;
+
+    let mut latest_bids: HashMap<i32, &entity::bids::Model> = HashMap::new();
+    for bid in &bids {
+        match latest_bids.get(&bid.user_id) {
+            Some(current) if current.id > bid.id => {}
+            _ => {
+                latest_bids.insert(bid.user_id, bid);
+            }
+        }
+    }
+
+    let mut absentee_bids = latest_bids
+        .into_values()
+        .filter(|bid| bid.channel == BidChannel::Absentee && bid.max_amount.is_some())
+        .collect::<Vec<_>>();
+    absentee_bids.sort_by_key(|bid| bid.id);
+
+    // Bidders held at their current bid because their limit will not stretch
+    // to where they would settle. Each pass holds one more, so this ends.
+    let mut held = HashSet::new();
+    let raises = loop {
+        let ceilings = absentee_bids
+            .iter()
+            .map(|bid| {
+                let ceiling = bid
+                    .max_amount
+                    .filter(|max_amount| !held.contains(&bid.user_id) && is_better_offer(auction, *max_amount, bid.amount))
+                    .unwrap_or(bid.amount);
+
+                (*bid, ceiling)
+            })
+            .collect::<Vec<_>>();
+
+        let raises = settle_absentee_bids(auction, listing, &bids, &ceilings)
+            .into_iter()
+            .zip(absentee_bids.iter())
+            .filter(|(amount, bid)| is_better_offer(auction, *amount, bid.amount))
+            .map(|(amount, bid)| (*bid, amount))
+            .collect::<Vec<_>>();
+
+        let mut over_limit = None;
+        for (bid, amount) in &raises {
+            if check_bid_within_limit(db, auction, bid.user_id, listing.id, *amount, bid.quantity).await.is_err() {
+                over_limit = Some(bid.user_id);
+                break;
+            }
+        }
+
+        match over_limit {
+            Some(user_id) => {
+                held.insert(user_id);
+            }
+            None => break raises,
+        }
+    };
+
+    let mut placed = Vec::new();
+    for (bid, amount) in raises {
+        let raised_bid = entity::bids::ActiveModel {
+            listing_id: Set(listing.id),
+            user_id: Set(bid.user_id),
+            amount: Set(amount),
+            quantity: Set(bid.quantity),
+            channel: Set(BidChannel::Absentee),
+            placed_by_id: Set(bid.placed_by_id),
+            note: Set(bid.note.clone()),
+            max_amount: Set(bid.max_amount),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        placed.push(raised_bid);
+    }
+
+    Ok(placed)
+}
@@ -0,0 +1,145 @@
+use chrono::{NaiveDateTime, Utc};
+use entity::sea_orm_active_enums::BidAuditAction;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, Set, TransactionTrait,
+};
+use serde_json::json;
+
+use crate::{
+    helpers::{
+        clearing::{allocate, bidding_closes_at},
+        dutch::is_dutch,
+    },
+    utils::{api_response::ApiResponse, constants, json_response::response},
+};
+
+/// Applies the retraction rules to a bidder taking back their own bid: only
+/// within the retraction window after placing it, never once bidding has
+/// closed, and not while they lead in the lockout period before close.
+/// Dutch acceptances are purchases and revealed commitments are final, so
+/// neither can be retracted.
+fn check_retraction(
+    auction: &entity::auctions::Model,
+    listing: &entity::listings::Model,
+    bid: &entity::bids::Model,
+    bids: &[entity::bids::Model],
+    user_id: i32,
+    now: NaiveDateTime,
+) -> Result<(), ApiResponse> {
+    if bid.user_id != user_id {
+        return Err(ApiResponse::new(403, response(json!({ "error": "You can only retract your own bids" }))));
+    }
+
+    if is_dutch(auction) || auction.reveal_end_time.is_some() {
+        return Err(ApiResponse::new(400, response(json!({ "error": "Bids in this auction cannot be retracted" }))));
+    }
+
+    let closes_at = bidding_closes_at(auction);
+    if closes_at < now {
+        return Err(ApiResponse::new(400, response(json!({ "error": "Bidding has closed" }))));
+    }
+
+    let window_seconds = *constants::BID_RETRACTION_WINDOW_SECONDS;
+    if (now - bid.created_at).num_seconds() > window_seconds {
+        return Err(ApiResponse::new(400, response(json!({
+            "error": format!("Bids can only be retracted within {} seconds of being placed", window_seconds)
+        }))));
+    }
+
+    let lockout_seconds = *constants::BID_RETRACTION_CLOSE_LOCKOUT_SECONDS;
+    if (closes_at - now).num_seconds() <= lockout_seconds
+        && allocate(auction, listing, bids).iter().any(|award| award.user_id == user_id)
+    {
+        return Err(ApiResponse::new(400, response(json!({
+            "error": format!("Leading bids cannot be retracted in the last {} seconds before close", lockout_seconds)
+        }))));
+    }
+
+    Ok(())
+}
+
+/// Takes a bid back, either retracted by its bidder under the retraction
+/// rules or voided by an admin. The bid is soft deleted and an audit record
+/// names who took it back and why, in one transaction. The listing stays
+/// locked meanwhile so no bid is judged against the bid being removed.
+/// Bids on listings that already have results cannot be taken back.
+pub async fn withdraw_bid(
+    db: &DatabaseConnection,
+    bid_id: i32,
+    action: BidAuditAction,
+    actor_id: i32,
+    reason: Option<String>,
+) -> Result<(entity::auctions::Model, entity::listings::Model, entity::bids::Model), ApiResponse> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let bid = entity::bids::Entity::find_by_id(bid_id)
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .one(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .ok_or_else(|| ApiResponse::new(404, response(json!({ "error": "Bid not found" }))))?;
+
+    let listing = entity::listings::Entity::find_by_id(bid.listing_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .ok_or_else(|| ApiResponse::new(404, response(json!({ "error": "Listing not found" }))))?;
+
+    let auction = entity::auctions::Entity::find_by_id(listing.auction_id)
+        .one(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .ok_or_else(|| ApiResponse::new(404, response(json!({ "error": "Auction not found" }))))?;
+
+    let existing_result = entity::auction_results::Entity::find()
+        .filter(entity::auction_results::Column::ListingId.eq(listing.id))
+        .one(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    if existing_result.is_some() || listing.closed_at.is_some() {
+        return Err(ApiResponse::new(400, response(json!({ "error": "This listing has already closed" }))));
+    }
+
+    let now = Utc::now().naive_utc();
+
+    if action == BidAuditAction::Retracted {
+        let bids = entity::bids::Entity::find()
+            .filter(entity::bids::Column::DeletedAt.is_null())
+            .filter(entity::bids::Column::ListingId.eq(listing.id))
+            .all(&txn)
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        check_retraction(&auction, &listing, &bid, &bids, actor_id, now)?;
+    }
+
+    let mut update_bid: entity::bids::ActiveModel = bid.into();
+    update_bid.deleted_at = Set(Some(now));
+    update_bid.updated_at = Set(now);
+    let bid = update_bid
+        .update(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    entity::bid_audits::ActiveModel {
+        bid_id: Set(bid.id),
+        action: Set(action),
+        actor_id: Set(actor_id),
+        reason: Set(reason),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    txn.commit()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    Ok((auction, listing, bid))
+}
@@ -0,0 +1,215 @@
+use chrono::Utc;
+use entity::sea_orm_active_enums::{DepositStatus, InvoiceStatus};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect,
+    Set, TransactionTrait,
+};
+use serde_json::json;
+
+use crate::{
+    helpers::{
+        clearing::{allocate, is_sealed},
+        ledger::{
+            account_balance, lock_user_account, post_invoice_payment, post_journal_entry, Posting, PaymentMethod,
+            UserAccount,
+        },
+    },
+    utils::{api_response::ApiResponse, constants, json_response::response},
+};
+
+/// Auctions with a deposit or credit approval requirement limit how much a
+/// bidder may have riding on leading bids at once.
+pub fn is_restricted(auction: &entity::auctions::Model) -> bool {
+    auction.deposit_amount.is_some() || auction.requires_credit_approval
+}
+
+/// The most a bidder may have on leading bids in a restricted auction. An
+/// approved credit limit wins; otherwise a held deposit that meets the
+/// auction's requirement buys `DEPOSIT_BIDDING_MULTIPLIER` times its value.
+pub fn effective_limit(auction: &entity::auctions::Model, bidder_limit: Option<&entity::bidder_limits::Model>) -> Decimal {
+    let Some(bidder_limit) = bidder_limit else {
+        return Decimal::ZERO;
+    };
+
+    if let (Some(credit_limit), Some(_)) = (bidder_limit.credit_limit, bidder_limit.approved_at) {
+        return credit_limit;
+    }
+
+    match auction.deposit_amount {
+        Some(required) if bidder_limit.deposit_status == Some(DepositStatus::Held) && bidder_limit.deposit_amount >= required => {
+            bidder_limit.deposit_amount * *constants::DEPOSIT_BIDDING_MULTIPLIER
+        }
+        _ => Decimal::ZERO,
+    }
+}
+
+/// What the user would pay for the units their bids are currently winning
+/// across the auction's listings, leaving out `exclude_listing_id` when given.
+/// In a sealed auction whether a bid is winning is not known until close, so
+/// every standing bid counts in full.
+pub async fn leading_exposure<C: ConnectionTrait>(
+    db: &C,
+    auction: &entity::auctions::Model,
+    user_id: i32,
+    exclude_listing_id: Option<i32>,
+) -> Result<Decimal, ApiResponse> {
+    let listings = entity::listings::Entity::find()
+        .filter(entity::listings::Column::DeletedAt.is_null())
+        .filter(entity::listings::Column::AuctionId.eq(auction.id))
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let bids = entity::bids::Entity::find()
+        .inner_join(entity::listings::Entity)
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .filter(entity::listings::Column::AuctionId.eq(auction.id))
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let listings = listings.iter().filter(|listing| Some(listing.id) != exclude_listing_id);
+
+    if is_sealed(auction) {
+        return Ok(listings
+            .filter_map(|listing| {
+                bids.iter()
+                    .filter(|bid| bid.listing_id == listing.id && bid.user_id == user_id)
+                    .max_by_key(|bid| bid.id)
+            })
+            .map(|bid| bid.amount * Decimal::from(bid.quantity))
+            .sum());
+    }
+
+    Ok(listings
+        .flat_map(|listing| allocate(auction, listing, &bids))
+        .filter(|award| award.user_id == user_id)
+        .map(|award| award.hammer_price())
+        .sum())
+}
+
+/// Rejects a bid in a restricted auction when the bidder has not qualified or
+/// when leading with it would take their exposure past their limit.
+pub async fn check_bid_within_limit<C: ConnectionTrait>(
+    db: &C,
+    auction: &entity::auctions::Model,
+    user_id: i32,
+    listing_id: i32,
+    amount: Decimal,
+    quantity: i32,
+) -> Result<(), ApiResponse> {
+    if !is_restricted(auction) {
+        return Ok(());
+    }
+
+    let bidder_limit = entity::bidder_limits::Entity::find()
+        .filter(entity::bidder_limits::Column::DeletedAt.is_null())
+        .filter(entity::bidder_limits::Column::AuctionId.eq(auction.id))
+        .filter(entity::bidder_limits::Column::UserId.eq(user_id))
+        .lock_exclusive()
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let limit = effective_limit(auction, bidder_limit.as_ref());
+    if limit <= Decimal::ZERO {
+        return Err(ApiResponse::new(403, response(json!({
+            "error": "This auction requires a deposit or an approved credit limit before you can bid"
+        }))));
+    }
+
+    let exposure = leading_exposure(db, auction, user_id, Some(listing_id)).await?;
+    let amount = amount * Decimal::from(quantity);
+    if exposure + amount > limit {
+        return Err(ApiResponse::new(400, response(json!({
+            "error": format!(
+                "This bid would take your exposure to {} which is above your limit of {}",
+                exposure + amount, limit
+            ),
+            "limit": limit,
+            "exposure": exposure,
+        }))));
+    }
+
+    Ok(())
+}
+
+/// Settles every deposit still held on the given ended auctions. The deposit
+/// goes back to the bidder's wallet and, when the bidder won and the wallet
+/// now covers their invoice, is applied to paying it.
+pub async fn settle_deposits(db: &DatabaseConnection, auction_ids: Vec<i32>) -> Result<(), ApiResponse> {
+    let held_deposits = entity::bidder_limits::Entity::find()
+        .filter(entity::bidder_limits::Column::DeletedAt.is_null())
+        .filter(entity::bidder_limits::Column::AuctionId.is_in(auction_ids))
+        .filter(entity::bidder_limits::Column::DepositStatus.eq(DepositStatus::Held))
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    for bidder_limit in held_deposits {
+        let txn = db
+            .begin()
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        let deposit_account = lock_user_account(&txn, bidder_limit.user_id, UserAccount::Deposit).await?;
+        let wallet = lock_user_account(&txn, bidder_limit.user_id, UserAccount::Wallet).await?;
+
+        post_journal_entry(
+            &txn,
+            &format!("deposit:{}:release", bidder_limit.id),
+            &format!("Deposit released for auction {}", bidder_limit.auction_id),
+            vec![
+                Posting::debit(deposit_account, bidder_limit.deposit_amount),
+                Posting::credit(wallet, bidder_limit.deposit_amount),
+            ],
+        )
+        .await?;
+
+        let invoice = entity::invoices::Entity::find()
+            .filter(entity::invoices::Column::DeletedAt.is_null())
+            .filter(entity::invoices::Column::AuctionId.eq(bidder_limit.auction_id))
+            .filter(entity::invoices::Column::UserId.eq(bidder_limit.user_id))
+            .filter(entity::invoices::Column::Status.eq(InvoiceStatus::Issued))
+            .lock_exclusive()
+            .one(&txn)
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        let now = Utc::now().naive_utc();
+        let mut deposit_status = DepositStatus::Released;
+
+        if let Some(invoice) = invoice {
+            if account_balance(&txn, wallet).await? >= invoice.total {
+                post_invoice_payment(&txn, &invoice, PaymentMethod::Wallet).await?;
+
+                let mut update_invoice_model: entity::invoices::ActiveModel = invoice.into();
+                update_invoice_model.status = Set(InvoiceStatus::Paid);
+                update_invoice_model.paid_at = Set(Some(now));
+                update_invoice_model.updated_at = Set(now);
+                update_invoice_model
+                    .update(&txn)
+                    .await
+                    .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+                deposit_status = DepositStatus::Applied;
+            }
+        }
+
+        let mut update_bidder_limit_model: entity::bidder_limits::ActiveModel = bidder_limit.into();
+        update_bidder_limit_model.deposit_status = Set(Some(deposit_status));
+        update_bidder_limit_model.settled_at = Set(Some(now));
+        update_bidder_limit_model.updated_at = Set(now);
+        update_bidder_limit_model
+            .update(&txn)
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        txn.commit()
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+    }
+
+    Ok(())
+}
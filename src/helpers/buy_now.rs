@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDateTime, Utc};
+use entity::sea_orm_active_enums::{AuctionDirection, AuctionFormat};
+use migration::Expr;
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, Set,
+    TransactionTrait,
+};
+use serde_json::json;
+
+use crate::{
+    helpers::{bidder_limits::check_bid_within_limit, fees::{apply_fees, round_amount}},
+    utils::{api_response::ApiResponse, json_response::response},
+};
+
+/// Buy-now is only offered where bids are visible and the highest one wins,
+/// and not in live auctions, where lots are only sold from the block.
+pub fn supports_buy_now(auction: &entity::auctions::Model) -> bool {
+    auction.format == AuctionFormat::Open && auction.direction == AuctionDirection::Forward && !auction.live
+}
+
+/// Whether the listing can still be bought outright. The offer is withdrawn
+/// once the best bid reaches the listing's threshold, or as soon as anyone
+/// bids when it has no threshold.
+pub fn buy_now_available(
+    buy_now_price: Option<Decimal>,
+    buy_now_threshold: Option<Decimal>,
+    closed_at: Option<NaiveDateTime>,
+    best_bid: Option<Decimal>,
+) -> bool {
+    if buy_now_price.is_none() || closed_at.is_some() {
+        return false;
+    }
+
+    match (best_bid, buy_now_threshold) {
+        (None, _) => true,
+        (Some(best_bid), Some(threshold)) => best_bid < threshold,
+        (Some(_), None) => false,
+    }
+}
+
+pub async fn best_bid<C: ConnectionTrait>(db: &C, listing_id: i32) -> Result<Option<Decimal>, ApiResponse> {
+    entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .filter(entity::bids::Column::ListingId.eq(listing_id))
+        .select_only()
+        .column_as(Expr::col(entity::bids::Column::Amount).max(), "best_bid")
+        .into_tuple::<Option<Decimal>>()
+        .one(db)
+        .await
+        .map(Option::flatten)
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))
+}
+
+/// The best bid on each of `listing_ids` that has any, for listing pages
+/// that show buy-now availability across many listings at once.
+pub async fn best_bids<C: ConnectionTrait>(db: &C, listing_ids: Vec<i32>) -> Result<HashMap<i32, Decimal>, ApiResponse> {
+    entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .filter(entity::bids::Column::ListingId.is_in(listing_ids))
+        .select_only()
+        .column(entity::bids::Column::ListingId)
+        .column_as(Expr::col(entity::bids::Column::Amount).max(), "best_bid")
+        .group_by(entity::bids::Column::ListingId)
+        .into_tuple::<(i32, Decimal)>()
+        .all(db)
+        .await
+        .map(|rows| rows.into_iter().collect())
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))
+}
+
+/// Sells the listing's whole volume to `user_id` at its buy-now price. The
+/// bid, the result with its fees and the closing of the listing are stored
+/// together, and the listing row stays locked throughout so a bid or a
+/// second buyer cannot slip in between the availability check and the sale.
+pub async fn buy_listing_now(
+    db: &DatabaseConnection,
+    auction: &entity::auctions::Model,
+    listing_id: i32,
+    user_id: i32,
+) -> Result<(entity::listings::Model, entity::auction_results::Model), ApiResponse> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let listing = entity::listings::Entity::find_by_id(listing_id)
+        .filter(entity::listings::Column::DeletedAt.is_null())
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .ok_or_else(|| ApiResponse::new(404, response(json!({ "error": "Listing not found" }))))?;
+
+    if listing.closed_at.is_some() {
+        return Err(ApiResponse::new(400, response(json!({ "error": "This listing has already been sold" }))));
+    }
+
+    let best_bid = best_bid(&txn, listing.id).await?;
+
+    let Some(buy_now_price) = listing
+        .buy_now_price
+        .filter(|_| buy_now_available(listing.buy_now_price, listing.buy_now_threshold, listing.closed_at, best_bid)) else {
+        return Err(ApiResponse::new(400, response(json!({ "error": "Buy now is not available on this listing" }))));
+    };
+
+    check_bid_within_limit(&txn, auction, user_id, listing.id, buy_now_price, listing.available_volume).await?;
+
+    let bid = entity::bids::ActiveModel {
+        listing_id: Set(listing.id),
+        user_id: Set(user_id),
+        amount: Set(buy_now_price),
+        quantity: Set(listing.available_volume),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let auction_result = entity::auction_results::ActiveModel {
+        listing_id: Set(listing.id),
+        winning_bid_id: Set(bid.id),
+        winning_user_id: Set(user_id),
+        quantity: Set(bid.quantity),
+        unit_price: Set(buy_now_price),
+        hammer_price: Set(round_amount(buy_now_price * Decimal::from(bid.quantity))),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    apply_fees(&txn, auction.id, auction_result.id, auction_result.hammer_price).await?;
+
+    let now = Utc::now().naive_utc();
+    let mut update_listing_model: entity::listings::ActiveModel = listing.into();
+    update_listing_model.closed_at = Set(Some(now));
+    update_listing_model.updated_at = Set(now);
+    let listing = update_listing_model
+        .update(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    txn.commit()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    Ok((listing, auction_result))
+}
@@ -0,0 +1,56 @@
+use chrono::{Duration, NaiveDateTime};
+use rand::Rng;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+/// A fresh secret seed for drawing an auction's cutoff: 32 random bytes in hex.
+pub fn generate_seed() -> String {
+    hex::encode(rand::rng().random::<[u8; 32]>())
+}
+
+/// The hex SHA-256 of the seed. It is published before the auction starts so
+/// the seed, and with it the cutoff, cannot be changed afterwards.
+pub fn seed_hash(seed: &str) -> String {
+    hex::encode(Sha256::digest(seed.as_bytes()))
+}
+
+/// Candle auctions keep taking bids after their nominal end and close at a
+/// random moment before the candle end time: the end time plus an offset
+/// drawn from the first eight bytes of the SHA-256 of `"{auction_id}:{seed}"`,
+/// read as a big-endian integer and taken modulo the closing window in
+/// milliseconds. Anyone holding the revealed seed can repeat the calculation.
+pub fn cutoff_from_seed(auction_id: i32, end_time: NaiveDateTime, candle_end_time: NaiveDateTime, seed: &str) -> NaiveDateTime {
+    let window_ms = (candle_end_time - end_time).num_milliseconds().max(1) as u64;
+    let digest = Sha256::digest(format!("{}:{}", auction_id, seed).as_bytes());
+
+    let mut draw = [0u8; 8];
+    draw.copy_from_slice(&digest[..8]);
+
+    end_time + Duration::milliseconds((u64::from_be_bytes(draw) % window_ms) as i64)
+}
+
+pub fn cutoff_at(auction: &entity::auctions::Model) -> Option<NaiveDateTime> {
+    match (auction.candle_end_time, auction.candle_seed.as_deref()) {
+        (Some(candle_end_time), Some(seed)) => Some(cutoff_from_seed(auction.id, auction.end_time, candle_end_time, seed)),
+        _ => None,
+    }
+}
+
+/// The seed stays secret until the closing window is over, otherwise bidders
+/// could work out when the auction will close.
+pub fn seed_revealed(auction: &entity::auctions::Model, now: NaiveDateTime) -> bool {
+    auction.candle_end_time.is_some_and(|candle_end_time| candle_end_time < now)
+}
+
+pub fn candle_json(auction: &entity::auctions::Model, now: NaiveDateTime) -> Value {
+    let revealed = seed_revealed(auction, now);
+
+    json!({
+        "auction_id": auction.id,
+        "end_time": auction.end_time,
+        "candle_end_time": auction.candle_end_time,
+        "seed_hash": auction.candle_seed_hash,
+        "seed": auction.candle_seed.as_ref().filter(|_| revealed),
+        "cutoff": cutoff_at(auction).filter(|_| revealed),
+    })
+}
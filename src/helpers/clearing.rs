@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use entity::sea_orm_active_enums::{AuctionDirection, AuctionFormat, ClearingRule};
+use rust_decimal::Decimal;
+
+use crate::helpers::fees::round_amount;
+
+/// Units of a listing awarded to one bid.
+pub struct Award {
+    pub bid_id: i32,
+    pub user_id: i32,
+    pub quantity: i32,
+    pub unit_price: Decimal,
+}
+
+impl Award {
+    pub fn hammer_price(&self) -> Decimal {
+        round_amount(self.unit_price * Decimal::from(self.quantity))
+    }
+}
+
+pub fn is_sealed_format(format: &AuctionFormat) -> bool {
+    matches!(format, AuctionFormat::SealedFirstPrice | AuctionFormat::SealedSecondPrice)
+}
+
+pub fn is_sealed(auction: &entity::auctions::Model) -> bool {
+    is_sealed_format(&auction.format)
+}
+
+/// When bids stop changing: the end of the reveal window for commit-reveal
+/// auctions, the end of the closing window for candle auctions, otherwise the
+/// end of the auction.
+pub fn bidding_closes_at(auction: &entity::auctions::Model) -> NaiveDateTime {
+    auction.reveal_end_time.or(auction.candle_end_time).unwrap_or(auction.end_time)
+}
+
+/// Bids on a sealed auction, and how many there are, stay hidden from
+/// everyone until it closes.
+pub fn bids_hidden(auction: &entity::auctions::Model, now: NaiveDateTime) -> bool {
+    is_sealed(auction) && bidding_closes_at(auction) >= now
+}
+
+/// Reverse auctions buy rather than sell: suppliers bid the price down and
+/// the lowest offer is the best one.
+pub fn is_reverse(auction: &entity::auctions::Model) -> bool {
+    auction.direction == AuctionDirection::Reverse
+}
+
+/// Whether `amount` is a better offer than `other` in this auction.
+pub fn is_better_offer(auction: &entity::auctions::Model, amount: Decimal, other: Decimal) -> bool {
+    if is_reverse(auction) {
+        amount < other
+    } else {
+        amount > other
+    }
+}
+
+/// The best standing offer on a listing, counting only each bidder's latest
+/// bid.
+pub fn best_offer(
+    auction: &entity::auctions::Model,
+    listing: &entity::listings::Model,
+    bids: &[entity::bids::Model],
+) -> Option<Decimal> {
+    let mut latest_bids: HashMap<i32, &entity::bids::Model> = HashMap::new();
+    for bid in bids.iter().filter(|bid| bid.listing_id == listing.id && bid.deleted_at.is_none()) {
+        match latest_bids.get(&bid.user_id) {
+            Some(current) if current.id > bid.id => {}
+            _ => {
+                latest_bids.insert(bid.user_id, bid);
+            }
+        }
+    }
+
+    latest_bids
+        .into_values()
+        .map(|bid| bid.amount)
+        .reduce(|best, amount| if is_better_offer(auction, amount, best) { amount } else { best })
+}
+
+/// Allocates the listing's available volume to its bids, best price per unit
+/// first (highest, or lowest in a reverse auction) with earlier bids winning
+/// ties. Revealed bids carry the time they were committed, so ties go to
+/// whoever committed first. A bidder's latest bid on the listing replaces
+/// their earlier ones, and the last bid to be filled may only get part of
+/// the quantity it asked for, as long as that is not below the listing's
+/// minimum purchase.
+///
+/// In open auctions the listing's clearing rule sets the price: under
+/// discriminatory clearing every winner gets their own bid and under uniform
+/// clearing they all get the worst winning bid. Sealed first-price auctions
+/// always settle at the winner's own bid. Sealed second-price (Vickrey)
+/// auctions settle at the best losing bid, or the base price when every bid
+/// won, but never worse for the winner than what they offered. In Dutch
+/// auctions each acceptance is kept and pays the clock price it was taken at.
+pub fn allocate(
+    auction: &entity::auctions::Model,
+    listing: &entity::listings::Model,
+    bids: &[entity::bids::Model],
+) -> Vec<Award> {
+    let listing_bids = bids.iter().filter(|bid| bid.listing_id == listing.id && bid.deleted_at.is_none());
+
+    let mut standing_bids = if auction.format == AuctionFormat::Dutch {
+        // Every acceptance off the clock is a purchase in its own right
+        listing_bids.collect::<Vec<_>>()
+    } else {
+        let mut latest_bids: HashMap<i32, &entity::bids::Model> = HashMap::new();
+        for bid in listing_bids {
+            match latest_bids.get(&bid.user_id) {
+                Some(current) if current.id > bid.id => {}
+                _ => {
+                    latest_bids.insert(bid.user_id, bid);
+                }
+            }
+        }
+
+        latest_bids.into_values().collect::<Vec<_>>()
+    };
+
+    let reverse = is_reverse(auction);
+    standing_bids.sort_by(|a, b| {
+        let by_price = if reverse { a.amount.cmp(&b.amount) } else { b.amount.cmp(&a.amount) };
+
+        by_price
+            .then(a.created_at.cmp(&b.created_at))
+            .then(a.id.cmp(&b.id))
+    });
+
+    let max_quantity = listing.max_quantity.unwrap_or(listing.available_volume);
+
+    let mut remaining = listing.available_volume;
+    let mut awards = Vec::new();
+    let mut best_losing_bid = None;
+    for bid in standing_bids {
+        // A bid that can only be part filled below the minimum purchase is
+        // passed over for worse bids that still fit
+        let quantity = bid.quantity.min(max_quantity).min(remaining);
+        if quantity < listing.min_quantity {
+            best_losing_bid = best_losing_bid.or(Some(bid.amount));
+            continue;
+        }
+
+        remaining -= quantity;
+
+        awards.push(Award {
+            bid_id: bid.id,
+            user_id: bid.user_id,
+            quantity,
+            unit_price: bid.amount,
+        });
+    }
+
+    let clearing_price = match (&auction.format, &listing.clearing_rule) {
+        (AuctionFormat::SealedSecondPrice, _) => Some(best_losing_bid.unwrap_or(listing.base_price)),
+        (AuctionFormat::Open, ClearingRule::Uniform) if reverse => awards.iter().map(|award| award.unit_price).max(),
+        (AuctionFormat::Open, ClearingRule::Uniform) => awards.iter().map(|award| award.unit_price).min(),
+        _ => None,
+    };
+
+    if let Some(clearing_price) = clearing_price {
+        for award in &mut awards {
+            award.unit_price = if reverse {
+                award.unit_price.max(clearing_price)
+            } else {
+                award.unit_price.min(clearing_price)
+            };
+        }
+    }
+
+    awards
+}
+
+/// The offer a new bid from `user_id` has to improve on: the worst bid still
+/// winning units once other bidders have taken the listing's whole volume.
+/// Nothing has to be beaten while there is volume left over.
+pub fn price_to_beat(
+    auction: &entity::auctions::Model,
+    listing: &entity::listings::Model,
+    bids: &[entity::bids::Model],
+    user_id: i32,
+) -> Option<Decimal> {
+    let other_bids = bids.iter().filter(|bid| bid.user_id != user_id).cloned().collect::<Vec<_>>();
+    let awards = allocate(auction, listing, &other_bids);
+
+    if awards.iter().map(|award| award.quantity).sum::<i32>() < listing.available_volume {
+        return None;
+    }
+
+    let marginal_award = awards.last()?;
+
+    other_bids.iter().find(|bid| bid.id == marginal_award.bid_id).map(|bid| bid.amount)
+}
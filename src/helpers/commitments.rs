@@ -0,0 +1,78 @@
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::utils::constants;
+
+/// The hash a bidder commits to during the bidding window: the hex SHA-256
+/// of `"{listing_id}:{user_id}:{amount}:{quantity}:{nonce}"`, with the amount
+/// written to two decimal places, the scale bids are stored at. Bidders
+/// compute it themselves so the amount never reaches the server before the
+/// reveal.
+pub fn commitment_hash(listing_id: i32, user_id: i32, amount: Decimal, quantity: i32, nonce: &str) -> String {
+    let mut amount = amount;
+    amount.rescale(2);
+    let preimage = format!("{}:{}:{}:{}:{}", listing_id, user_id, amount, quantity, nonce);
+
+    hex::encode(Sha256::digest(preimage.as_bytes()))
+}
+
+/// A revealed amount has to fit the stored scale as it is. Rounding it would
+/// let one commitment open to several different amounts.
+pub fn fits_bid_scale(amount: Decimal) -> bool {
+    amount.normalize().scale() <= 2
+}
+
+pub fn is_valid_commitment(commitment: &str) -> bool {
+    commitment.len() == 64 && commitment.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Receipts are signed with Ed25519 so bidders can check them on their own
+/// with the published public key. The signing key is derived from
+/// `RECEIPT_SIGNING_KEY`, which never leaves the server.
+fn receipt_signing_key() -> SigningKey {
+    let seed: [u8; 32] = Sha256::digest(constants::RECEIPT_SIGNING_KEY.as_bytes()).into();
+
+    SigningKey::from_bytes(&seed)
+}
+
+/// The hex encoded Ed25519 public key that receipt signatures verify against.
+pub fn receipt_public_key() -> String {
+    hex::encode(receipt_signing_key().verifying_key().to_bytes())
+}
+
+/// The bytes a receipt signature covers: the payload as compact JSON. serde_json
+/// writes object keys in sorted order, so the same payload always signs the same way.
+fn receipt_message(payload: &Value) -> Vec<u8> {
+    payload.to_string().into_bytes()
+}
+
+/// Wraps a payload with its Ed25519 signature. Bidders keep the receipt and
+/// can later prove what the platform acknowledged and when, without having to
+/// ask the platform whether the signature holds.
+pub fn sign_receipt(payload: Value) -> Value {
+    let signature = hex::encode(receipt_signing_key().sign(&receipt_message(&payload)).to_bytes());
+
+    json!({
+        "payload": payload,
+        "algorithm": "ed25519",
+        "public_key": receipt_public_key(),
+        "signature": signature,
+    })
+}
+
+pub fn verify_receipt(payload: &Value, signature: &str) -> bool {
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+
+    let Ok(signature) = Signature::from_slice(&signature) else {
+        return false;
+    };
+
+    receipt_signing_key()
+        .verifying_key()
+        .verify(&receipt_message(payload), &signature)
+        .is_ok()
+}
@@ -0,0 +1,184 @@
+use std::{collections::HashMap, time::Duration};
+
+use actix_web::rt::time::interval;
+use chrono::{NaiveDateTime, Utc};
+use entity::sea_orm_active_enums::AuctionFormat;
+use migration::Expr;
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect};
+use serde_json::{json, Value};
+
+use crate::utils::{api_response::ApiResponse, json_response::response, live_feed::LiveFeed};
+
+pub fn is_dutch(auction: &entity::auctions::Model) -> bool {
+    auction.format == AuctionFormat::Dutch
+}
+
+/// Number of price drops since the auction opened, or None before it has.
+fn steps_taken(auction: &entity::auctions::Model, listing: &entity::listings::Model, now: NaiveDateTime) -> Option<i64> {
+    if now < auction.start_time {
+        return None;
+    }
+
+    let step_seconds = i64::from(listing.price_step_seconds.unwrap_or(1).max(1));
+
+    Some((now - auction.start_time).num_seconds() / step_seconds)
+}
+
+/// The price on the clock at `now`. A listing without a start price never
+/// moves off its base price.
+pub fn current_price(auction: &entity::auctions::Model, listing: &entity::listings::Model, now: NaiveDateTime) -> Option<Decimal> {
+    let steps = steps_taken(auction, listing, now)?;
+
+    let price = match (listing.start_price, listing.price_step) {
+        (Some(start_price), Some(price_step)) => start_price - price_step * Decimal::from(steps),
+        _ => listing.base_price,
+    };
+
+    Some(price.max(listing.base_price))
+}
+
+/// When the price next drops, or None once it has reached the base price.
+pub fn next_drop_at(auction: &entity::auctions::Model, listing: &entity::listings::Model, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let steps = steps_taken(auction, listing, now)?;
+
+    if current_price(auction, listing, now)? <= listing.base_price {
+        return None;
+    }
+
+    let step_seconds = i64::from(listing.price_step_seconds.unwrap_or(1).max(1));
+
+    Some(auction.start_time + chrono::Duration::seconds((steps + 1) * step_seconds))
+}
+
+/// Units already bought off each listing's clock.
+pub async fn accepted_quantities<C: ConnectionTrait>(db: &C, listing_ids: Vec<i32>) -> Result<HashMap<i32, i32>, ApiResponse> {
+    let accepted = entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .filter(entity::bids::Column::ListingId.is_in(listing_ids))
+        .group_by(entity::bids::Column::ListingId)
+        .select_only()
+        .column(entity::bids::Column::ListingId)
+        .column_as(Expr::col(entity::bids::Column::Quantity).sum(), "accepted")
+        .into_tuple::<(i32, i64)>()
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    Ok(accepted
+        .into_iter()
+        .map(|(listing_id, accepted)| (listing_id, i32::try_from(accepted).unwrap_or(i32::MAX)))
+        .collect())
+}
+
+pub fn price_json(
+    auction: &entity::auctions::Model,
+    listing: &entity::listings::Model,
+    price: Decimal,
+    remaining_volume: i32,
+    now: NaiveDateTime,
+) -> Value {
+    json!({
+        "type": "price",
+        "auction_id": auction.id,
+        "listing_id": listing.id,
+        "price": price,
+        "remaining_volume": remaining_volume,
+        "unit_of_measure": listing.unit_of_measure,
+        "next_drop_at": next_drop_at(auction, listing, now),
+    })
+}
+
+/// Current clock prices for every listing of a running Dutch auction that
+/// still has stock.
+pub async fn price_snapshot(db: &DatabaseConnection, auction: &entity::auctions::Model) -> Result<Vec<Value>, ApiResponse> {
+    let now = Utc::now().naive_utc();
+
+    if !is_dutch(auction) || auction.end_time < now {
+        return Ok(Vec::new());
+    }
+
+    let listings = entity::listings::Entity::find()
+        .filter(entity::listings::Column::DeletedAt.is_null())
+        .filter(entity::listings::Column::AuctionId.eq(auction.id))
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let accepted = accepted_quantities(db, listings.iter().map(|listing| listing.id).collect()).await?;
+
+    Ok(listings
+        .iter()
+        .filter_map(|listing| {
+            let remaining_volume = listing.available_volume - accepted.get(&listing.id).copied().unwrap_or(0);
+            let price = current_price(auction, listing, now)?;
+
+            (remaining_volume > 0).then(|| price_json(auction, listing, price, remaining_volume, now))
+        })
+        .collect())
+}
+
+/// Drives the descending clock: once a second works out every running Dutch
+/// listing's price and pushes it to live clients whenever it has dropped.
+pub async fn run_dutch_clock(db: DatabaseConnection, live_feed: LiveFeed) -> Result<(), ApiResponse> {
+    let mut interval = interval(Duration::from_secs(1));
+    let mut last_prices: HashMap<i32, Decimal> = HashMap::new();
+
+    loop {
+        interval.tick().await;
+
+        if let Err(err) = tick_dutch_clock(&db, &live_feed, &mut last_prices).await {
+            eprintln!("Error driving Dutch auction clock: {:?}", err);
+        }
+    }
+}
+
+async fn tick_dutch_clock(
+    db: &DatabaseConnection,
+    live_feed: &LiveFeed,
+    last_prices: &mut HashMap<i32, Decimal>,
+) -> Result<(), ApiResponse> {
+    let now = Utc::now().naive_utc();
+
+    let running = entity::auctions::Entity::find()
+        .filter(entity::auctions::Column::DeletedAt.is_null())
+        .filter(entity::auctions::Column::Format.eq(AuctionFormat::Dutch))
+        .filter(entity::auctions::Column::StartTime.lte(now))
+        .filter(entity::auctions::Column::EndTime.gte(now))
+        .find_with_related(entity::listings::Entity)
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let listing_ids = running
+        .iter()
+        .flat_map(|(_, listings)| listings.iter().map(|listing| listing.id))
+        .collect::<Vec<_>>();
+
+    // Listings whose auctions have finished no longer need a last price
+    last_prices.retain(|listing_id, _| listing_ids.contains(listing_id));
+
+    if listing_ids.is_empty() {
+        return Ok(());
+    }
+
+    let accepted = accepted_quantities(db, listing_ids).await?;
+
+    for (auction, listings) in &running {
+        for listing in listings.iter().filter(|listing| listing.deleted_at.is_none()) {
+            let remaining_volume = listing.available_volume - accepted.get(&listing.id).copied().unwrap_or(0);
+            let Some(price) = current_price(auction, listing, now) else {
+                continue;
+            };
+
+            if remaining_volume <= 0 || last_prices.get(&listing.id) == Some(&price) {
+                continue;
+            }
+
+            last_prices.insert(listing.id, price);
+            live_feed.publish(auction.id, price_json(auction, listing, price, remaining_volume, now));
+        }
+    }
+
+    Ok(())
+}
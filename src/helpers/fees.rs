@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use entity::sea_orm_active_enums::{FeeParty, FeeType};
+use rust_decimal::{Decimal, RoundingStrategy};
+use sea_orm::{ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde_json::json;
+
+use crate::utils::{api_response::ApiResponse, json_response::response};
+
+/// A single itemised charge calculated for a sold lot.
+#[derive(Debug, Clone)]
+pub struct FeeLine {
+    pub fee_rule_id: Option<i32>,
+    pub fee_type: FeeType,
+    pub party: FeeParty,
+    pub description: String,
+    pub base_amount: Decimal,
+    pub rate: Option<Decimal>,
+    pub amount: Decimal,
+}
+
+/// Fee and tax totals for the buyer and the seller of a lot.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FeeTotals {
+    pub buyer_fees: Decimal,
+    pub buyer_tax: Decimal,
+    pub seller_fees: Decimal,
+    pub seller_tax: Decimal,
+}
+
+impl FeeTotals {
+    pub fn from_models(lines: &[entity::auction_result_fees::Model]) -> Self {
+        let mut totals = Self::default();
+        for line in lines {
+            totals.add(&line.fee_type, &line.party, line.amount);
+        }
+        totals
+    }
+
+    pub fn add(&mut self, fee_type: &FeeType, party: &FeeParty, amount: Decimal) {
+        match (fee_type, party) {
+            (FeeType::Tax, FeeParty::Buyer) => self.buyer_tax += amount,
+            (FeeType::Tax, FeeParty::Seller) => self.seller_tax += amount,
+            (_, FeeParty::Buyer) => self.buyer_fees += amount,
+            (_, FeeParty::Seller) => self.seller_fees += amount,
+        }
+    }
+
+    /// What the buyer owes: hammer price plus buyer-side fees and tax.
+    pub fn buyer_total(&self, hammer_price: Decimal) -> Decimal {
+        hammer_price + self.buyer_fees + self.buyer_tax
+    }
+
+    /// What the seller receives: hammer price less seller-side fees and tax.
+    pub fn seller_net(&self, hammer_price: Decimal) -> Decimal {
+        hammer_price - self.seller_fees - self.seller_tax
+    }
+}
+
+pub fn round_amount(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+}
+
+fn percent_of(amount: Decimal, rate: Option<Decimal>) -> Decimal {
+    rate.map(|rate| amount * rate / Decimal::ONE_HUNDRED).unwrap_or(Decimal::ZERO)
+}
+
+/// Marginal tiers: each rule charges its rate on the part of the hammer price
+/// between its `min_amount` and the next tier's `min_amount`.
+fn tiered_lines(hammer_price: Decimal, rules: &[entity::fee_rules::Model], fee_type: FeeType) -> Vec<FeeLine> {
+    let mut tiers = rules
+        .iter()
+        .filter(|rule| rule.fee_type == fee_type)
+        .collect::<Vec<_>>();
+    tiers.sort_by_key(|rule| rule.min_amount);
+
+    tiers
+        .iter()
+        .enumerate()
+        .filter_map(|(index, rule)| {
+            let upper = tiers
+                .get(index + 1)
+                .map(|next| next.min_amount.min(hammer_price))
+                .unwrap_or(hammer_price);
+            let portion = upper - rule.min_amount;
+
+            if portion <= Decimal::ZERO {
+                return None;
+            }
+
+            let amount = round_amount(percent_of(portion, rule.rate) + rule.fixed_amount.unwrap_or(Decimal::ZERO));
+
+            Some(FeeLine {
+                fee_rule_id: Some(rule.id),
+                fee_type: rule.fee_type.clone(),
+                party: rule.party.clone(),
+                description: rule.name.clone(),
+                base_amount: portion,
+                rate: rule.rate,
+                amount,
+            })
+        })
+        .collect()
+}
+
+/// Calculates the itemised fees for a lot sold at `hammer_price` under the
+/// given auction fee schedule. Tax is charged on each party's fees, not on the
+/// hammer price itself.
+pub fn calculate_fees(hammer_price: Decimal, rules: &[entity::fee_rules::Model]) -> Vec<FeeLine> {
+    let mut lines = tiered_lines(hammer_price, rules, FeeType::BuyersPremium);
+    lines.extend(tiered_lines(hammer_price, rules, FeeType::SellerCommission));
+
+    for rule in rules.iter().filter(|rule| rule.fee_type == FeeType::LotFee) {
+        if hammer_price < rule.min_amount {
+            continue;
+        }
+
+        lines.push(FeeLine {
+            fee_rule_id: Some(rule.id),
+            fee_type: rule.fee_type.clone(),
+            party: rule.party.clone(),
+            description: rule.name.clone(),
+            base_amount: hammer_price,
+            rate: rule.rate,
+            amount: round_amount(percent_of(hammer_price, rule.rate) + rule.fixed_amount.unwrap_or(Decimal::ZERO)),
+        });
+    }
+
+    let taxes = rules
+        .iter()
+        .filter(|rule| rule.fee_type == FeeType::Tax && hammer_price >= rule.min_amount)
+        .filter_map(|rule| {
+            let base_amount: Decimal = lines
+                .iter()
+                .filter(|line| line.party == rule.party)
+                .map(|line| line.amount)
+                .sum();
+
+            if base_amount <= Decimal::ZERO {
+                return None;
+            }
+
+            Some(FeeLine {
+                fee_rule_id: Some(rule.id),
+                fee_type: rule.fee_type.clone(),
+                party: rule.party.clone(),
+                description: rule.name.clone(),
+                base_amount,
+                rate: rule.rate,
+                amount: round_amount(percent_of(base_amount, rule.rate)),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    lines.extend(taxes);
+    lines
+}
+
+/// Calculates the fees for an auction result from its auction's fee schedule
+/// and stores them as itemised lines.
+pub async fn apply_fees<C: ConnectionTrait>(
+    db: &C,
+    auction_id: i32,
+    auction_result_id: i32,
+    hammer_price: Decimal,
+) -> Result<Vec<entity::auction_result_fees::Model>, ApiResponse> {
+    let rules = entity::fee_rules::Entity::find()
+        .filter(entity::fee_rules::Column::DeletedAt.is_null())
+        .filter(entity::fee_rules::Column::AuctionId.eq(auction_id))
+        .order_by_asc(entity::fee_rules::Column::Id)
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let mut fee_models = Vec::new();
+    for line in calculate_fees(hammer_price, &rules) {
+        let fee_model = entity::auction_result_fees::ActiveModel {
+            auction_result_id: Set(auction_result_id),
+            fee_rule_id: Set(line.fee_rule_id),
+            fee_type: Set(line.fee_type),
+            party: Set(line.party),
+            description: Set(line.description),
+            base_amount: Set(line.base_amount),
+            rate: Set(line.rate),
+            amount: Set(line.amount),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        fee_models.push(fee_model);
+    }
+
+    Ok(fee_models)
+}
+
+/// Loads the stored fee lines of the given auction results, keyed by result id.
+pub async fn fees_for_results<C: ConnectionTrait>(
+    db: &C,
+    auction_result_ids: Vec<i32>,
+) -> Result<HashMap<i32, Vec<entity::auction_result_fees::Model>>, ApiResponse> {
+    let fee_models = entity::auction_result_fees::Entity::find()
+        .filter(entity::auction_result_fees::Column::DeletedAt.is_null())
+        .filter(entity::auction_result_fees::Column::AuctionResultId.is_in(auction_result_ids))
+        .order_by_asc(entity::auction_result_fees::Column::Id)
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let mut fees_map: HashMap<i32, Vec<entity::auction_result_fees::Model>> = HashMap::new();
+    for fee_model in fee_models {
+        fees_map.entry(fee_model.auction_result_id).or_default().push(fee_model);
+    }
+
+    Ok(fees_map)
+}
+
+pub fn fee_lines_json(lines: &[entity::auction_result_fees::Model]) -> Vec<serde_json::Value> {
+    lines
+        .iter()
+        .map(|line| {
+            json!({
+                "fee_type": line.fee_type,
+                "party": line.party,
+                "description": line.description,
+                "base_amount": line.base_amount,
+                "rate": line.rate,
+                "amount": line.amount,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,394 @@
+use std::collections::{BTreeMap, HashMap};
+
+use entity::sea_orm_active_enums::{AuctionDirection, FeeParty, FeeType, InvoiceStatus};
+use migration::{Expr, Query};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult,
+    JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set, SqlErr, Statement, TransactionTrait,
+};
+use serde_json::json;
+
+use crate::{
+    helpers::{
+        fees::{fees_for_results, FeeTotals},
+        ledger::{post_invoice_issued, SalePosting},
+    },
+    utils::{api_response::ApiResponse, json_response::response, pdf::{render_text_pdf, truncate}},
+};
+
+#[derive(Debug, FromQueryResult)]
+struct InvoiceableResult {
+    id: i32,
+    winning_user_id: i32,
+    auction_id: i32,
+    title: String,
+    quantity: i32,
+    unit_price: Decimal,
+    unit_of_measure: String,
+    amount: Decimal,
+    seller_id: Option<i32>,
+}
+
+/// Issues one invoice per buyer per auction for every result of the given
+/// auctions that is not yet on an invoice. Results whose invoices were all
+/// voided are only picked up again when `include_voided` is set, so a voided
+/// sale is not silently re-billed. A result can only be on one invoice that
+/// has not been voided, so when two runs race for the same results the buyer's
+/// invoice from the later one is dropped.
+pub async fn generate_invoices(
+    db: &DatabaseConnection,
+    auction_ids: Vec<i32>,
+    include_voided: bool,
+) -> Result<Vec<entity::invoices::Model>, ApiResponse> {
+    let mut invoiced_results = Query::select();
+    invoiced_results
+        .column((entity::invoice_lines::Entity, entity::invoice_lines::Column::AuctionResultId))
+        .from(entity::invoice_lines::Entity)
+        .inner_join(
+            entity::invoices::Entity,
+            Expr::col((entity::invoices::Entity, entity::invoices::Column::Id))
+                .equals((entity::invoice_lines::Entity, entity::invoice_lines::Column::InvoiceId)),
+        )
+        .and_where(Expr::col((entity::invoice_lines::Entity, entity::invoice_lines::Column::AuctionResultId)).is_not_null());
+
+    if include_voided {
+        invoiced_results.and_where(entity::invoices::Column::Status.ne(InvoiceStatus::Void));
+    }
+
+    let results = entity::auction_results::Entity::find()
+        .filter(entity::auction_results::Column::DeletedAt.is_null())
+        .filter(entity::listings::Column::AuctionId.is_in(auction_ids))
+        .filter(entity::auction_results::Column::Id.not_in_subquery(invoiced_results.to_owned()))
+        // Reverse auctions buy from their winners, so there is nobody to bill
+        .filter(entity::auctions::Column::Direction.eq(AuctionDirection::Forward))
+        .inner_join(entity::listings::Entity)
+        .join(JoinType::InnerJoin, entity::listings::Relation::Auctions.def())
+        .order_by_asc(entity::auction_results::Column::Id)
+        .select_only()
+        .column(entity::auction_results::Column::Id)
+        .column(entity::auction_results::Column::WinningUserId)
+        .column(entity::listings::Column::AuctionId)
+        .column(entity::listings::Column::Title)
+        .column(entity::auction_results::Column::Quantity)
+        .column(entity::auction_results::Column::UnitPrice)
+        .column(entity::listings::Column::UnitOfMeasure)
+        .column_as(entity::auction_results::Column::HammerPrice, "amount")
+        .column(entity::listings::Column::SellerId)
+        .into_model::<InvoiceableResult>()
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    if results.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let fees_map = fees_for_results(db, results.iter().map(|result| result.id).collect()).await?;
+
+    let mut results_by_buyer: BTreeMap<(i32, i32), Vec<InvoiceableResult>> = BTreeMap::new();
+    for result in results {
+        results_by_buyer
+            .entry((result.auction_id, result.winning_user_id))
+            .or_default()
+            .push(result);
+    }
+
+    let mut invoices = Vec::new();
+    'buyers: for ((auction_id, user_id), buyer_results) in results_by_buyer {
+        let txn = db
+            .begin()
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        let invoice_number = next_invoice_number(&txn).await?;
+
+        let mut invoice_totals = FeeTotals::default();
+        let subtotal: Decimal = buyer_results.iter().map(|result| result.amount).sum();
+        for result in &buyer_results {
+            let totals = buyer_fee_totals(fees_map.get(&result.id).map(Vec::as_slice).unwrap_or_default());
+            invoice_totals.buyer_fees += totals.buyer_fees;
+            invoice_totals.buyer_tax += totals.buyer_tax;
+        }
+
+        let invoice = entity::invoices::ActiveModel {
+            invoice_number: Set(invoice_number),
+            user_id: Set(user_id),
+            auction_id: Set(auction_id),
+            status: Set(InvoiceStatus::Issued),
+            subtotal: Set(subtotal),
+            fees_total: Set(invoice_totals.buyer_fees),
+            tax_total: Set(invoice_totals.buyer_tax),
+            total: Set(invoice_totals.buyer_total(subtotal)),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        for result in &buyer_results {
+            let totals = buyer_fee_totals(fees_map.get(&result.id).map(Vec::as_slice).unwrap_or_default());
+
+            let line = entity::invoice_lines::ActiveModel {
+                invoice_id: Set(invoice.id),
+                auction_result_id: Set(Some(result.id)),
+                description: Set(result.title.clone()),
+                quantity: Set(result.quantity),
+                unit_price: Set(Some(result.unit_price)),
+                unit_of_measure: Set(Some(result.unit_of_measure.clone())),
+                hammer_price: Set(result.amount),
+                fees: Set(totals.buyer_fees),
+                tax: Set(totals.buyer_tax),
+                total: Set(totals.buyer_total(result.amount)),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await;
+
+            match line {
+                Ok(_) => {}
+                // Another run has just invoiced this result. Dropping the
+                // transaction rolls back this buyer's invoice.
+                Err(err) if matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => continue 'buyers,
+                Err(err) => return Err(ApiResponse::new(500, response(json!({ "error": err.to_string() })))),
+            }
+        }
+
+        let sales = buyer_results
+            .iter()
+            .map(|result| SalePosting {
+                seller_id: result.seller_id,
+                hammer_price: result.amount,
+                fee_lines: fees_map.get(&result.id).map(Vec::as_slice).unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+        post_invoice_issued(&txn, &invoice, &sales).await?;
+
+        txn.commit()
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        invoices.push(invoice);
+    }
+
+    Ok(invoices)
+}
+
+fn buyer_fee_totals(lines: &[entity::auction_result_fees::Model]) -> FeeTotals {
+    let buyer_lines = lines
+        .iter()
+        .filter(|line| line.party == FeeParty::Buyer)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    FeeTotals::from_models(&buyer_lines)
+}
+
+async fn next_invoice_number<C: ConnectionTrait>(db: &C) -> Result<String, ApiResponse> {
+    let row = db
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT nextval('invoice_number_seq') AS number",
+        ))
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .ok_or_else(|| ApiResponse::new(500, response(json!({ "error": "Could not allocate an invoice number" }))))?;
+
+    let number: i64 = row
+        .try_get("", "number")
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    Ok(format!("INV-{:06}", number))
+}
+
+/// Everything needed to render an invoice.
+pub struct InvoiceDocument {
+    pub invoice: entity::invoices::Model,
+    pub lines: Vec<entity::invoice_lines::Model>,
+    pub buyer: entity::users::Model,
+    pub auction: entity::auctions::Model,
+    pub fees: HashMap<i32, Vec<entity::auction_result_fees::Model>>,
+}
+
+impl InvoiceDocument {
+    pub async fn load(db: &DatabaseConnection, invoice_id: i32) -> Result<Self, ApiResponse> {
+        let invoice = entity::invoices::Entity::find_by_id(invoice_id)
+            .filter(entity::invoices::Column::DeletedAt.is_null())
+            .one(db)
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+            .ok_or_else(|| ApiResponse::new(404, response(json!({ "error": "Invoice not found" }))))?;
+
+        let lines = entity::invoice_lines::Entity::find()
+            .filter(entity::invoice_lines::Column::DeletedAt.is_null())
+            .filter(entity::invoice_lines::Column::InvoiceId.eq(invoice.id))
+            .order_by_asc(entity::invoice_lines::Column::Id)
+            .all(db)
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        let buyer = entity::users::Entity::find_by_id(invoice.user_id)
+            .one(db)
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+            .ok_or_else(|| ApiResponse::new(404, response(json!({ "error": "Buyer not found" }))))?;
+
+        let auction = entity::auctions::Entity::find_by_id(invoice.auction_id)
+            .one(db)
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+            .ok_or_else(|| ApiResponse::new(404, response(json!({ "error": "Auction not found" }))))?;
+
+        let fees = fees_for_results(db, lines.iter().filter_map(|line| line.auction_result_id).collect()).await?;
+
+        Ok(Self { invoice, lines, buyer, auction, fees })
+    }
+
+    fn buyer_fee_lines(&self, line: &entity::invoice_lines::Model) -> Vec<&entity::auction_result_fees::Model> {
+        line.auction_result_id
+            .and_then(|id| self.fees.get(&id))
+            .map(|fees| fees.iter().filter(|fee| fee.party == FeeParty::Buyer).collect())
+            .unwrap_or_default()
+    }
+
+    fn status_label(&self) -> &'static str {
+        match self.invoice.status {
+            InvoiceStatus::Issued => "ISSUED",
+            InvoiceStatus::Paid => "PAID",
+            InvoiceStatus::Void => "VOID",
+        }
+    }
+
+    pub fn render_html(&self) -> String {
+        let mut rows = String::new();
+        for line in &self.lines {
+            rows.push_str(&format!(
+                "<tr><td>{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td></tr>\n",
+                escape_html(&line.description),
+                escape_html(&quantity_label(line)),
+                line.unit_price.map(|price| price.to_string()).unwrap_or_default(),
+                line.hammer_price, line.fees, line.tax, line.total
+            ));
+
+            for fee in self.buyer_fee_lines(line) {
+                let (fee_amount, tax_amount) = match fee.fee_type {
+                    FeeType::Tax => (String::new(), fee.amount.to_string()),
+                    _ => (fee.amount.to_string(), String::new()),
+                };
+
+                rows.push_str(&format!(
+                    "<tr class=\"fee\"><td>{}</td><td></td><td></td><td></td><td class=\"amount\">{}</td><td class=\"amount\">{}</td><td></td></tr>\n",
+                    escape_html(&fee_description(fee)), fee_amount, tax_amount
+                ));
+            }
+        }
+
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Invoice {number}</title>
+<style>
+body {{ font-family: Helvetica, Arial, sans-serif; margin: 40px; color: #222; }}
+table {{ width: 100%; border-collapse: collapse; margin-top: 24px; }}
+th, td {{ padding: 6px 8px; border-bottom: 1px solid #ddd; text-align: left; }}
+.amount {{ text-align: right; }}
+.fee td {{ color: #666; font-size: 0.9em; padding-left: 24px; }}
+.totals td {{ font-weight: bold; }}
+.status {{ float: right; font-size: 1.4em; font-weight: bold; }}
+</style>
+</head>
+<body>
+<div class="status">{status}</div>
+<h1>Invoice {number}</h1>
+<p>Issued: {issued_at}<br>Auction: {auction}<br>Buyer: {buyer} ({phone_number})</p>
+<table>
+<thead><tr><th>Lot</th><th class="amount">Quantity</th><th class="amount">Unit price</th><th class="amount">Hammer price</th><th class="amount">Fees</th><th class="amount">Tax</th><th class="amount">Total</th></tr></thead>
+<tbody>
+{rows}</tbody>
+<tfoot>
+<tr class="totals"><td>Totals</td><td></td><td></td><td class="amount">{subtotal}</td><td class="amount">{fees_total}</td><td class="amount">{tax_total}</td><td class="amount">{total}</td></tr>
+</tfoot>
+</table>
+</body>
+</html>
+"#,
+            number = escape_html(&self.invoice.invoice_number),
+            status = self.status_label(),
+            issued_at = self.invoice.issued_at.format("%Y-%m-%d %H:%M"),
+            auction = escape_html(&self.auction.name),
+            buyer = escape_html(&self.buyer.name),
+            phone_number = escape_html(&self.buyer.phone_number),
+            rows = rows,
+            subtotal = self.invoice.subtotal,
+            fees_total = self.invoice.fees_total,
+            tax_total = self.invoice.tax_total,
+            total = self.invoice.total,
+        )
+    }
+
+    pub fn render_pdf(&self) -> Result<Vec<u8>, ApiResponse> {
+        let mut lines = vec![
+            format!("INVOICE {:>60}", self.status_label()),
+            String::new(),
+            format!("Invoice number: {}", self.invoice.invoice_number),
+            format!("Issued:         {}", self.invoice.issued_at.format("%Y-%m-%d %H:%M")),
+            format!("Auction:        {}", self.auction.name),
+            format!("Buyer:          {} ({})", self.buyer.name, self.buyer.phone_number),
+            String::new(),
+            format!("{:<36}{:>14}{:>12}{:>12}{:>14}", "Lot", "Hammer", "Fees", "Tax", "Total"),
+            "-".repeat(88),
+        ];
+
+        for line in &self.lines {
+            lines.push(format!(
+                "{:<36}{:>14}{:>12}{:>12}{:>14}",
+                truncate(&line.description, 35), line.hammer_price, line.fees, line.tax, line.total
+            ));
+
+            if let Some(unit_price) = line.unit_price {
+                lines.push(format!("  {} @ {}", quantity_label(line), unit_price));
+            }
+
+            for fee in self.buyer_fee_lines(line) {
+                match fee.fee_type {
+                    FeeType::Tax => lines.push(format!("  {:<60}{:>12}", truncate(&fee_description(fee), 47), fee.amount)),
+                    _ => lines.push(format!("  {:<48}{:>12}", truncate(&fee_description(fee), 47), fee.amount)),
+                }
+            }
+        }
+
+        lines.push("-".repeat(88));
+        lines.push(format!(
+            "{:<36}{:>14}{:>12}{:>12}{:>14}",
+            "Totals", self.invoice.subtotal, self.invoice.fees_total, self.invoice.tax_total, self.invoice.total
+        ));
+
+        render_text_pdf(&format!("Invoice {}", self.invoice.invoice_number), &lines)
+    }
+}
+
+/// The quantity billed on a line with its unit, e.g. "40 kg".
+fn quantity_label(line: &entity::invoice_lines::Model) -> String {
+    match &line.unit_of_measure {
+        Some(unit) => format!("{} {}", line.quantity, unit),
+        None => line.quantity.to_string(),
+    }
+}
+
+fn fee_description(fee: &entity::auction_result_fees::Model) -> String {
+    match fee.rate {
+        Some(rate) => format!("{} ({}% of {})", fee.description, rate.normalize(), fee.base_amount),
+        None => fee.description.clone(),
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
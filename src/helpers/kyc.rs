@@ -0,0 +1,126 @@
+use std::{fs, path::PathBuf};
+
+use actix_web::web;
+use entity::sea_orm_active_enums::VerificationLevel;
+use sea_orm::{ConnectionTrait, EntityTrait};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::utils::{api_response::ApiResponse, constants, json_response::response};
+
+/// Verification levels in increasing order of assurance.
+pub fn level_rank(level: &VerificationLevel) -> u8 {
+    match level {
+        VerificationLevel::None => 0,
+        VerificationLevel::Basic => 1,
+        VerificationLevel::Enhanced => 2,
+    }
+}
+
+/// The level a bidder needs for a listing: the auction's minimum, raised by
+/// the listing's own minimum when it asks for more.
+pub fn required_level(auction: &entity::auctions::Model, listing: &entity::listings::Model) -> VerificationLevel {
+    match &listing.min_verification_level {
+        Some(level) if level_rank(level) > level_rank(&auction.min_verification_level) => level.clone(),
+        _ => auction.min_verification_level.clone(),
+    }
+}
+
+/// Rejects a bidder whose verified identity falls short of what the listing
+/// requires.
+pub async fn check_verification<C: ConnectionTrait>(
+    db: &C,
+    auction: &entity::auctions::Model,
+    listing: &entity::listings::Model,
+    user_id: i32,
+) -> Result<(), ApiResponse> {
+    let required = required_level(auction, listing);
+    if required == VerificationLevel::None {
+        return Ok(());
+    }
+
+    let user = entity::users::Entity::find_by_id(user_id)
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .ok_or_else(|| ApiResponse::new(404, response(json!({ "error": "User not found" }))))?;
+
+    if level_rank(&user.verification_level) < level_rank(&required) {
+        return Err(ApiResponse::new(403, response(json!({
+            "error": "This listing requires a verified identity before you can bid",
+            "required_verification_level": required,
+            "verification_level": user.verification_level,
+        }))));
+    }
+
+    Ok(())
+}
+
+/// The file extension a document of the given content type is stored under,
+/// or None when the type is not accepted.
+pub fn document_extension(content_type: &str) -> Option<&'static str> {
+    match content_type {
+        "application/pdf" => Some("pdf"),
+        "image/jpeg" => Some("jpg"),
+        "image/png" => Some("png"),
+        _ => None,
+    }
+}
+
+/// Writes an uploaded document under the KYC document directory and returns
+/// where it was stored along with the hex SHA-256 of its contents.
+pub async fn store_document(user_id: i32, extension: &str, contents: Vec<u8>) -> Result<(String, String), ApiResponse> {
+    let checksum = hex::encode(Sha256::digest(&contents));
+    let directory = PathBuf::from(constants::KYC_DOCUMENT_DIR.as_str()).join(user_id.to_string());
+    let path = directory.join(format!("{}.{}", uuid::Uuid::new_v4(), extension));
+    let storage_path = path.to_string_lossy().to_string();
+
+    web::block(move || fs::create_dir_all(&directory).and_then(|_| fs::write(&path, contents)))
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    Ok((storage_path, checksum))
+}
+
+pub async fn read_document(storage_path: String) -> Result<Vec<u8>, ApiResponse> {
+    web::block(move || fs::read(storage_path))
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))
+}
+
+pub fn kyc_json(user: &entity::users::Model) -> Value {
+    json!({
+        "user_id": user.id,
+        "national_id_number": user.national_id_number,
+        "kyc_status": user.kyc_status,
+        "verification_level": user.verification_level,
+        "kyc_submitted_at": user.kyc_submitted_at,
+    })
+}
+
+pub fn document_json(document: &entity::kyc_documents::Model) -> Value {
+    json!({
+        "id": document.id,
+        "user_id": document.user_id,
+        "document_type": document.document_type,
+        "file_name": document.file_name,
+        "content_type": document.content_type,
+        "size_bytes": document.size_bytes,
+        "sha256": document.sha256,
+        "created_at": document.created_at,
+    })
+}
+
+pub fn review_json(review: &entity::kyc_reviews::Model) -> Value {
+    json!({
+        "id": review.id,
+        "user_id": review.user_id,
+        "reviewer_id": review.reviewer_id,
+        "status": review.status,
+        "verification_level": review.verification_level,
+        "note": review.note,
+        "created_at": review.created_at,
+    })
+}
@@ -0,0 +1,392 @@
+use std::collections::BTreeMap;
+
+use entity::sea_orm_active_enums::{FeeParty, FeeType, LedgerAccountType};
+use migration::{Expr, OnConflict};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseTransaction, EntityTrait, QueryFilter, QuerySelect, Set,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::utils::{api_response::ApiResponse, json_response::response};
+
+/// Platform-level accounts, created on first use.
+#[derive(Debug, Clone, Copy)]
+pub enum SystemAccount {
+    Cash,
+    ConsignorPayables,
+    VatPayable,
+    BuyersPremiumRevenue,
+    CommissionRevenue,
+    LotFeeRevenue,
+}
+
+impl SystemAccount {
+    fn code(&self) -> &'static str {
+        match self {
+            SystemAccount::Cash => "1000",
+            SystemAccount::ConsignorPayables => "2300",
+            SystemAccount::VatPayable => "2400",
+            SystemAccount::BuyersPremiumRevenue => "4000",
+            SystemAccount::CommissionRevenue => "4100",
+            SystemAccount::LotFeeRevenue => "4200",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            SystemAccount::Cash => "Cash at bank",
+            SystemAccount::ConsignorPayables => "Consignor payables",
+            SystemAccount::VatPayable => "VAT payable",
+            SystemAccount::BuyersPremiumRevenue => "Buyer's premium revenue",
+            SystemAccount::CommissionRevenue => "Seller commission revenue",
+            SystemAccount::LotFeeRevenue => "Lot fee revenue",
+        }
+    }
+
+    fn account_type(&self) -> LedgerAccountType {
+        match self {
+            SystemAccount::Cash => LedgerAccountType::Asset,
+            SystemAccount::ConsignorPayables | SystemAccount::VatPayable => LedgerAccountType::Liability,
+            SystemAccount::BuyersPremiumRevenue | SystemAccount::CommissionRevenue | SystemAccount::LotFeeRevenue => {
+                LedgerAccountType::Revenue
+            }
+        }
+    }
+
+    /// The account a fee line is credited to.
+    pub fn for_fee(fee_type: &FeeType) -> Self {
+        match fee_type {
+            FeeType::BuyersPremium => SystemAccount::BuyersPremiumRevenue,
+            FeeType::SellerCommission => SystemAccount::CommissionRevenue,
+            FeeType::LotFee => SystemAccount::LotFeeRevenue,
+            FeeType::Tax => SystemAccount::VatPayable,
+        }
+    }
+}
+
+/// Accounts held per user.
+#[derive(Debug, Clone, Copy)]
+pub enum UserAccount {
+    /// Money the platform holds for the user (liability).
+    Wallet,
+    /// Money the user owes the platform (asset).
+    Receivable,
+    /// Bidding deposits the platform is holding for the user (liability).
+    Deposit,
+    /// Sale proceeds owed to the user as a consignor (liability).
+    ConsignorPayable,
+}
+
+impl UserAccount {
+    pub fn code(&self, user_id: i32) -> String {
+        match self {
+            UserAccount::Wallet => format!("2100-{}", user_id),
+            UserAccount::Receivable => format!("1100-{}", user_id),
+            UserAccount::Deposit => format!("2200-{}", user_id),
+            UserAccount::ConsignorPayable => format!("2300-{}", user_id),
+        }
+    }
+
+    fn name(&self, user_id: i32) -> String {
+        match self {
+            UserAccount::Wallet => format!("Wallet: user {}", user_id),
+            UserAccount::Receivable => format!("Receivable: user {}", user_id),
+            UserAccount::Deposit => format!("Deposits held: user {}", user_id),
+            UserAccount::ConsignorPayable => format!("Consignor payable: user {}", user_id),
+        }
+    }
+
+    fn account_type(&self) -> LedgerAccountType {
+        match self {
+            UserAccount::Wallet | UserAccount::Deposit | UserAccount::ConsignorPayable => LedgerAccountType::Liability,
+            UserAccount::Receivable => LedgerAccountType::Asset,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentMethod {
+    /// Paid from the user's wallet balance.
+    Wallet,
+    /// Paid directly into the platform's bank or M-Pesa account.
+    Bank,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Posting {
+    pub account_id: i32,
+    pub debit: Decimal,
+    pub credit: Decimal,
+}
+
+impl Posting {
+    pub fn debit(account_id: i32, amount: Decimal) -> Self {
+        Posting { account_id, debit: amount, credit: Decimal::ZERO }
+    }
+
+    pub fn credit(account_id: i32, amount: Decimal) -> Self {
+        Posting { account_id, debit: Decimal::ZERO, credit: amount }
+    }
+}
+
+async fn find_or_create_account(
+    txn: &DatabaseTransaction,
+    code: String,
+    name: String,
+    account_type: LedgerAccountType,
+    user_id: Option<i32>,
+) -> Result<i32, ApiResponse> {
+    entity::ledger_accounts::Entity::insert(entity::ledger_accounts::ActiveModel {
+        code: Set(code.clone()),
+        name: Set(name),
+        account_type: Set(account_type),
+        user_id: Set(user_id),
+        ..Default::default()
+    })
+    .on_conflict(OnConflict::column(entity::ledger_accounts::Column::Code).do_nothing().to_owned())
+    .exec_without_returning(txn)
+    .await
+    .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    entity::ledger_accounts::Entity::find()
+        .filter(entity::ledger_accounts::Column::Code.eq(code))
+        .select_only()
+        .column(entity::ledger_accounts::Column::Id)
+        .into_tuple::<i32>()
+        .one(txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .ok_or_else(|| ApiResponse::new(500, response(json!({ "error": "Ledger account could not be created" }))))
+}
+
+pub async fn system_account(txn: &DatabaseTransaction, account: SystemAccount) -> Result<i32, ApiResponse> {
+    find_or_create_account(txn, account.code().to_string(), account.name().to_string(), account.account_type(), None).await
+}
+
+pub async fn user_account(txn: &DatabaseTransaction, user_id: i32, account: UserAccount) -> Result<i32, ApiResponse> {
+    find_or_create_account(txn, account.code(user_id), account.name(user_id), account.account_type(), Some(user_id)).await
+}
+
+/// Locks a user's account row until the transaction ends, so concurrent
+/// spends from the same balance are serialised.
+pub async fn lock_user_account(txn: &DatabaseTransaction, user_id: i32, account: UserAccount) -> Result<i32, ApiResponse> {
+    let account_id = user_account(txn, user_id, account).await?;
+
+    entity::ledger_accounts::Entity::find_by_id(account_id)
+        .lock_exclusive()
+        .one(txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    Ok(account_id)
+}
+
+/// Posts a balanced journal entry. Postings on the same account and side are
+/// merged. The database re-checks the balance when the transaction commits.
+pub async fn post_journal_entry(
+    txn: &DatabaseTransaction,
+    reference: &str,
+    description: &str,
+    postings: Vec<Posting>,
+) -> Result<entity::journal_entries::Model, ApiResponse> {
+    let mut merged: BTreeMap<(i32, bool), Decimal> = BTreeMap::new();
+    for posting in postings {
+        if posting.debit < Decimal::ZERO || posting.credit < Decimal::ZERO {
+            return Err(ApiResponse::new(500, response(json!({ "error": "Ledger postings cannot be negative" }))));
+        }
+
+        if posting.debit > Decimal::ZERO {
+            *merged.entry((posting.account_id, true)).or_default() += posting.debit;
+        }
+
+        if posting.credit > Decimal::ZERO {
+            *merged.entry((posting.account_id, false)).or_default() += posting.credit;
+        }
+    }
+
+    let total_debit: Decimal = merged.iter().filter(|((_, is_debit), _)| *is_debit).map(|(_, amount)| *amount).sum();
+    let total_credit: Decimal = merged.iter().filter(|((_, is_debit), _)| !*is_debit).map(|(_, amount)| *amount).sum();
+
+    if merged.is_empty() || total_debit != total_credit {
+        return Err(ApiResponse::new(500, response(json!({
+            "error": format!("Journal entry {} is not balanced (debits {}, credits {})", reference, total_debit, total_credit)
+        }))));
+    }
+
+    let journal_entry = entity::journal_entries::ActiveModel {
+        reference: Set(reference.to_string()),
+        description: Set(description.to_string()),
+        ..Default::default()
+    }
+    .insert(txn)
+    .await
+    .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    for ((account_id, is_debit), amount) in merged {
+        entity::ledger_postings::ActiveModel {
+            journal_entry_id: Set(journal_entry.id),
+            account_id: Set(account_id),
+            debit: Set(if is_debit { amount } else { Decimal::ZERO }),
+            credit: Set(if is_debit { Decimal::ZERO } else { amount }),
+            ..Default::default()
+        }
+        .insert(txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+    }
+
+    Ok(journal_entry)
+}
+
+/// Posts the reverse of every entry with the given reference.
+pub async fn reverse_journal_entries(
+    txn: &DatabaseTransaction,
+    reference: &str,
+    reversal_reference: &str,
+    description: &str,
+) -> Result<(), ApiResponse> {
+    let postings = entity::ledger_postings::Entity::find()
+        .inner_join(entity::journal_entries::Entity)
+        .filter(entity::journal_entries::Column::Reference.eq(reference))
+        .filter(entity::journal_entries::Column::DeletedAt.is_null())
+        .all(txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .into_iter()
+        .map(|posting| Posting { account_id: posting.account_id, debit: posting.credit, credit: posting.debit })
+        .collect::<Vec<_>>();
+
+    if postings.is_empty() {
+        return Ok(());
+    }
+
+    post_journal_entry(txn, reversal_reference, description, postings).await?;
+
+    Ok(())
+}
+
+/// One sold lot on an invoice.
+pub struct SalePosting<'a> {
+    /// Consignor of the lot, if it has one.
+    pub seller_id: Option<i32>,
+    pub hammer_price: Decimal,
+    pub fee_lines: &'a [entity::auction_result_fees::Model],
+}
+
+/// Raises the buyer's receivable for an issued invoice, moves each hammer
+/// price to what the platform owes the lot's consignor and recognises fee
+/// revenue and VAT for both parties of each sale. Lots without a consignor
+/// go to the house consignor payables account.
+pub async fn post_invoice_issued(
+    txn: &DatabaseTransaction,
+    invoice: &entity::invoices::Model,
+    sales: &[SalePosting<'_>],
+) -> Result<(), ApiResponse> {
+    let receivable = user_account(txn, invoice.user_id, UserAccount::Receivable).await?;
+
+    let mut postings = vec![Posting::debit(receivable, invoice.total)];
+
+    for sale in sales {
+        let consignor_payable = match sale.seller_id {
+            Some(seller_id) => user_account(txn, seller_id, UserAccount::ConsignorPayable).await?,
+            None => system_account(txn, SystemAccount::ConsignorPayables).await?,
+        };
+        postings.push(Posting::credit(consignor_payable, sale.hammer_price));
+
+        for fee_line in sale.fee_lines {
+            let fee_account = system_account(txn, SystemAccount::for_fee(&fee_line.fee_type)).await?;
+            postings.push(Posting::credit(fee_account, fee_line.amount));
+
+            if fee_line.party == FeeParty::Seller {
+                postings.push(Posting::debit(consignor_payable, fee_line.amount));
+            }
+        }
+    }
+
+    post_journal_entry(txn, &format!("invoice:{}", invoice.id), &format!("Invoice {} issued", invoice.invoice_number), postings).await?;
+
+    Ok(())
+}
+
+/// Settles an invoice's receivable from the buyer's wallet or a direct payment.
+pub async fn post_invoice_payment(
+    txn: &DatabaseTransaction,
+    invoice: &entity::invoices::Model,
+    payment_method: PaymentMethod,
+) -> Result<(), ApiResponse> {
+    let receivable = user_account(txn, invoice.user_id, UserAccount::Receivable).await?;
+
+    let source = match payment_method {
+        PaymentMethod::Wallet => {
+            let wallet = lock_user_account(txn, invoice.user_id, UserAccount::Wallet).await?;
+
+            if account_balance(txn, wallet).await? < invoice.total {
+                return Err(ApiResponse::new(400, response(json!({ "error": "Insufficient wallet balance to pay this invoice" }))));
+            }
+
+            wallet
+        }
+        PaymentMethod::Bank => system_account(txn, SystemAccount::Cash).await?,
+    };
+
+    post_journal_entry(
+        txn,
+        &format!("invoice:{}:payment", invoice.id),
+        &format!("Payment for invoice {}", invoice.invoice_number),
+        vec![Posting::debit(source, invoice.total), Posting::credit(receivable, invoice.total)],
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Balance of an account on its normal side: debits less credits for assets
+/// and expenses, credits less debits for everything else.
+pub async fn account_balance<C: ConnectionTrait>(db: &C, account_id: i32) -> Result<Decimal, ApiResponse> {
+    let account = entity::ledger_accounts::Entity::find_by_id(account_id)
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .ok_or_else(|| ApiResponse::new(404, response(json!({ "error": "Ledger account not found" }))))?;
+
+    let (total_debit, total_credit) = entity::ledger_postings::Entity::find()
+        .filter(entity::ledger_postings::Column::AccountId.eq(account_id))
+        .filter(entity::ledger_postings::Column::DeletedAt.is_null())
+        .select_only()
+        .column_as(Expr::col(entity::ledger_postings::Column::Debit).sum(), "total_debit")
+        .column_as(Expr::col(entity::ledger_postings::Column::Credit).sum(), "total_credit")
+        .into_tuple::<(Option<Decimal>, Option<Decimal>)>()
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .unwrap_or_default();
+
+    Ok(normal_balance(&account.account_type, total_debit.unwrap_or_default(), total_credit.unwrap_or_default()))
+}
+
+pub fn normal_balance(account_type: &LedgerAccountType, total_debit: Decimal, total_credit: Decimal) -> Decimal {
+    match account_type {
+        LedgerAccountType::Asset | LedgerAccountType::Expense => total_debit - total_credit,
+        _ => total_credit - total_debit,
+    }
+}
+
+/// Balance of a user's account, zero if it has never been used.
+pub async fn user_account_balance<C: ConnectionTrait>(db: &C, user_id: i32, account: UserAccount) -> Result<Decimal, ApiResponse> {
+    let account_id = entity::ledger_accounts::Entity::find()
+        .filter(entity::ledger_accounts::Column::Code.eq(account.code(user_id)))
+        .select_only()
+        .column(entity::ledger_accounts::Column::Id)
+        .into_tuple::<i32>()
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    match account_id {
+        Some(account_id) => account_balance(db, account_id).await,
+        None => Ok(Decimal::ZERO),
+    }
+}
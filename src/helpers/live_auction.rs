@@ -0,0 +1,207 @@
+use chrono::Utc;
+use migration::Expr;
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set, TransactionTrait,
+};
+use serde_json::{json, Value};
+
+use crate::{
+    helpers::{clearing::allocate, fees::apply_fees},
+    utils::{api_response::ApiResponse, json_response::response},
+};
+
+/// A bid as it goes out on the public live feed, under the bidder's
+/// pseudonym for the auction.
+pub fn live_bid_json(bid: &entity::bids::Model, bidder: String) -> Value {
+    json!({
+        "type": "bid",
+        "listing_id": bid.listing_id,
+        "bidder": bidder,
+        "amount": bid.amount,
+        "quantity": bid.quantity,
+        "channel": bid.channel,
+        "created_at": bid.created_at,
+    })
+}
+
+pub fn lot_json(listing: &entity::listings::Model, best_bid: Option<Decimal>) -> Value {
+    json!({
+        "listing_id": listing.id,
+        "lot_number": listing.lot_number,
+        "title": listing.title,
+        "base_price": listing.base_price,
+        "available_volume": listing.available_volume,
+        "unit_of_measure": listing.unit_of_measure,
+        "opened_at": listing.opened_at,
+        "best_bid": best_bid,
+    })
+}
+
+/// Locks the auction row so console actions on one auction run one at a time.
+async fn lock_live_auction<C: ConnectionTrait>(db: &C, auction_id: i32) -> Result<entity::auctions::Model, ApiResponse> {
+    entity::auctions::Entity::find_by_id(auction_id)
+        .filter(entity::auctions::Column::DeletedAt.is_null())
+        .filter(entity::auctions::Column::Live.eq(true))
+        .lock_exclusive()
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .ok_or_else(|| ApiResponse::new(404, response(json!({ "error": "Live auction not found" }))))
+}
+
+/// The lot on the block: opened and not yet hammered.
+pub async fn current_lot<C: ConnectionTrait>(db: &C, auction_id: i32) -> Result<Option<entity::listings::Model>, ApiResponse> {
+    entity::listings::Entity::find()
+        .filter(entity::listings::Column::DeletedAt.is_null())
+        .filter(entity::listings::Column::AuctionId.eq(auction_id))
+        .filter(entity::listings::Column::OpenedAt.is_not_null())
+        .filter(entity::listings::Column::ClosedAt.is_null())
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))
+}
+
+/// Puts the next lot in catalogue order on the block: by lot number, with
+/// unnumbered lots last in the order they were listed. Returns None once
+/// every lot has been sold or passed.
+pub async fn open_next_lot(db: &DatabaseConnection, auction_id: i32) -> Result<Option<entity::listings::Model>, ApiResponse> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let auction = lock_live_auction(&txn, auction_id).await?;
+
+    let now = Utc::now().naive_utc();
+    if auction.start_time > now || auction.end_time < now {
+        return Err(ApiResponse::new(400, response(json!({ "error": "Lots can only be opened during the auction" }))));
+    }
+
+    if let Some(lot) = current_lot(&txn, auction.id).await? {
+        return Err(ApiResponse::new(400, response(json!({
+            "error": format!("{} is still on the block", lot.title)
+        }))));
+    }
+
+    let Some(next_lot) = entity::listings::Entity::find()
+        .filter(entity::listings::Column::DeletedAt.is_null())
+        .filter(entity::listings::Column::AuctionId.eq(auction.id))
+        .filter(entity::listings::Column::OpenedAt.is_null())
+        .filter(entity::listings::Column::ClosedAt.is_null())
+        .order_by_asc(entity::listings::Column::LotNumber)
+        .order_by_asc(entity::listings::Column::Id)
+        .one(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+    else {
+        return Ok(None);
+    };
+
+    let mut update_lot: entity::listings::ActiveModel = next_lot.into();
+    update_lot.opened_at = Set(Some(now));
+    update_lot.updated_at = Set(now);
+    let next_lot = update_lot
+        .update(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    txn.commit()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    Ok(Some(next_lot))
+}
+
+/// Knocks down the lot on the block to its best bids and takes it off the
+/// block. Its results and their fees are stored with the closing of the lot,
+/// so the lot is either sold in full or still open. A lot without bids is
+/// passed and gets no result.
+pub async fn hammer_lot(
+    db: &DatabaseConnection,
+    auction_id: i32,
+) -> Result<(entity::listings::Model, Vec<entity::auction_results::Model>), ApiResponse> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let auction = lock_live_auction(&txn, auction_id).await?;
+
+    let lot = current_lot(&txn, auction.id)
+        .await?
+        .ok_or_else(|| ApiResponse::new(400, response(json!({ "error": "No lot is on the block" }))))?;
+
+    // Waits for any bid still being stored on the lot
+    entity::listings::Entity::find_by_id(lot.id)
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let bids = entity::bids::Entity::find()
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .filter(entity::bids::Column::ListingId.eq(lot.id))
+        .all(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let mut auction_results = Vec::new();
+    for award in allocate(&auction, &lot, &bids) {
+        let auction_result = entity::auction_results::ActiveModel {
+            listing_id: Set(lot.id),
+            winning_bid_id: Set(award.bid_id),
+            winning_user_id: Set(award.user_id),
+            quantity: Set(award.quantity),
+            unit_price: Set(award.unit_price),
+            hammer_price: Set(award.hammer_price()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        apply_fees(&txn, auction.id, auction_result.id, auction_result.hammer_price).await?;
+        auction_results.push(auction_result);
+    }
+
+    let now = Utc::now().naive_utc();
+    let mut update_lot: entity::listings::ActiveModel = lot.into();
+    update_lot.closed_at = Set(Some(now));
+    update_lot.updated_at = Set(now);
+    let lot = update_lot
+        .update(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    txn.commit()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    Ok((lot, auction_results))
+}
+
+/// Winds up a live auction once its end time has passed, so no lot is left
+/// hanging when the auctioneer stops early. The lot still on the block is
+/// hammered to its best bids, and lots that were never opened are passed
+/// without a result since nobody could bid on them.
+pub async fn close_live_auction(db: &DatabaseConnection, auction_id: i32) -> Result<(), ApiResponse> {
+    if current_lot(db, auction_id).await?.is_some() {
+        hammer_lot(db, auction_id).await?;
+    }
+
+    let now = Utc::now().naive_utc();
+    entity::listings::Entity::update_many()
+        .col_expr(entity::listings::Column::ClosedAt, Expr::value(now))
+        .col_expr(entity::listings::Column::UpdatedAt, Expr::value(now))
+        .filter(entity::listings::Column::DeletedAt.is_null())
+        .filter(entity::listings::Column::AuctionId.eq(auction_id))
+        .filter(entity::listings::Column::OpenedAt.is_null())
+        .filter(entity::listings::Column::ClosedAt.is_null())
+        .exec(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    Ok(())
+}
@@ -0,0 +1,23 @@
+pub mod fees;
+pub mod invoices;
+pub mod ledger;
+pub mod bidder_limits;
+pub mod settlements;
+pub mod payouts;
+pub mod clearing;
+pub mod commitments;
+pub mod dutch;
+pub mod order_book;
+pub mod buy_now;
+pub mod candle;
+pub mod live_auction;
+pub mod staff;
+pub mod absentee;
+pub mod bid_withdrawals;
+pub mod registrations;
+pub mod kyc;
+pub mod notifications;
+pub mod phone_changes;
+pub mod privacy;
+pub mod pseudonyms;
+pub mod one_time_codes;
\ No newline at end of file
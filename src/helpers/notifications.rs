@@ -0,0 +1,242 @@
+use std::{collections::HashMap, time::Duration};
+
+use actix_web::rt::time::interval;
+use chrono::{Duration as ChronoDuration, NaiveDateTime, Utc};
+use entity::sea_orm_active_enums::{NotificationCategory, NotificationChannel, PreferredLanguage};
+use migration::Expr;
+use sea_orm::{
+    sea_query::LockBehavior, sea_query::LockType, ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use serde_json::{json, Value};
+
+use crate::utils::{api_response::ApiResponse, email::send_email, json_response::response, whatsapp::send_whatsapp_message};
+
+/// Deliveries that keep failing are given up after this many attempts.
+const MAX_DELIVERY_ATTEMPTS: i32 = 5;
+
+/// How many waiting notifications one dispatcher pass sends at most.
+const DISPATCH_BATCH_SIZE: u64 = 100;
+
+pub fn notification_json(notification: &entity::notifications::Model) -> Value {
+    json!({
+        "id": notification.id,
+        "channel": notification.channel,
+        "category": notification.category,
+        "subject": notification.subject,
+        "body": notification.body,
+        "send_after": notification.send_after,
+        "sent_at": notification.sent_at,
+        "attempts": notification.attempts,
+        "created_at": notification.created_at,
+    })
+}
+
+/// When the user's quiet hours are in force at `now`, the moment they end in
+/// UTC. Quiet hours are local times and may run past midnight.
+pub fn quiet_hours_end(user: &entity::users::Model, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let (start, end) = (user.quiet_hours_start?, user.quiet_hours_end?);
+    let offset = ChronoDuration::minutes(user.utc_offset_minutes as i64);
+    let local_now = now + offset;
+    let time = local_now.time();
+
+    let quiet = if start <= end {
+        time >= start && time < end
+    } else {
+        time >= start || time < end
+    };
+
+    if !quiet {
+        return None;
+    }
+
+    let end_date = if time < end { local_now.date() } else { local_now.date() + ChronoDuration::days(1) };
+    Some(end_date.and_time(end) - offset)
+}
+
+fn subject(category: &NotificationCategory, language: &PreferredLanguage) -> String {
+    match (category, language) {
+        (NotificationCategory::Account, PreferredLanguage::En) => "Your account",
+        (NotificationCategory::Account, PreferredLanguage::Sw) => "Akaunti yako",
+        (NotificationCategory::Auction, PreferredLanguage::En) => "Auction update",
+        (NotificationCategory::Auction, PreferredLanguage::Sw) => "Taarifa ya mnada",
+        (NotificationCategory::Marketing, PreferredLanguage::En) => "Upcoming auction",
+        (NotificationCategory::Marketing, PreferredLanguage::Sw) => "Mnada ujao",
+    }
+    .to_string()
+}
+
+/// Queues a message for a user on each channel they have chosen, written in
+/// their preferred language. Marketing only goes to users who consented to
+/// it. Auction and marketing messages wait until the user's quiet hours are
+/// over; account messages go straight away. Messages that are due are sent
+/// at once and a failed send is retried by the dispatcher, so a delivery
+/// problem never fails the caller.
+pub async fn notify_user<F>(
+    db: &DatabaseConnection,
+    user: &entity::users::Model,
+    category: NotificationCategory,
+    message: F,
+) -> Result<(), ApiResponse>
+where
+    F: Fn(&PreferredLanguage) -> String,
+{
+    if category == NotificationCategory::Marketing && !user.marketing_consent {
+        return Ok(());
+    }
+
+    let now = Utc::now().naive_utc();
+    let send_after = match category {
+        NotificationCategory::Account => now,
+        _ => quiet_hours_end(user, now).unwrap_or(now),
+    };
+
+    let mut channels = Vec::new();
+    if user.notify_whatsapp {
+        channels.push(NotificationChannel::Whatsapp);
+    }
+    if user.notify_email && user.email.is_some() {
+        channels.push(NotificationChannel::Email);
+    }
+
+    let body = message(&user.preferred_language);
+    let subject = subject(&category, &user.preferred_language);
+
+    for channel in channels {
+        let notification = entity::notifications::ActiveModel {
+            user_id: Set(user.id),
+            channel: Set(channel),
+            category: Set(category.clone()),
+            subject: Set(subject.clone()),
+            body: Set(body.clone()),
+            send_after: Set(send_after),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        if send_after <= now {
+            deliver_notification(db, notification.id, user).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn send_notification(notification: &entity::notifications::Model, user: &entity::users::Model) -> Result<(), String> {
+    match notification.channel {
+        NotificationChannel::Whatsapp => {
+            let res = send_whatsapp_message(&user.phone_number, &notification.body)
+                .await
+                .map_err(|err| err.body)?;
+
+            if !res.status().is_success() {
+                return Err(format!("WhatsApp responded with {}", res.status()));
+            }
+
+            Ok(())
+        }
+        NotificationChannel::Email => {
+            let email = user.email.as_deref().ok_or_else(|| "The user has no email address".to_string())?;
+            send_email(email, &notification.subject, &notification.body).await.map_err(|err| err.body)
+        }
+    }
+}
+
+/// Sends one waiting notification and records the outcome. The row stays
+/// locked while it is sent so it goes out once even when the dispatcher
+/// reaches it at the same moment.
+async fn deliver_notification(
+    db: &DatabaseConnection,
+    notification_id: i32,
+    user: &entity::users::Model,
+) -> Result<(), ApiResponse> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let Some(notification) = entity::notifications::Entity::find_by_id(notification_id)
+        .filter(entity::notifications::Column::SentAt.is_null())
+        .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+        .one(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+    else {
+        return Ok(());
+    };
+
+    let outcome = send_notification(&notification, user).await;
+
+    let now = Utc::now().naive_utc();
+    let attempts = notification.attempts + 1;
+    let mut update_notification: entity::notifications::ActiveModel = notification.into();
+    update_notification.attempts = Set(attempts);
+    update_notification.updated_at = Set(now);
+    match outcome {
+        Ok(()) => {
+            update_notification.sent_at = Set(Some(now));
+            update_notification.last_error = Set(None);
+        }
+        Err(err) => {
+            eprintln!("Error sending notification {}: {}", notification_id, err);
+            update_notification.last_error = Set(Some(err));
+        }
+    }
+    update_notification
+        .update(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    txn.commit()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))
+}
+
+async fn dispatch_due_notifications(db: &DatabaseConnection) -> Result<(), ApiResponse> {
+    let due = entity::notifications::Entity::find()
+        .filter(entity::notifications::Column::SentAt.is_null())
+        .filter(entity::notifications::Column::SendAfter.lte(Utc::now().naive_utc()))
+        .filter(entity::notifications::Column::Attempts.lt(MAX_DELIVERY_ATTEMPTS))
+        .order_by_asc(entity::notifications::Column::Id)
+        .limit(DISPATCH_BATCH_SIZE)
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    let users: HashMap<i32, entity::users::Model> = entity::users::Entity::find()
+        .filter(Expr::col(entity::users::Column::Id).is_in(due.iter().map(|notification| notification.user_id)))
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .into_iter()
+        .map(|user| (user.id, user))
+        .collect();
+
+    for notification in due {
+        if let Some(user) = users.get(&notification.user_id) {
+            deliver_notification(db, notification.id, user).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Sends notifications once their quiet hours are over and retries failed
+/// deliveries.
+pub async fn run_notification_dispatcher(db: DatabaseConnection) -> Result<(), ApiResponse> {
+    let mut interval = interval(Duration::from_secs(30));
+
+    loop {
+        interval.tick().await;
+
+        if let Err(err) = dispatch_due_notifications(&db).await {
+            eprintln!("Error dispatching notifications: {:?}", err);
+        }
+    }
+}
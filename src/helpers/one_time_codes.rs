@@ -0,0 +1,174 @@
+use chrono::{Duration, Utc};
+use entity::sea_orm_active_enums::OneTimeCodePurpose;
+use hmac::{Hmac, Mac};
+use migration::Expr;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde_json::json;
+use sha2::Sha256;
+
+use crate::{
+    helpers::phone_changes::{generate_code, send_to_number, MAX_CODE_ATTEMPTS},
+    utils::{api_response::ApiResponse, constants, json_response::response},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// How long a number has to wait before it can be sent another code.
+const RESEND_INTERVAL_SECONDS: i64 = 60;
+
+fn purpose_key(purpose: &OneTimeCodePurpose) -> &'static str {
+    match purpose {
+        OneTimeCodePurpose::SignIn => "sign_in",
+        OneTimeCodePurpose::DataExport => "data_export",
+        OneTimeCodePurpose::AccountErasure => "account_erasure",
+    }
+}
+
+/// Codes are kept as an HMAC bound to the number and what they are for, so a
+/// code sent for one purpose cannot be spent on another.
+fn code_mac(purpose: &OneTimeCodePurpose, phone_number: &str, code: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(constants::SECRET.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}:{}:{}", purpose_key(purpose), phone_number, code).as_bytes());
+    mac
+}
+
+fn code_message(purpose: &OneTimeCodePurpose, code: &str, ttl_minutes: i64) -> String {
+    match purpose {
+        OneTimeCodePurpose::SignIn => format!(
+            "{} is your code to sign in to your auction account. It expires in {} minutes. Do not share it.",
+            code, ttl_minutes
+        ),
+        OneTimeCodePurpose::DataExport => format!(
+            "{} is your code to download your auction account data. It expires in {} minutes. If you did not ask for this, do not share it.",
+            code, ttl_minutes
+        ),
+        OneTimeCodePurpose::AccountErasure => format!(
+            "{} is your code to erase your auction account. This cannot be undone. It expires in {} minutes. If you did not ask for this, do not share it.",
+            code, ttl_minutes
+        ),
+    }
+}
+
+/// Sends a fresh code to a number, replacing any earlier one for the same
+/// purpose. Returns the stored code and whether the message was accepted.
+pub async fn issue_code(
+    db: &DatabaseConnection,
+    phone_number: &str,
+    purpose: OneTimeCodePurpose,
+    user_id: Option<i32>,
+    name: Option<String>,
+) -> Result<(entity::one_time_codes::Model, bool), ApiResponse> {
+    let now = Utc::now().naive_utc();
+
+    let recent = entity::one_time_codes::Entity::find()
+        .filter(entity::one_time_codes::Column::PhoneNumber.eq(phone_number))
+        .filter(entity::one_time_codes::Column::Purpose.eq(purpose.clone()))
+        .filter(entity::one_time_codes::Column::UsedAt.is_null())
+        .filter(entity::one_time_codes::Column::CreatedAt.gt(now - Duration::seconds(RESEND_INTERVAL_SECONDS)))
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    if recent.is_some() {
+        return Err(ApiResponse::new(429, response(json!({ "error": "A code was just sent. Wait a minute before asking for another" }))));
+    }
+
+    entity::one_time_codes::Entity::update_many()
+        .col_expr(entity::one_time_codes::Column::ExpiresAt, Expr::value(now))
+        .col_expr(entity::one_time_codes::Column::UpdatedAt, Expr::value(now))
+        .filter(entity::one_time_codes::Column::PhoneNumber.eq(phone_number))
+        .filter(entity::one_time_codes::Column::Purpose.eq(purpose.clone()))
+        .filter(entity::one_time_codes::Column::UsedAt.is_null())
+        .filter(entity::one_time_codes::Column::ExpiresAt.gt(now))
+        .exec(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let code = generate_code();
+    let ttl_minutes = *constants::ONE_TIME_CODE_TTL_MINUTES;
+
+    let code_model = entity::one_time_codes::ActiveModel {
+        user_id: Set(user_id),
+        phone_number: Set(phone_number.to_string()),
+        purpose: Set(purpose.clone()),
+        name: Set(name),
+        code_hash: Set(hex::encode(code_mac(&purpose, phone_number, &code).finalize().into_bytes())),
+        expires_at: Set(now + Duration::minutes(ttl_minutes)),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let sent = send_to_number(phone_number, &code_message(&purpose, &code, ttl_minutes)).await;
+
+    Ok((code_model, sent))
+}
+
+/// Spends the code waiting on a number for the given purpose. Each wrong
+/// guess counts against the code, which stops working after
+/// `MAX_CODE_ATTEMPTS` of them, and a code can only be used once.
+pub async fn verify_code(
+    db: &DatabaseConnection,
+    phone_number: &str,
+    purpose: OneTimeCodePurpose,
+    code: &str,
+) -> Result<entity::one_time_codes::Model, ApiResponse> {
+    let now = Utc::now().naive_utc();
+
+    let code_model = entity::one_time_codes::Entity::find()
+        .filter(entity::one_time_codes::Column::PhoneNumber.eq(phone_number))
+        .filter(entity::one_time_codes::Column::Purpose.eq(purpose.clone()))
+        .filter(entity::one_time_codes::Column::UsedAt.is_null())
+        .filter(entity::one_time_codes::Column::ExpiresAt.gt(now))
+        .filter(entity::one_time_codes::Column::FailedAttempts.lt(MAX_CODE_ATTEMPTS))
+        .order_by_desc(entity::one_time_codes::Column::Id)
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .ok_or_else(|| ApiResponse::new(400, response(json!({ "error": "No code is waiting for this number. Ask for a new one" }))))?;
+
+    let matches = match hex::decode(&code_model.code_hash) {
+        Ok(expected) => code_mac(&purpose, phone_number, code.trim()).verify_slice(&expected).is_ok(),
+        Err(_) => false,
+    };
+
+    if !matches {
+        entity::one_time_codes::Entity::update_many()
+            .col_expr(
+                entity::one_time_codes::Column::FailedAttempts,
+                Expr::col(entity::one_time_codes::Column::FailedAttempts).add(1),
+            )
+            .col_expr(entity::one_time_codes::Column::UpdatedAt, Expr::value(now))
+            .filter(entity::one_time_codes::Column::Id.eq(code_model.id))
+            .exec(db)
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        let attempts_remaining = MAX_CODE_ATTEMPTS - code_model.failed_attempts - 1;
+        if attempts_remaining <= 0 {
+            return Err(ApiResponse::new(400, response(json!({ "error": "Too many wrong codes. Ask for a new one" }))));
+        }
+
+        return Err(ApiResponse::new(400, response(json!({
+            "error": "The code is not correct",
+            "attempts_remaining": attempts_remaining
+        }))));
+    }
+
+    // Only one request gets to spend the code
+    let spent = entity::one_time_codes::Entity::update_many()
+        .col_expr(entity::one_time_codes::Column::UsedAt, Expr::value(now))
+        .col_expr(entity::one_time_codes::Column::UpdatedAt, Expr::value(now))
+        .filter(entity::one_time_codes::Column::Id.eq(code_model.id))
+        .filter(entity::one_time_codes::Column::UsedAt.is_null())
+        .exec(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    if spent.rows_affected == 0 {
+        return Err(ApiResponse::new(400, response(json!({ "error": "No code is waiting for this number. Ask for a new one" }))));
+    }
+
+    Ok(code_model)
+}
@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+
+use chrono::Utc;
+use entity::sea_orm_active_enums::{OrderSide, OrderStatus};
+use migration::Expr;
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveEnum, ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+    Set, TransactionTrait,
+};
+use serde_json::json;
+
+use crate::utils::{api_response::ApiResponse, json_response::response};
+
+/// Units of an order still waiting to be matched.
+pub fn unfilled_quantity(order: &entity::orders::Model) -> i32 {
+    order.quantity - order.filled_quantity
+}
+
+fn resting_statuses() -> [OrderStatus; 2] {
+    [OrderStatus::Open, OrderStatus::PartiallyFilled]
+}
+
+fn fill_status(quantity: i32, filled_quantity: i32) -> OrderStatus {
+    if filled_quantity >= quantity {
+        OrderStatus::Filled
+    } else if filled_quantity > 0 {
+        OrderStatus::PartiallyFilled
+    } else {
+        OrderStatus::Open
+    }
+}
+
+/// Adds a limit order to a listing's book and matches it against resting
+/// orders on the other side with price-time priority: the best price first
+/// and, at the same price, the oldest order. Each match trades at the resting
+/// order's price and an order can be filled by several matches. Whatever is
+/// left unmatched rests on the book. Orders never match against another
+/// order from the same user.
+pub async fn place_order(
+    db: &DatabaseConnection,
+    listing_id: i32,
+    user_id: i32,
+    side: OrderSide,
+    price: Decimal,
+    quantity: i32,
+) -> Result<(entity::orders::Model, Vec<entity::trades::Model>), ApiResponse> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    // One order matches against a listing's book at a time
+    entity::listings::Entity::find_by_id(listing_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let order = entity::orders::ActiveModel {
+        listing_id: Set(listing_id),
+        user_id: Set(user_id),
+        side: Set(side.clone()),
+        price: Set(price),
+        quantity: Set(quantity),
+        filled_quantity: Set(0),
+        status: Set(OrderStatus::Open),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let mut resting_orders = entity::orders::Entity::find()
+        .filter(entity::orders::Column::DeletedAt.is_null())
+        .filter(entity::orders::Column::ListingId.eq(listing_id))
+        .filter(entity::orders::Column::Status.is_in(resting_statuses()))
+        .filter(entity::orders::Column::UserId.ne(user_id));
+
+    resting_orders = match side {
+        OrderSide::Buy => resting_orders
+            .filter(entity::orders::Column::Side.eq(OrderSide::Sell))
+            .filter(entity::orders::Column::Price.lte(price))
+            .order_by_asc(entity::orders::Column::Price),
+        OrderSide::Sell => resting_orders
+            .filter(entity::orders::Column::Side.eq(OrderSide::Buy))
+            .filter(entity::orders::Column::Price.gte(price))
+            .order_by_desc(entity::orders::Column::Price),
+    };
+
+    let resting_orders = resting_orders
+        .order_by_asc(entity::orders::Column::CreatedAt)
+        .order_by_asc(entity::orders::Column::Id)
+        .all(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let now = Utc::now().naive_utc();
+    let mut filled_quantity = 0;
+    let mut trades = Vec::new();
+
+    for resting_order in resting_orders {
+        if filled_quantity >= quantity {
+            break;
+        }
+
+        let trade_quantity = (quantity - filled_quantity).min(unfilled_quantity(&resting_order));
+        let (buy_order, sell_order) = match side {
+            OrderSide::Buy => (&order, &resting_order),
+            OrderSide::Sell => (&resting_order, &order),
+        };
+
+        let trade = entity::trades::ActiveModel {
+            listing_id: Set(listing_id),
+            buy_order_id: Set(buy_order.id),
+            sell_order_id: Set(sell_order.id),
+            buyer_id: Set(buy_order.user_id),
+            seller_id: Set(sell_order.user_id),
+            price: Set(resting_order.price),
+            quantity: Set(trade_quantity),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        let resting_filled_quantity = resting_order.filled_quantity + trade_quantity;
+        let mut update_resting_order: entity::orders::ActiveModel = resting_order.clone().into();
+        update_resting_order.filled_quantity = Set(resting_filled_quantity);
+        update_resting_order.status = Set(fill_status(resting_order.quantity, resting_filled_quantity));
+        update_resting_order.updated_at = Set(now);
+        update_resting_order
+            .update(&txn)
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        filled_quantity += trade_quantity;
+        trades.push(trade);
+    }
+
+    let order = if filled_quantity > 0 {
+        let mut update_order: entity::orders::ActiveModel = order.into();
+        update_order.filled_quantity = Set(filled_quantity);
+        update_order.status = Set(fill_status(quantity, filled_quantity));
+        update_order.updated_at = Set(now);
+        update_order
+            .update(&txn)
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+    } else {
+        order
+    };
+
+    txn.commit()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    Ok((order, trades))
+}
+
+/// Takes the unfilled part of a user's order off the book. The status check
+/// is part of the update so an order cannot be cancelled while it fills.
+pub async fn cancel_order(db: &DatabaseConnection, order_id: i32, user_id: i32) -> Result<(), ApiResponse> {
+    let now = Utc::now().naive_utc();
+
+    let result = entity::orders::Entity::update_many()
+        .col_expr(entity::orders::Column::Status, Expr::val(OrderStatus::Cancelled).as_enum(OrderStatus::name()))
+        .col_expr(entity::orders::Column::CancelledAt, Expr::val(now).into())
+        .col_expr(entity::orders::Column::UpdatedAt, Expr::val(now).into())
+        .filter(entity::orders::Column::Id.eq(order_id))
+        .filter(entity::orders::Column::UserId.eq(user_id))
+        .filter(entity::orders::Column::DeletedAt.is_null())
+        .filter(entity::orders::Column::Status.is_in(resting_statuses()))
+        .exec(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    if result.rows_affected == 0 {
+        return Err(ApiResponse::new(400, response(json!({
+            "error": "Only your own open orders can be cancelled"
+        }))));
+    }
+
+    Ok(())
+}
+
+/// Resting quantity at one price on one side of the book.
+pub struct PriceLevel {
+    pub price: Decimal,
+    pub quantity: i32,
+    pub orders: usize,
+}
+
+/// The best `levels` price levels on each side of a listing's book: bids
+/// from the highest price down and asks from the lowest up.
+pub async fn market_depth(
+    db: &DatabaseConnection,
+    listing_id: i32,
+    levels: usize,
+) -> Result<(Vec<PriceLevel>, Vec<PriceLevel>), ApiResponse> {
+    let resting_orders = entity::orders::Entity::find()
+        .filter(entity::orders::Column::DeletedAt.is_null())
+        .filter(entity::orders::Column::ListingId.eq(listing_id))
+        .filter(entity::orders::Column::Status.is_in(resting_statuses()))
+        .select_only()
+        .column(entity::orders::Column::Side)
+        .column(entity::orders::Column::Price)
+        .column(entity::orders::Column::Quantity)
+        .column(entity::orders::Column::FilledQuantity)
+        .into_tuple::<(OrderSide, Decimal, i32, i32)>()
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let mut bids: BTreeMap<Decimal, PriceLevel> = BTreeMap::new();
+    let mut asks: BTreeMap<Decimal, PriceLevel> = BTreeMap::new();
+
+    for (side, price, quantity, filled_quantity) in resting_orders {
+        let book = match side {
+            OrderSide::Buy => &mut bids,
+            OrderSide::Sell => &mut asks,
+        };
+
+        let level = book.entry(price).or_insert(PriceLevel { price, quantity: 0, orders: 0 });
+        level.quantity += quantity - filled_quantity;
+        level.orders += 1;
+    }
+
+    Ok((
+        bids.into_values().rev().take(levels).collect(),
+        asks.into_values().take(levels).collect(),
+    ))
+}
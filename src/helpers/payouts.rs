@@ -0,0 +1,381 @@
+use std::collections::BTreeMap;
+
+use chrono::Utc;
+use entity::sea_orm_active_enums::{InvoiceStatus, PayoutStatus};
+use migration::{Expr, Query};
+use rust_decimal::Decimal;
+use sea_orm::{
+    ActiveEnum, ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use serde_json::json;
+
+use crate::{
+    helpers::{
+        ledger::{post_journal_entry, system_account, user_account, Posting, SystemAccount, UserAccount},
+        settlements::SellerStatement,
+    },
+    utils::{
+        api_response::ApiResponse,
+        disbursement::{DisbursementProvider, DisbursementRequest},
+        json_response::response,
+    },
+};
+
+/// Groups every paid, not yet paid out result by consignor into a new
+/// pending batch. Results on a failed payout become eligible again.
+pub async fn create_payout_batch(
+    db: &DatabaseConnection,
+) -> Result<Option<(entity::payout_batches::Model, Vec<entity::payouts::Model>)>, ApiResponse> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    // Two batches built at once would both pick up the same results
+    txn.execute_unprepared("LOCK TABLE payout_items IN EXCLUSIVE MODE")
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let mut paid_results = Query::select();
+    paid_results
+        .column((entity::invoice_lines::Entity, entity::invoice_lines::Column::AuctionResultId))
+        .from(entity::invoice_lines::Entity)
+        .inner_join(
+            entity::invoices::Entity,
+            Expr::col((entity::invoices::Entity, entity::invoices::Column::Id))
+                .equals((entity::invoice_lines::Entity, entity::invoice_lines::Column::InvoiceId)),
+        )
+        .and_where(entity::invoices::Column::Status.eq(InvoiceStatus::Paid))
+        .and_where(Expr::col((entity::invoice_lines::Entity, entity::invoice_lines::Column::AuctionResultId)).is_not_null());
+
+    let mut paid_out_results = Query::select();
+    paid_out_results
+        .column((entity::payout_items::Entity, entity::payout_items::Column::AuctionResultId))
+        .from(entity::payout_items::Entity)
+        .inner_join(
+            entity::payouts::Entity,
+            Expr::col((entity::payouts::Entity, entity::payouts::Column::Id))
+                .equals((entity::payout_items::Entity, entity::payout_items::Column::PayoutId)),
+        )
+        .and_where(entity::payouts::Column::Status.ne(PayoutStatus::Failed));
+
+    let eligible_results = entity::auction_results::Entity::find()
+        .inner_join(entity::listings::Entity)
+        .filter(entity::auction_results::Column::DeletedAt.is_null())
+        .filter(entity::listings::Column::SellerId.is_not_null())
+        .filter(entity::auction_results::Column::Id.in_subquery(paid_results.to_owned()))
+        .filter(entity::auction_results::Column::Id.not_in_subquery(paid_out_results.to_owned()))
+        .order_by_asc(entity::auction_results::Column::Id)
+        .select_only()
+        .column(entity::auction_results::Column::Id)
+        .column(entity::listings::Column::SellerId)
+        .into_tuple::<(i32, i32)>()
+        .all(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let mut results_by_seller: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+    for (auction_result_id, seller_id) in eligible_results {
+        results_by_seller.entry(seller_id).or_default().push(auction_result_id);
+    }
+
+    let mut statements = Vec::new();
+    for (seller_id, auction_result_ids) in results_by_seller {
+        let statement = SellerStatement::load(&txn, seller_id, None, Some(auction_result_ids)).await?;
+        let owed = statement.net_total() + held_back(&txn, seller_id).await?;
+        let amount = owed.floor();
+
+        // Nothing can be sent to a consignor whose charges swallowed the hammer
+        // price, and less than a shilling waits for their next results
+        if amount > Decimal::ZERO {
+            statements.push((statement, amount, owed - amount));
+        }
+    }
+
+    if statements.is_empty() {
+        return Ok(None);
+    }
+
+    let batch = entity::payout_batches::ActiveModel {
+        status: Set(PayoutStatus::Pending),
+        total: Set(statements.iter().map(|(_, amount, _)| *amount).sum()),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let mut payouts = Vec::new();
+    for (statement, amount, carried_forward) in statements {
+        let payout = entity::payouts::ActiveModel {
+            payout_batch_id: Set(batch.id),
+            seller_id: Set(statement.seller_id),
+            status: Set(PayoutStatus::Pending),
+            hammer_total: Set(statement.hammer_total()),
+            commission_total: Set(statement.commission_total()),
+            tax_total: Set(statement.tax_total()),
+            amount: Set(amount),
+            carried_forward: Set(carried_forward),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        for line in &statement.lines {
+            entity::payout_items::ActiveModel {
+                payout_id: Set(payout.id),
+                auction_result_id: Set(line.auction_result_id),
+                hammer_price: Set(line.hammer_price),
+                commission: Set(line.totals.seller_fees),
+                tax: Set(line.totals.seller_tax),
+                net: Set(line.net()),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+        }
+
+        payouts.push(payout);
+    }
+
+    txn.commit()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    Ok(Some((batch, payouts)))
+}
+
+/// What a consignor has been owed on earlier payouts but not sent, because
+/// payouts go out in whole shillings. Failed payouts do not count, since
+/// their results go into a later batch anyway. The cents stay on the
+/// consignor's payable account in the ledger until they are paid.
+async fn held_back<C: ConnectionTrait>(db: &C, seller_id: i32) -> Result<Decimal, ApiResponse> {
+    let owed = entity::payout_items::Entity::find()
+        .inner_join(entity::payouts::Entity)
+        .filter(entity::payouts::Column::DeletedAt.is_null())
+        .filter(entity::payouts::Column::SellerId.eq(seller_id))
+        .filter(entity::payouts::Column::Status.ne(PayoutStatus::Failed))
+        .select_only()
+        .column_as(entity::payout_items::Column::Net.sum(), "owed")
+        .into_tuple::<Option<Decimal>>()
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .flatten()
+        .unwrap_or(Decimal::ZERO);
+
+    let paid = entity::payouts::Entity::find()
+        .filter(entity::payouts::Column::DeletedAt.is_null())
+        .filter(entity::payouts::Column::SellerId.eq(seller_id))
+        .filter(entity::payouts::Column::Status.ne(PayoutStatus::Failed))
+        .select_only()
+        .column_as(entity::payouts::Column::Amount.sum(), "paid")
+        .into_tuple::<Option<Decimal>>()
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .flatten()
+        .unwrap_or(Decimal::ZERO);
+
+    Ok(owed - paid)
+}
+
+/// Moves a batch into `to` if it is currently in one of `from`, returning
+/// whether it moved. The check and update are one statement so two requests
+/// cannot both act on the same batch.
+async fn update_batch_status<C: ConnectionTrait>(
+    db: &C,
+    batch_id: i32,
+    from: &[PayoutStatus],
+    to: PayoutStatus,
+) -> Result<bool, ApiResponse> {
+    let now = Utc::now().naive_utc();
+
+    let mut update = entity::payout_batches::Entity::update_many()
+        .col_expr(entity::payout_batches::Column::Status, Expr::val(to.clone()).as_enum(PayoutStatus::name()))
+        .col_expr(entity::payout_batches::Column::UpdatedAt, Expr::val(now).into());
+
+    update = match to {
+        PayoutStatus::Approved => update.col_expr(entity::payout_batches::Column::ApprovedAt, Expr::val(now).into()),
+        PayoutStatus::Sent | PayoutStatus::Failed => update.col_expr(entity::payout_batches::Column::SentAt, Expr::val(now).into()),
+        _ => update,
+    };
+
+    let result = update
+        .filter(entity::payout_batches::Column::Id.eq(batch_id))
+        .filter(entity::payout_batches::Column::DeletedAt.is_null())
+        .filter(entity::payout_batches::Column::Status.is_in(from.to_vec()))
+        .exec(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    Ok(result.rows_affected > 0)
+}
+
+/// Moves a batch from one status to another, failing if it is not currently
+/// in `from`.
+async fn transition_batch<C: ConnectionTrait>(
+    db: &C,
+    batch_id: i32,
+    from: PayoutStatus,
+    to: PayoutStatus,
+    error: &str,
+) -> Result<(), ApiResponse> {
+    if !update_batch_status(db, batch_id, &[from], to).await? {
+        return Err(ApiResponse::new(400, response(json!({ "error": error }))));
+    }
+
+    Ok(())
+}
+
+/// Approves a pending batch and its payouts, recording the admin who did.
+pub async fn approve_payout_batch(db: &DatabaseConnection, batch_id: i32, admin_id: i32) -> Result<(), ApiResponse> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    transition_batch(&txn, batch_id, PayoutStatus::Pending, PayoutStatus::Approved, "Only pending batches can be approved").await?;
+
+    entity::payout_batches::Entity::update_many()
+        .col_expr(entity::payout_batches::Column::ApprovedBy, Expr::val(admin_id).into())
+        .filter(entity::payout_batches::Column::Id.eq(batch_id))
+        .exec(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    entity::payouts::Entity::update_many()
+        .col_expr(entity::payouts::Column::Status, Expr::val(PayoutStatus::Approved).as_enum(PayoutStatus::name()))
+        .col_expr(entity::payouts::Column::UpdatedAt, Expr::val(Utc::now().naive_utc()).into())
+        .filter(entity::payouts::Column::PayoutBatchId.eq(batch_id))
+        .filter(entity::payouts::Column::Status.eq(PayoutStatus::Pending))
+        .exec(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    txn.commit()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))
+}
+
+/// Sends every approved payout in a batch through the provider. The batch
+/// is marked sending and each payout is claimed on its own before it is
+/// disbursed, so no consignor is paid twice and a send that stops part way
+/// can be run again for the payouts still approved. A payout left sending
+/// was claimed but its outcome never recorded, and has to be checked with
+/// the provider by its reference. Once every payout is settled the batch
+/// ends up sent, or failed if any of its payouts did, and the failed
+/// payouts' results can go into a later batch.
+pub async fn send_payout_batch(
+    db: &DatabaseConnection,
+    provider: &dyn DisbursementProvider,
+    batch_id: i32,
+) -> Result<entity::payout_batches::Model, ApiResponse> {
+    // A batch already sending is picked up again where it stopped
+    if !update_batch_status(db, batch_id, &[PayoutStatus::Approved, PayoutStatus::Sending], PayoutStatus::Sending).await? {
+        return Err(ApiResponse::new(400, response(json!({ "error": "Only approved batches can be sent" }))));
+    }
+
+    let payouts = entity::payouts::Entity::find()
+        .filter(entity::payouts::Column::DeletedAt.is_null())
+        .filter(entity::payouts::Column::PayoutBatchId.eq(batch_id))
+        .filter(entity::payouts::Column::Status.eq(PayoutStatus::Approved))
+        .find_also_related(entity::users::Entity)
+        .order_by_asc(entity::payouts::Column::Id)
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    for (payout, seller) in payouts {
+        // Claims the payout so a second send request cannot pay it too
+        let claimed = entity::payouts::Entity::update_many()
+            .col_expr(entity::payouts::Column::Status, Expr::val(PayoutStatus::Sending).as_enum(PayoutStatus::name()))
+            .col_expr(entity::payouts::Column::UpdatedAt, Expr::val(Utc::now().naive_utc()).into())
+            .filter(entity::payouts::Column::Id.eq(payout.id))
+            .filter(entity::payouts::Column::Status.eq(PayoutStatus::Approved))
+            .exec(db)
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        if claimed.rows_affected == 0 {
+            continue;
+        }
+
+        let request = DisbursementRequest {
+            reference: format!("PAYOUT-{:06}", payout.id),
+            phone_number: seller.map(|seller| seller.phone_number).unwrap_or_default(),
+            amount: payout.amount,
+            remarks: format!("Consignment proceeds, batch {}", batch_id),
+        };
+
+        let outcome = provider.disburse(&request).await;
+
+        let txn = db
+            .begin()
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        let now = Utc::now().naive_utc();
+        let mut update_payout_model: entity::payouts::ActiveModel = payout.clone().into();
+        update_payout_model.updated_at = Set(now);
+
+        match outcome {
+            Ok(receipt) => {
+                let consignor_payable = user_account(&txn, payout.seller_id, UserAccount::ConsignorPayable).await?;
+                let cash = system_account(&txn, SystemAccount::Cash).await?;
+
+                post_journal_entry(
+                    &txn,
+                    &format!("payout:{}", payout.id),
+                    &format!("Payout to consignor {} ({})", payout.seller_id, receipt.provider_reference),
+                    vec![Posting::debit(consignor_payable, payout.amount), Posting::credit(cash, payout.amount)],
+                )
+                .await?;
+
+                update_payout_model.status = Set(PayoutStatus::Sent);
+                update_payout_model.provider_reference = Set(Some(receipt.provider_reference));
+                update_payout_model.sent_at = Set(Some(now));
+            }
+            Err(reason) => {
+                update_payout_model.status = Set(PayoutStatus::Failed);
+                update_payout_model.failure_reason = Set(Some(reason));
+            }
+        }
+
+        update_payout_model
+            .update(&txn)
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        txn.commit()
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+    }
+
+    let statuses = entity::payouts::Entity::find()
+        .filter(entity::payouts::Column::DeletedAt.is_null())
+        .filter(entity::payouts::Column::PayoutBatchId.eq(batch_id))
+        .select_only()
+        .column(entity::payouts::Column::Status)
+        .into_tuple::<PayoutStatus>()
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    // The batch stays sending while any payout is unsettled, whether another
+    // request is still sending it or its outcome was lost
+    if !statuses.iter().any(|status| matches!(status, PayoutStatus::Approved | PayoutStatus::Sending)) {
+        let status = if statuses.contains(&PayoutStatus::Failed) { PayoutStatus::Failed } else { PayoutStatus::Sent };
+
+        update_batch_status(db, batch_id, &[PayoutStatus::Sending], status).await?;
+    }
+
+    entity::payout_batches::Entity::find_by_id(batch_id)
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .ok_or_else(|| ApiResponse::new(404, response(json!({ "error": "Payout batch not found" }))))
+}
@@ -0,0 +1,311 @@
+use std::time::Duration;
+
+use actix_web::rt::time::interval;
+use chrono::{Duration as ChronoDuration, NaiveDateTime, Utc};
+use entity::sea_orm_active_enums::{NotificationCategory, PhoneChangeStatus, PreferredLanguage};
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sea_orm::{
+    sea_query::LockType, ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, QuerySelect, Set, TransactionTrait,
+};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use crate::{
+    helpers::notifications::notify_user,
+    utils::{api_response::ApiResponse, constants, json_response::response, whatsapp::send_whatsapp_message},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Wrong codes allowed on one change request before it is cancelled.
+pub const MAX_CODE_ATTEMPTS: i32 = 5;
+
+pub fn generate_code() -> String {
+    format!("{:06}", rand::rng().random_range(0..1_000_000))
+}
+
+/// Codes are kept as an HMAC bound to the user and the number they were sent
+/// to, so a code sent to one number cannot confirm the other.
+fn code_mac(user_id: i32, phone_number: &str, code: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(constants::SECRET.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}:{}:{}", user_id, phone_number, code).as_bytes());
+    mac
+}
+
+pub fn code_hash(user_id: i32, phone_number: &str, code: &str) -> String {
+    hex::encode(code_mac(user_id, phone_number, code).finalize().into_bytes())
+}
+
+pub fn code_matches(user_id: i32, phone_number: &str, code: &str, hash: &str) -> bool {
+    match hex::decode(hash) {
+        Ok(expected) => code_mac(user_id, phone_number, code).verify_slice(&expected).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Sends a message straight to a number rather than through the user's
+/// notification preferences, since the number itself is what is being
+/// proven. Returns whether the message was accepted.
+pub async fn send_to_number(phone_number: &str, message: &str) -> bool {
+    match send_whatsapp_message(phone_number, message).await {
+        Ok(res) if res.status().is_success() => true,
+        Ok(res) => {
+            eprintln!("Error sending message to {}: WhatsApp responded with {}", phone_number, res.status());
+            false
+        }
+        Err(err) => {
+            eprintln!("Error sending message to {}: {}", phone_number, err.body);
+            false
+        }
+    }
+}
+
+/// When a change whose new number is confirmed takes effect. Confirming the
+/// old number as well earns the shorter wait.
+pub fn effective_at(change: &entity::phone_number_changes::Model) -> Option<NaiveDateTime> {
+    let new_verified_at = change.new_verified_at?;
+
+    Some(match change.old_verified_at {
+        Some(old_verified_at) => {
+            new_verified_at.max(old_verified_at) + ChronoDuration::hours(*constants::PHONE_CHANGE_COOLING_OFF_HOURS)
+        }
+        None => new_verified_at + ChronoDuration::hours(*constants::PHONE_CHANGE_UNCONFIRMED_COOLING_OFF_HOURS),
+    })
+}
+
+/// Change requests that still hold on to their new number: those waiting for
+/// codes that have not expired and those in their cooling-off period.
+pub fn open_change_condition(now: NaiveDateTime) -> Condition {
+    Condition::any()
+        .add(entity::phone_number_changes::Column::Status.eq(PhoneChangeStatus::CoolingOff))
+        .add(
+            Condition::all()
+                .add(entity::phone_number_changes::Column::Status.eq(PhoneChangeStatus::AwaitingVerification))
+                .add(entity::phone_number_changes::Column::CodesExpireAt.gt(now)),
+        )
+}
+
+/// Refuses a number that belongs to another account, including closed ones,
+/// or that another user is in the middle of moving to.
+pub async fn check_number_available<C: ConnectionTrait>(
+    db: &C,
+    phone_number: &str,
+    user_id: Option<i32>,
+) -> Result<(), ApiResponse> {
+    let mut owner = entity::users::Entity::find().filter(entity::users::Column::PhoneNumber.eq(phone_number));
+    let mut reservation = entity::phone_number_changes::Entity::find()
+        .filter(entity::phone_number_changes::Column::NewPhoneNumber.eq(phone_number))
+        .filter(open_change_condition(Utc::now().naive_utc()));
+
+    if let Some(user_id) = user_id {
+        owner = owner.filter(entity::users::Column::Id.ne(user_id));
+        reservation = reservation.filter(entity::phone_number_changes::Column::UserId.ne(user_id));
+    }
+
+    let owner = owner
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    if owner.is_some() {
+        return Err(ApiResponse::new(409, response(json!({ "error": "This phone number belongs to another account" }))));
+    }
+
+    let reservation = reservation
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    if reservation.is_some() {
+        return Err(ApiResponse::new(409, response(json!({ "error": "This phone number is being moved to another account" }))));
+    }
+
+    Ok(())
+}
+
+pub async fn cancel_change<C: ConnectionTrait>(
+    db: &C,
+    change: entity::phone_number_changes::Model,
+    reason: &str,
+) -> Result<entity::phone_number_changes::Model, ApiResponse> {
+    let now = Utc::now().naive_utc();
+    let mut update_change: entity::phone_number_changes::ActiveModel = change.into();
+    update_change.status = Set(PhoneChangeStatus::Cancelled);
+    update_change.cancelled_at = Set(Some(now));
+    update_change.cancel_reason = Set(Some(reason.to_string()));
+    update_change.updated_at = Set(now);
+    update_change
+        .update(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))
+}
+
+pub fn change_json(change: &entity::phone_number_changes::Model) -> Value {
+    json!({
+        "id": change.id,
+        "user_id": change.user_id,
+        "old_phone_number": change.old_phone_number,
+        "new_phone_number": change.new_phone_number,
+        "status": change.status,
+        "codes_expire_at": change.codes_expire_at,
+        "old_verified_at": change.old_verified_at,
+        "new_verified_at": change.new_verified_at,
+        "effective_at": change.effective_at,
+        "completed_at": change.completed_at,
+        "cancelled_at": change.cancelled_at,
+        "cancel_reason": change.cancel_reason,
+        "created_at": change.created_at,
+    })
+}
+
+pub fn history_json(history: &entity::phone_number_history::Model) -> Value {
+    json!({
+        "id": history.id,
+        "phone_number": history.phone_number,
+        "change_id": history.change_id,
+        "valid_until": history.valid_until,
+    })
+}
+
+/// Moves the account to its new number once the cooling-off period is over.
+/// The number is checked again first and the change is cancelled if it has
+/// since been taken. The old number is kept in the user's history and both
+/// numbers are told about the move.
+async fn complete_phone_change(db: &DatabaseConnection, change_id: i32) -> Result<(), ApiResponse> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let now = Utc::now().naive_utc();
+    let Some(change) = entity::phone_number_changes::Entity::find_by_id(change_id)
+        .filter(entity::phone_number_changes::Column::Status.eq(PhoneChangeStatus::CoolingOff))
+        .filter(entity::phone_number_changes::Column::EffectiveAt.lte(now))
+        .lock(LockType::Update)
+        .one(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+    else {
+        return Ok(());
+    };
+
+    let user = entity::users::Entity::find_by_id(change.user_id)
+        .filter(entity::users::Column::DeletedAt.is_null())
+        .lock(LockType::Update)
+        .one(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let Some(user) = user.filter(|user| user.phone_number == change.old_phone_number) else {
+        cancel_change(&txn, change, "The account is closed or its number has already changed").await?;
+        return txn
+            .commit()
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))));
+    };
+
+    let taken = entity::users::Entity::find()
+        .filter(entity::users::Column::PhoneNumber.eq(change.new_phone_number.clone()))
+        .one(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    if taken.is_some() {
+        cancel_change(&txn, change, "The new number now belongs to another account").await?;
+        return txn
+            .commit()
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))));
+    }
+
+    entity::phone_number_history::ActiveModel {
+        user_id: Set(user.id),
+        phone_number: Set(change.old_phone_number.clone()),
+        change_id: Set(Some(change.id)),
+        valid_until: Set(now),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let mut update_user: entity::users::ActiveModel = user.into();
+    update_user.phone_number = Set(change.new_phone_number.clone());
+    update_user.updated_at = Set(now);
+    let user = update_user
+        .update(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let mut update_change: entity::phone_number_changes::ActiveModel = change.clone().into();
+    update_change.status = Set(PhoneChangeStatus::Completed);
+    update_change.completed_at = Set(Some(now));
+    update_change.updated_at = Set(now);
+    update_change
+        .update(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    txn.commit()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    send_to_number(
+        &change.old_phone_number,
+        &format!(
+            "Your auction account has moved to {}. This number no longer signs in to it.",
+            change.new_phone_number
+        ),
+    )
+    .await;
+
+    notify_user(db, &user, NotificationCategory::Account, |language| match language {
+        PreferredLanguage::En => format!("Your account now uses {}.", change.new_phone_number),
+        PreferredLanguage::Sw => format!("Akaunti yako sasa inatumia {}.", change.new_phone_number),
+    })
+    .await
+}
+
+async fn process_phone_changes(db: &DatabaseConnection) -> Result<(), ApiResponse> {
+    let now = Utc::now().naive_utc();
+
+    let expired = entity::phone_number_changes::Entity::find()
+        .filter(entity::phone_number_changes::Column::Status.eq(PhoneChangeStatus::AwaitingVerification))
+        .filter(entity::phone_number_changes::Column::CodesExpireAt.lte(now))
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    for change in expired {
+        cancel_change(db, change, "The verification codes expired").await?;
+    }
+
+    let due = entity::phone_number_changes::Entity::find()
+        .filter(entity::phone_number_changes::Column::Status.eq(PhoneChangeStatus::CoolingOff))
+        .filter(entity::phone_number_changes::Column::EffectiveAt.lte(now))
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    for change in due {
+        complete_phone_change(db, change.id).await?;
+    }
+
+    Ok(())
+}
+
+/// Completes phone number changes whose cooling-off period is over and clears
+/// out requests whose codes were never used.
+pub async fn run_phone_change_completion(db: DatabaseConnection) -> Result<(), ApiResponse> {
+    let mut interval = interval(Duration::from_secs(60));
+
+    loop {
+        interval.tick().await;
+
+        if let Err(err) = process_phone_changes(&db).await {
+            eprintln!("Error completing phone number changes: {:?}", err);
+        }
+    }
+}
@@ -0,0 +1,456 @@
+use std::{
+    fs,
+    io::{Cursor, Write},
+    path::Path,
+};
+
+use actix_web::web;
+use chrono::Utc;
+use entity::sea_orm_active_enums::{InvoiceStatus, OrderStatus, PayoutStatus};
+use migration::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Select, Set,
+    TransactionTrait,
+};
+use serde_json::{json, Map, Value};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use crate::{
+    helpers::{clearing::bidding_closes_at, kyc::read_document, phone_changes::send_to_number},
+    utils::{api_response::ApiResponse, json_response::response},
+};
+
+/// Every row of a query as JSON, less the columns that stay internal even
+/// from the person the row is about.
+async fn export_rows<E: EntityTrait>(
+    db: &DatabaseConnection,
+    select: Select<E>,
+    hidden: &[&str],
+) -> Result<Vec<Value>, ApiResponse> {
+    let rows = select
+        .into_json()
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|mut row| {
+            if let Some(object) = row.as_object_mut() {
+                for column in hidden {
+                    object.remove(*column);
+                }
+            }
+            row
+        })
+        .collect())
+}
+
+/// Everything held about a user, one section per kind of record, along with
+/// the identity documents they uploaded so the archive can carry the files.
+pub async fn collect_user_data(
+    db: &DatabaseConnection,
+    user: &entity::users::Model,
+) -> Result<(Value, Vec<entity::kyc_documents::Model>), ApiResponse> {
+    let mut data = Map::new();
+    data.insert("exported_at".to_string(), json!(Utc::now().naive_utc()));
+
+    let profile = export_rows(db, entity::users::Entity::find_by_id(user.id), &[]).await?;
+    data.insert("profile".to_string(), profile.into_iter().next().unwrap_or(Value::Null));
+
+    let phone_number_history = export_rows(
+        db,
+        entity::phone_number_history::Entity::find()
+            .filter(entity::phone_number_history::Column::UserId.eq(user.id))
+            .order_by_asc(entity::phone_number_history::Column::Id),
+        &[],
+    )
+    .await?;
+    data.insert("phone_number_history".to_string(), json!(phone_number_history));
+
+    let phone_number_changes = export_rows(
+        db,
+        entity::phone_number_changes::Entity::find()
+            .filter(entity::phone_number_changes::Column::UserId.eq(user.id))
+            .order_by_asc(entity::phone_number_changes::Column::Id),
+        &["old_code_hash", "new_code_hash"],
+    )
+    .await?;
+    data.insert("phone_number_changes".to_string(), json!(phone_number_changes));
+
+    let documents = entity::kyc_documents::Entity::find()
+        .filter(entity::kyc_documents::Column::UserId.eq(user.id))
+        .filter(entity::kyc_documents::Column::DeletedAt.is_null())
+        .order_by_asc(entity::kyc_documents::Column::Id)
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let kyc_documents = export_rows(
+        db,
+        entity::kyc_documents::Entity::find()
+            .filter(entity::kyc_documents::Column::UserId.eq(user.id))
+            .filter(entity::kyc_documents::Column::DeletedAt.is_null())
+            .order_by_asc(entity::kyc_documents::Column::Id),
+        &["storage_path"],
+    )
+    .await?;
+    data.insert("kyc_documents".to_string(), json!(kyc_documents));
+
+    let kyc_reviews = export_rows(
+        db,
+        entity::kyc_reviews::Entity::find()
+            .filter(entity::kyc_reviews::Column::UserId.eq(user.id))
+            .order_by_asc(entity::kyc_reviews::Column::Id),
+        &[],
+    )
+    .await?;
+    data.insert("kyc_reviews".to_string(), json!(kyc_reviews));
+
+    let auction_registrations = export_rows(
+        db,
+        entity::auction_registrations::Entity::find()
+            .filter(entity::auction_registrations::Column::UserId.eq(user.id))
+            .order_by_asc(entity::auction_registrations::Column::Id),
+        &[],
+    )
+    .await?;
+    data.insert("auction_registrations".to_string(), json!(auction_registrations));
+
+    let bidder_limits = export_rows(
+        db,
+        entity::bidder_limits::Entity::find()
+            .filter(entity::bidder_limits::Column::UserId.eq(user.id))
+            .order_by_asc(entity::bidder_limits::Column::Id),
+        &[],
+    )
+    .await?;
+    data.insert("deposits".to_string(), json!(bidder_limits));
+
+    let bids = export_rows(
+        db,
+        entity::bids::Entity::find()
+            .filter(entity::bids::Column::UserId.eq(user.id))
+            .order_by_asc(entity::bids::Column::Id),
+        &[],
+    )
+    .await?;
+    let bid_ids = bids.iter().filter_map(|bid| bid["id"].as_i64()).collect::<Vec<_>>();
+    data.insert("bids".to_string(), json!(bids));
+
+    let bid_audits = export_rows(
+        db,
+        entity::bid_audits::Entity::find()
+            .filter(Expr::col(entity::bid_audits::Column::BidId).is_in(bid_ids))
+            .order_by_asc(entity::bid_audits::Column::Id),
+        &[],
+    )
+    .await?;
+    data.insert("bid_withdrawals".to_string(), json!(bid_audits));
+
+    let bid_commitments = export_rows(
+        db,
+        entity::bid_commitments::Entity::find()
+            .filter(entity::bid_commitments::Column::UserId.eq(user.id))
+            .order_by_asc(entity::bid_commitments::Column::Id),
+        &[],
+    )
+    .await?;
+    data.insert("sealed_bid_commitments".to_string(), json!(bid_commitments));
+
+    let orders = export_rows(
+        db,
+        entity::orders::Entity::find()
+            .filter(entity::orders::Column::UserId.eq(user.id))
+            .order_by_asc(entity::orders::Column::Id),
+        &[],
+    )
+    .await?;
+    data.insert("orders".to_string(), json!(orders));
+
+    let trades = export_rows(
+        db,
+        entity::trades::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(entity::trades::Column::BuyerId.eq(user.id))
+                    .add(entity::trades::Column::SellerId.eq(user.id)),
+            )
+            .order_by_asc(entity::trades::Column::Id),
+        &[],
+    )
+    .await?;
+    data.insert("trades".to_string(), json!(trades));
+
+    let auction_results = export_rows(
+        db,
+        entity::auction_results::Entity::find()
+            .filter(entity::auction_results::Column::WinningUserId.eq(user.id))
+            .order_by_asc(entity::auction_results::Column::Id),
+        &[],
+    )
+    .await?;
+    data.insert("auction_results".to_string(), json!(auction_results));
+
+    let invoices = export_rows(
+        db,
+        entity::invoices::Entity::find()
+            .filter(entity::invoices::Column::UserId.eq(user.id))
+            .order_by_asc(entity::invoices::Column::Id),
+        &[],
+    )
+    .await?;
+    let invoice_ids = invoices.iter().filter_map(|invoice| invoice["id"].as_i64()).collect::<Vec<_>>();
+    data.insert("invoices".to_string(), json!(invoices));
+
+    let invoice_lines = export_rows(
+        db,
+        entity::invoice_lines::Entity::find()
+            .filter(Expr::col(entity::invoice_lines::Column::InvoiceId).is_in(invoice_ids))
+            .order_by_asc(entity::invoice_lines::Column::Id),
+        &[],
+    )
+    .await?;
+    data.insert("invoice_lines".to_string(), json!(invoice_lines));
+
+    let listings = export_rows(
+        db,
+        entity::listings::Entity::find()
+            .filter(entity::listings::Column::SellerId.eq(user.id))
+            .order_by_asc(entity::listings::Column::Id),
+        &[],
+    )
+    .await?;
+    data.insert("consigned_listings".to_string(), json!(listings));
+
+    let payouts = export_rows(
+        db,
+        entity::payouts::Entity::find()
+            .filter(entity::payouts::Column::SellerId.eq(user.id))
+            .order_by_asc(entity::payouts::Column::Id),
+        &[],
+    )
+    .await?;
+    data.insert("payouts".to_string(), json!(payouts));
+
+    let notifications = export_rows(
+        db,
+        entity::notifications::Entity::find()
+            .filter(entity::notifications::Column::UserId.eq(user.id))
+            .order_by_asc(entity::notifications::Column::Id),
+        &[],
+    )
+    .await?;
+    data.insert("notifications".to_string(), json!(notifications));
+
+    Ok((Value::Object(data), documents))
+}
+
+/// Packs an export into a ZIP archive: the data as `data.json` and each
+/// identity document under `documents/`, named by its id.
+pub async fn build_archive(data: Value, documents: Vec<entity::kyc_documents::Model>) -> Result<Vec<u8>, ApiResponse> {
+    let mut files = Vec::new();
+    for document in documents {
+        let extension = Path::new(&document.storage_path)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_string())
+            .unwrap_or_else(|| "bin".to_string());
+        let contents = read_document(document.storage_path.clone()).await?;
+        files.push((format!("documents/{}.{}", document.id, extension), contents));
+    }
+
+    let json = serde_json::to_vec_pretty(&data)
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    web::block(move || -> zip::result::ZipResult<Vec<u8>> {
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+
+        archive.start_file("data.json", options)?;
+        archive.write_all(&json)?;
+
+        for (name, contents) in files {
+            archive.start_file(name, options)?;
+            archive.write_all(&contents)?;
+        }
+
+        Ok(archive.finish()?.into_inner())
+    })
+    .await
+    .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+    .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))
+}
+
+/// Refuses to erase an account that still has business in progress, since
+/// the other side of it would be left with no one to deal with.
+async fn check_erasable(db: &DatabaseConnection, user_id: i32) -> Result<(), ApiResponse> {
+    let now = Utc::now().naive_utc();
+
+    let running = entity::bids::Entity::find()
+        .filter(entity::bids::Column::UserId.eq(user_id))
+        .filter(entity::bids::Column::DeletedAt.is_null())
+        .find_also_related(entity::listings::Entity)
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .into_iter()
+        .filter_map(|(_, listing)| listing.map(|listing| listing.auction_id))
+        .collect::<Vec<_>>();
+
+    let running_auction = entity::auctions::Entity::find()
+        .filter(Expr::col(entity::auctions::Column::Id).is_in(running))
+        .filter(entity::auctions::Column::DeletedAt.is_null())
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .iter()
+        .any(|auction| bidding_closes_at(auction) > now);
+
+    if running_auction {
+        return Err(ApiResponse::new(409, response(json!({
+            "error": "The account has bids in auctions that are still running"
+        }))));
+    }
+
+    let open_orders = entity::orders::Entity::find()
+        .filter(entity::orders::Column::UserId.eq(user_id))
+        .filter(entity::orders::Column::DeletedAt.is_null())
+        .filter(entity::orders::Column::Status.is_in([OrderStatus::Open, OrderStatus::PartiallyFilled]))
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    if open_orders.is_some() {
+        return Err(ApiResponse::new(409, response(json!({
+            "error": "The account has open orders. Cancel them first"
+        }))));
+    }
+
+    let unpaid_invoice = entity::invoices::Entity::find()
+        .filter(entity::invoices::Column::UserId.eq(user_id))
+        .filter(entity::invoices::Column::DeletedAt.is_null())
+        .filter(entity::invoices::Column::Status.eq(InvoiceStatus::Issued))
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    if unpaid_invoice.is_some() {
+        return Err(ApiResponse::new(409, response(json!({
+            "error": "The account has unpaid invoices"
+        }))));
+    }
+
+    let pending_payout = entity::payouts::Entity::find()
+        .filter(entity::payouts::Column::SellerId.eq(user_id))
+        .filter(entity::payouts::Column::DeletedAt.is_null())
+        .filter(entity::payouts::Column::Status.is_in([PayoutStatus::Pending, PayoutStatus::Approved, PayoutStatus::Sending]))
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    if pending_payout.is_some() {
+        return Err(ApiResponse::new(409, response(json!({
+            "error": "The account has payouts that have not been sent yet"
+        }))));
+    }
+
+    Ok(())
+}
+
+/// Erases a user by pseudonymising them. The account row stays, with its
+/// name and number replaced, so bids, results, invoices and payouts remain
+/// intact for audit. Contact details, identity documents, notifications and
+/// past numbers are removed, and the account can no longer sign in.
+pub async fn erase_user(
+    db: &DatabaseConnection,
+    user: entity::users::Model,
+) -> Result<entity::users::Model, ApiResponse> {
+    check_erasable(db, user.id).await?;
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let storage_paths = entity::kyc_documents::Entity::find()
+        .filter(entity::kyc_documents::Column::UserId.eq(user.id))
+        .all(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .into_iter()
+        .map(|document| document.storage_path)
+        .collect::<Vec<_>>();
+
+    entity::kyc_documents::Entity::delete_many()
+        .filter(entity::kyc_documents::Column::UserId.eq(user.id))
+        .exec(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    entity::kyc_reviews::Entity::delete_many()
+        .filter(entity::kyc_reviews::Column::UserId.eq(user.id))
+        .exec(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    entity::notifications::Entity::delete_many()
+        .filter(entity::notifications::Column::UserId.eq(user.id))
+        .exec(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    entity::phone_number_history::Entity::delete_many()
+        .filter(entity::phone_number_history::Column::UserId.eq(user.id))
+        .exec(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    entity::phone_number_changes::Entity::delete_many()
+        .filter(entity::phone_number_changes::Column::UserId.eq(user.id))
+        .exec(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let now = Utc::now().naive_utc();
+    let (user_id, phone_number) = (user.id, user.phone_number.clone());
+    let mut update_user: entity::users::ActiveModel = user.into();
+    update_user.name = Set(format!("Erased user {}", user_id));
+    update_user.phone_number = Set(format!("erased-{}", user_id));
+    update_user.email = Set(None);
+    update_user.national_id_number = Set(None);
+    update_user.county = Set(None);
+    update_user.location = Set(None);
+    update_user.notify_whatsapp = Set(false);
+    update_user.notify_email = Set(false);
+    update_user.quiet_hours_start = Set(None);
+    update_user.quiet_hours_end = Set(None);
+    update_user.marketing_consent = Set(false);
+    update_user.marketing_consent_at = Set(None);
+    update_user.erased_at = Set(Some(now));
+    update_user.deleted_at = Set(Some(now));
+    update_user.updated_at = Set(now);
+    let user = update_user
+        .update(&txn)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    txn.commit()
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    for storage_path in storage_paths {
+        match web::block(move || fs::remove_file(storage_path)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => eprintln!("Error removing identity document: {}", err),
+            Err(err) => eprintln!("Error removing identity document: {}", err),
+        }
+    }
+
+    send_to_number(
+        &phone_number,
+        "Your auction account has been erased. Your bids and payments are kept without your name for our records.",
+    )
+    .await;
+
+    Ok(user)
+}
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use migration::Expr;
+use sea_orm::{ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde_json::json;
+
+use crate::utils::{api_response::ApiResponse, json_response::response};
+
+/// Numbers bidders within each auction in the order they first bid, keyed by
+/// (auction id, user id). Withdrawn bids keep their place so a bidder's
+/// number never changes once given.
+pub async fn bidder_numbers<C: ConnectionTrait>(
+    db: &C,
+    auction_ids: Vec<i32>,
+) -> Result<HashMap<(i32, i32), i32>, ApiResponse> {
+    let first_bids = entity::bids::Entity::find()
+        .inner_join(entity::listings::Entity)
+        .filter(Expr::col((entity::listings::Entity, entity::listings::Column::AuctionId)).is_in(auction_ids))
+        .select_only()
+        .column(entity::listings::Column::AuctionId)
+        .column(entity::bids::Column::UserId)
+        .column_as(Expr::col((entity::bids::Entity, entity::bids::Column::Id)).min(), "first_bid_id")
+        .group_by(entity::listings::Column::AuctionId)
+        .group_by(entity::bids::Column::UserId)
+        .order_by_asc(entity::listings::Column::AuctionId)
+        .order_by_asc(Expr::col((entity::bids::Entity, entity::bids::Column::Id)).min())
+        .into_tuple::<(i32, i32, i32)>()
+        .all(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let mut numbers = HashMap::new();
+    let mut next: HashMap<i32, i32> = HashMap::new();
+    for (auction_id, user_id, _) in first_bids {
+        let number = next.entry(auction_id).or_insert(0);
+        *number += 1;
+        numbers.insert((auction_id, user_id), *number);
+    }
+
+    Ok(numbers)
+}
+
+/// The name a bidder goes by in an auction's public bid history.
+pub fn bidder_label(numbers: &HashMap<(i32, i32), i32>, auction_id: i32, user_id: i32) -> String {
+    match numbers.get(&(auction_id, user_id)) {
+        Some(number) => format!("Bidder {}", number),
+        None => "Bidder".to_string(),
+    }
+}
@@ -0,0 +1,60 @@
+use entity::sea_orm_active_enums::RegistrationStatus;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::utils::{api_response::ApiResponse, json_response::response};
+
+/// The hex SHA-256 of an auction's terms, kept with each registration as a
+/// record of exactly which terms the bidder accepted.
+pub fn terms_hash(auction: &entity::auctions::Model) -> Option<String> {
+    auction.terms.as_ref().map(|terms| hex::encode(Sha256::digest(terms.as_bytes())))
+}
+
+pub fn registration_json(registration: &entity::auction_registrations::Model) -> Value {
+    json!({
+        "id": registration.id,
+        "auction_id": registration.auction_id,
+        "user_id": registration.user_id,
+        "status": registration.status,
+        "terms_hash": registration.terms_hash,
+        "terms_accepted_at": registration.terms_accepted_at,
+        "reviewed_by_id": registration.reviewed_by_id,
+        "reviewed_at": registration.reviewed_at,
+        "review_note": registration.review_note,
+        "created_at": registration.created_at,
+    })
+}
+
+/// Rejects anyone an admin has not approved to bid in a registration-required
+/// auction. Open auctions take bids from everyone.
+pub async fn check_registered<C: ConnectionTrait>(
+    db: &C,
+    auction: &entity::auctions::Model,
+    user_id: i32,
+) -> Result<(), ApiResponse> {
+    if !auction.requires_registration {
+        return Ok(());
+    }
+
+    let registration = entity::auction_registrations::Entity::find()
+        .filter(entity::auction_registrations::Column::DeletedAt.is_null())
+        .filter(entity::auction_registrations::Column::AuctionId.eq(auction.id))
+        .filter(entity::auction_registrations::Column::UserId.eq(user_id))
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    match registration.map(|registration| registration.status) {
+        Some(RegistrationStatus::Approved) => Ok(()),
+        Some(RegistrationStatus::Pending) => Err(ApiResponse::new(403, response(json!({
+            "error": "Your registration for this auction is awaiting approval"
+        })))),
+        Some(RegistrationStatus::Rejected) => Err(ApiResponse::new(403, response(json!({
+            "error": "Your registration for this auction was rejected"
+        })))),
+        None => Err(ApiResponse::new(403, response(json!({
+            "error": "You must register for this auction before you can bid"
+        })))),
+    }
+}
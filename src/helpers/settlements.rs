@@ -0,0 +1,220 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, FromQueryResult, QueryFilter, QueryOrder, QuerySelect};
+use serde_json::json;
+
+use crate::{
+    helpers::fees::{fee_lines_json, fees_for_results, FeeTotals},
+    utils::{api_response::ApiResponse, json_response::response, pdf::{render_text_pdf, truncate}},
+};
+
+#[derive(Debug, FromQueryResult)]
+struct SoldLotResult {
+    id: i32,
+    auction_id: i32,
+    listing_id: i32,
+    title: String,
+    amount: Decimal,
+    created_at: NaiveDateTime,
+}
+
+/// A sold lot as it appears on a consignor's statement.
+pub struct StatementLine {
+    pub auction_result_id: i32,
+    pub auction_id: i32,
+    pub listing_id: i32,
+    pub title: String,
+    pub hammer_price: Decimal,
+    pub fees: Vec<entity::auction_result_fees::Model>,
+    pub totals: FeeTotals,
+    pub sold_at: NaiveDateTime,
+}
+
+impl StatementLine {
+    pub fn net(&self) -> Decimal {
+        self.totals.seller_net(self.hammer_price)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let seller_fees = self
+            .fees
+            .iter()
+            .filter(|line| line.party == entity::sea_orm_active_enums::FeeParty::Seller)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        json!({
+            "auction_result_id": self.auction_result_id,
+            "auction_id": self.auction_id,
+            "listing_id": self.listing_id,
+            "title": self.title,
+            "hammer_price": self.hammer_price,
+            "fees": fee_lines_json(&seller_fees),
+            "commission": self.totals.seller_fees,
+            "tax": self.totals.seller_tax,
+            "net": self.net(),
+            "sold_at": self.sold_at,
+        })
+    }
+}
+
+/// Hammer price less commission and other seller-side charges for each lot
+/// the consignor sold, optionally limited to one auction or to a set of
+/// results.
+pub struct SellerStatement {
+    pub seller_id: i32,
+    pub lines: Vec<StatementLine>,
+}
+
+impl SellerStatement {
+    pub async fn load<C: ConnectionTrait>(
+        db: &C,
+        seller_id: i32,
+        auction_id: Option<i32>,
+        auction_result_ids: Option<Vec<i32>>,
+    ) -> Result<Self, ApiResponse> {
+        let mut query = entity::auction_results::Entity::find()
+            .filter(entity::auction_results::Column::DeletedAt.is_null())
+            .filter(entity::listings::Column::SellerId.eq(seller_id))
+            .inner_join(entity::listings::Entity);
+
+        if let Some(auction_id) = auction_id {
+            query = query.filter(entity::listings::Column::AuctionId.eq(auction_id));
+        }
+
+        if let Some(auction_result_ids) = auction_result_ids {
+            query = query.filter(entity::auction_results::Column::Id.is_in(auction_result_ids));
+        }
+
+        let sold_lots = query
+            .order_by_asc(entity::auction_results::Column::Id)
+            .select_only()
+            .column(entity::auction_results::Column::Id)
+            .column(entity::listings::Column::AuctionId)
+            .column(entity::auction_results::Column::ListingId)
+            .column(entity::listings::Column::Title)
+            .column_as(entity::auction_results::Column::HammerPrice, "amount")
+            .column(entity::auction_results::Column::CreatedAt)
+            .into_model::<SoldLotResult>()
+            .all(db)
+            .await
+            .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+        let mut fees_map = fees_for_results(db, sold_lots.iter().map(|lot| lot.id).collect()).await?;
+
+        let lines = sold_lots
+            .into_iter()
+            .map(|lot| {
+                let fees = fees_map.remove(&lot.id).unwrap_or_default();
+                let totals = FeeTotals::from_models(&fees);
+
+                StatementLine {
+                    auction_result_id: lot.id,
+                    auction_id: lot.auction_id,
+                    listing_id: lot.listing_id,
+                    title: lot.title,
+                    hammer_price: lot.amount,
+                    fees,
+                    totals,
+                    sold_at: lot.created_at,
+                }
+            })
+            .collect();
+
+        Ok(SellerStatement { seller_id, lines })
+    }
+
+    pub fn hammer_total(&self) -> Decimal {
+        self.lines.iter().map(|line| line.hammer_price).sum()
+    }
+
+    pub fn commission_total(&self) -> Decimal {
+        self.lines.iter().map(|line| line.totals.seller_fees).sum()
+    }
+
+    pub fn tax_total(&self) -> Decimal {
+        self.lines.iter().map(|line| line.totals.seller_tax).sum()
+    }
+
+    pub fn net_total(&self) -> Decimal {
+        self.lines.iter().map(StatementLine::net).sum()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "seller_id": self.seller_id,
+            "lots": self.lines.iter().map(StatementLine::to_json).collect::<Vec<_>>(),
+            "totals": {
+                "hammer_total": self.hammer_total(),
+                "commission": self.commission_total(),
+                "tax": self.tax_total(),
+                "net": self.net_total(),
+            },
+        })
+    }
+
+    pub fn render_csv(&self) -> String {
+        let mut rows = vec![
+            "auction_id,listing_id,lot,sold_at,hammer_price,commission,tax,net".to_string(),
+        ];
+
+        for line in &self.lines {
+            rows.push(format!(
+                "{},{},{},{},{},{},{},{}",
+                line.auction_id,
+                line.listing_id,
+                csv_field(&line.title),
+                line.sold_at.format("%Y-%m-%d %H:%M:%S"),
+                line.hammer_price,
+                line.totals.seller_fees,
+                line.totals.seller_tax,
+                line.net()
+            ));
+        }
+
+        rows.push(format!(
+            ",,Totals,,{},{},{},{}",
+            self.hammer_total(), self.commission_total(), self.tax_total(), self.net_total()
+        ));
+
+        rows.join("\r\n") + "\r\n"
+    }
+
+    /// Renders the statement under the given heading lines.
+    pub fn render_pdf(&self, title: &str, heading: Vec<String>) -> Result<Vec<u8>, ApiResponse> {
+        let mut lines = heading;
+        lines.push(String::new());
+        lines.push(format!("{:<38}{:>14}{:>12}{:>10}{:>14}", "Lot", "Hammer", "Commission", "Tax", "Net"));
+        lines.push("-".repeat(88));
+
+        for line in &self.lines {
+            lines.push(format!(
+                "{:<38}{:>14}{:>12}{:>10}{:>14}",
+                truncate(&line.title, 37), line.hammer_price, line.totals.seller_fees, line.totals.seller_tax, line.net()
+            ));
+        }
+
+        lines.push("-".repeat(88));
+        lines.push(format!(
+            "{:<38}{:>14}{:>12}{:>10}{:>14}",
+            "Totals", self.hammer_total(), self.commission_total(), self.tax_total(), self.net_total()
+        ));
+
+        render_text_pdf(title, &lines)
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    // Spreadsheets would run a lot title starting with one of these as a formula
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
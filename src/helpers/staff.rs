@@ -0,0 +1,43 @@
+use actix_session::Session;
+use entity::sea_orm_active_enums::UserRole;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter};
+use serde_json::json;
+
+use crate::utils::{api_response::ApiResponse, json_response::response, session::session_user_id};
+
+/// Loads the signed-in user and refuses anyone who is not staff. Admins count
+/// as staff.
+pub async fn require_staff<C: ConnectionTrait>(db: &C, session: &Session) -> Result<entity::users::Model, ApiResponse> {
+    let user_id = session_user_id(session)?;
+
+    entity::users::Entity::find_by_id(user_id)
+        .filter(entity::users::Column::DeletedAt.is_null())
+        .filter(entity::users::Column::Role.is_in([UserRole::Staff, UserRole::Admin]))
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .ok_or_else(|| ApiResponse::new(403, response(json!({ "error": "Only staff can do this" }))))
+}
+
+/// Loads the signed-in user and refuses anyone who is not an admin.
+pub async fn require_admin<C: ConnectionTrait>(db: &C, session: &Session) -> Result<entity::users::Model, ApiResponse> {
+    let user_id = session_user_id(session)?;
+
+    entity::users::Entity::find_by_id(user_id)
+        .filter(entity::users::Column::DeletedAt.is_null())
+        .filter(entity::users::Column::Role.eq(UserRole::Admin))
+        .one(db)
+        .await
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .ok_or_else(|| ApiResponse::new(403, response(json!({ "error": "Only admins can do this" }))))
+}
+
+/// Lets the signed-in user through to their own records and admins through
+/// to anyone's.
+pub async fn require_user_or_admin<C: ConnectionTrait>(db: &C, session: &Session, user_id: i32) -> Result<(), ApiResponse> {
+    if session_user_id(session)? == user_id {
+        return Ok(());
+    }
+
+    require_admin(db, session).await.map(|_| ())
+}
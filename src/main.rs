@@ -3,8 +3,9 @@ use actix_cors::Cors;
 use actix_session::{config::PersistentSession, SessionMiddleware, storage::CookieSessionStore};
 use actix_web::{cookie::{Key, SameSite}, middleware::Logger, web, App, HttpServer};
 use controllers::auction_result::create_auction_result;
+use helpers::{dutch::run_dutch_clock, notifications::run_notification_dispatcher, phone_changes::run_phone_change_completion};
 use sea_orm::{Database, DatabaseConnection};
-use utils::app_state::AppState;
+use utils::{app_state::AppState, live_feed::LiveFeed};
 
 mod utils;
 mod routes;
@@ -46,6 +47,30 @@ fn spawn_auction_task(db: DatabaseConnection) {
     });
 }
 
+fn spawn_dutch_clock_task(db: DatabaseConnection, live_feed: LiveFeed) {
+    actix_rt::spawn(async move {
+        if let Err(e) = run_dutch_clock(db, live_feed).await {
+            eprintln!("Dutch clock task failed: {}", e);
+        }
+    });
+}
+
+fn spawn_notification_task(db: DatabaseConnection) {
+    actix_rt::spawn(async move {
+        if let Err(e) = run_notification_dispatcher(db).await {
+            eprintln!("Notification task failed: {}", e);
+        }
+    });
+}
+
+fn spawn_phone_change_task(db: DatabaseConnection) {
+    actix_rt::spawn(async move {
+        if let Err(e) = run_phone_change_completion(db).await {
+            eprintln!("Phone change task failed: {}", e);
+        }
+    });
+}
+
 #[actix_web::main] // or #[tokio::main]
 async fn main() -> Result<(), MainError>{
     if std::env::var_os("RUST_LOG").is_none() {
@@ -55,23 +80,29 @@ async fn main() -> Result<(), MainError>{
     dotenv::dotenv().ok();
     env_logger::init();
 
-    let port = (utils::constants::PORT).clone();
+    let port = *(utils::constants::PORT);
     let address = (utils::constants::ADDRESS).clone();
     let database_url = (utils::constants::DATABASE_URL).clone();
-    let fronted_url = (utils::constants::FRONTEND_URL).clone();
-    let session_expiration_time = (utils::constants::SESSION_EXPIRATION_TIME).clone();
+    let _fronted_url = (utils::constants::FRONTEND_URL).clone();
+    let session_expiration_time = *(utils::constants::SESSION_EXPIRATION_TIME);
+    let session_key = Key::derive_from((utils::constants::SESSION_KEY).as_bytes());
     
     let db: DatabaseConnection = Database::connect(database_url)
         .await
         .map_err(|err| MainError { message: err.to_string() })?;
 
+    let live_feed = LiveFeed::new();
+
     spawn_auction_task(db.clone());
+    spawn_dutch_clock_task(db.clone(), live_feed.clone());
+    spawn_notification_task(db.clone());
+    spawn_phone_change_task(db.clone());
 
     HttpServer::new( move || {
         App::new()
-            .app_data(web::Data::new( AppState { db: db.clone() } ))
+            .app_data(web::Data::new( AppState { db: db.clone(), live_feed: live_feed.clone() } ))
             .wrap(
-                SessionMiddleware::builder(CookieSessionStore::default(), Key::from(&[0; 64]))
+                SessionMiddleware::builder(CookieSessionStore::default(), session_key.clone())
                 .cookie_secure(false)
                 // .cookie_http_only(true)
                 .cookie_same_site(SameSite::Lax)
@@ -13,34 +13,147 @@ pub fn config(config: &mut web::ServiceConfig) {
             .service(controllers::listings::get_all_auction_listings)
             .service(controllers::listings::get_all_listings)
             .service(controllers::listings::delete_listing)
+            .service(controllers::listings::buy_now)
             // End: API's for listings
 
             // Start: API's for users
             .service(controllers::user::create_user)
+            .service(controllers::user::verify_user)
             .service(controllers::user::get_all_users)
             .service(controllers::user::get_user)
+            .service(controllers::user::update_user_role)
             .service(controllers::user::delete_user)
+            .service(controllers::user::get_profile)
+            .service(controllers::user::update_profile)
+            .service(controllers::user::logout)
             // End: API's for users
 
+            // Start: API's for phone number changes
+            .service(controllers::phone_changes::request_phone_change)
+            .service(controllers::phone_changes::verify_phone_change)
+            .service(controllers::phone_changes::cancel_phone_change)
+            .service(controllers::phone_changes::get_phone_change)
+            .service(controllers::phone_changes::get_phone_history)
+            // End: API's for phone number changes
+
+            // Start: API's for personal data
+            .service(controllers::privacy::request_data_code)
+            .service(controllers::privacy::export_user_data)
+            .service(controllers::privacy::erase_account)
+            // End: API's for personal data
+
             // Start: API's for auctions
             .service(controllers::auction::create_auction)
             .service(controllers::auction::update_auction)
             .service(controllers::auction::get_all_auctions)
             .service(controllers::auction::get_user_auctions)
+            .service(controllers::auction::get_auction_candle)
             .service(controllers::auction::delete_auction)
             // End: API's for auctions
 
             // Start: API's for auction results
             .service(controllers::auction_result::get_auction_results)
+            .service(controllers::auction_result::get_auction_settlement)
+            .service(controllers::auction_result::get_auction_result_invoice)
             // End: API's for auction results
 
+            // Start: API's for fee rules
+            .service(controllers::fees::create_fee_rule)
+            .service(controllers::fees::update_fee_rule)
+            .service(controllers::fees::get_auction_fee_rules)
+            .service(controllers::fees::delete_fee_rule)
+            // End: API's for fee rules
+
+            // Start: API's for invoices
+            .service(controllers::invoices::generate_auction_invoices)
+            .service(controllers::invoices::get_auction_invoices)
+            .service(controllers::invoices::get_user_invoices)
+            .service(controllers::invoices::update_invoice_status)
+            // End: API's for invoices
+
+            // Start: API's for wallets and the ledger
+            .service(controllers::ledger::deposit_to_wallet)
+            .service(controllers::ledger::refund_from_wallet)
+            .service(controllers::ledger::get_wallet)
+            .service(controllers::ledger::get_trial_balance)
+            // End: API's for wallets and the ledger
+
+            // Start: API's for bidder deposits and credit limits
+            .service(controllers::bidder_limits::lodge_deposit)
+            .service(controllers::bidder_limits::approve_credit_limit)
+            .service(controllers::bidder_limits::get_auction_bidder_limits)
+            .service(controllers::bidder_limits::get_user_bidder_limit)
+            // End: API's for bidder deposits and credit limits
+
+            // Start: API's for auction registrations
+            .service(controllers::registrations::register_for_auction)
+            .service(controllers::registrations::review_registration)
+            .service(controllers::registrations::get_auction_registrations)
+            .service(controllers::registrations::get_user_registration)
+            // End: API's for auction registrations
+
+            // Start: API's for identity verification
+            .service(controllers::kyc::upload_kyc_document)
+            .service(controllers::kyc::submit_kyc)
+            .service(controllers::kyc::review_kyc)
+            .service(controllers::kyc::get_kyc_status)
+            .service(controllers::kyc::get_kyc_queue)
+            .service(controllers::kyc::get_kyc_review)
+            .service(controllers::kyc::get_kyc_document_file)
+            // End: API's for identity verification
+
+            // Start: API's for notifications
+            .service(controllers::notifications::get_user_notifications)
+            .service(controllers::notifications::announce_auction)
+            // End: API's for notifications
+
+            // Start: API's for sellers
+            .service(controllers::sellers::create_seller_listing)
+            .service(controllers::sellers::update_seller_listing)
+            .service(controllers::sellers::delete_seller_listing)
+            .service(controllers::sellers::get_seller_listings)
+            .service(controllers::sellers::get_seller_results)
+            .service(controllers::sellers::get_seller_statement)
+            // End: API's for sellers
+
+            // Start: API's for payouts
+            .service(controllers::payouts::create_batch)
+            .service(controllers::payouts::get_all_batches)
+            .service(controllers::payouts::get_batch)
+            .service(controllers::payouts::approve_batch)
+            .service(controllers::payouts::send_batch)
+            .service(controllers::payouts::get_payout_statement)
+            // End: API's for payouts
+
             // Start: API's for bids
             .service(controllers::bids::create_bid)
+            .service(controllers::bids::create_staff_bid)
+            .service(controllers::bids::retract_bid)
+            .service(controllers::bids::void_bid)
+            .service(controllers::bids::get_listing_bid_audits)
+            .service(controllers::bids::commit_bid)
+            .service(controllers::bids::reveal_bid)
+            .service(controllers::bids::verify_bid_receipt)
+            .service(controllers::bids::get_receipt_key)
+            .service(controllers::bids::accept_price)
             .service(controllers::bids::get_all_bids)
             .service(controllers::bids::get_active_bids)
             .service(controllers::bids::get_all_user_bids)
             .service(controllers::bids::get_active_listings_bids)
             .service(controllers::bids::get_all_listings_bids)
             // End: API's for bids
+
+            // Start: API's for the order book
+            .service(controllers::orders::create_order)
+            .service(controllers::orders::cancel_user_order)
+            .service(controllers::orders::get_user_orders)
+            .service(controllers::orders::get_market_depth)
+            .service(controllers::orders::get_market_trades)
+            // End: API's for the order book
+
+            // Start: API's for live feeds
+            .service(controllers::live::auction_feed)
+            .service(controllers::live::auctioneer_console)
+            // End: API's for live feeds
         );
 }
\ No newline at end of file
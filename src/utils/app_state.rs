@@ -1,5 +1,8 @@
 use sea_orm::DatabaseConnection;
 
+use crate::utils::live_feed::LiveFeed;
+
 pub struct AppState {
-    pub db: DatabaseConnection
-}
\ No newline at end of file
+    pub db: DatabaseConnection,
+    pub live_feed: LiveFeed,
+}
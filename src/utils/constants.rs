@@ -1,6 +1,10 @@
 use std::env;
 
 use lazy_static::lazy_static;
+use phonenumber::country;
+use rust_decimal::Decimal;
+
+use super::phone::parse_countries;
 
 lazy_static!(
     pub static ref ADDRESS: String = set_address();
@@ -9,10 +13,28 @@ lazy_static!(
     pub static ref SECRET: String = set_secret();
     pub static ref FRONTEND_URL: String = frontend_url();
     pub static ref SESSION_EXPIRATION_TIME: i64 = session_expiration_time();
+    pub static ref SESSION_KEY: String = session_key();
     pub static ref WHATSAPP_ACCESS_TOKEN: String = whatsapp_access_token();
     pub static ref WHATSAPP_PHONE_NUMBER_ID: String = whatsapp_phone_number_id();
     pub static ref WHATSAPP_BUSINESS_ACCOUNT_ID: String = whatsapp_business_account_id();
     pub static ref WHATSAPP_PHONE_NUMBER: String = whatsapp_phone_number();
+    pub static ref DEPOSIT_BIDDING_MULTIPLIER: Decimal = deposit_bidding_multiplier();
+    pub static ref PAYOUT_PROVIDER: String = payout_provider();
+    pub static ref RECEIPT_SIGNING_KEY: String = receipt_signing_key();
+    pub static ref BID_RETRACTION_WINDOW_SECONDS: i64 = bid_retraction_window_seconds();
+    pub static ref BID_RETRACTION_CLOSE_LOCKOUT_SECONDS: i64 = bid_retraction_close_lockout_seconds();
+    pub static ref KYC_DOCUMENT_DIR: String = kyc_document_dir();
+    pub static ref KYC_MAX_DOCUMENT_BYTES: usize = kyc_max_document_bytes();
+    pub static ref SMTP_HOST: Option<String> = smtp_host();
+    pub static ref SMTP_USERNAME: String = smtp_username();
+    pub static ref SMTP_PASSWORD: String = smtp_password();
+    pub static ref EMAIL_FROM: String = email_from();
+    pub static ref PHONE_CHANGE_CODE_TTL_MINUTES: i64 = phone_change_code_ttl_minutes();
+    pub static ref PHONE_CHANGE_COOLING_OFF_HOURS: i64 = phone_change_cooling_off_hours();
+    pub static ref PHONE_CHANGE_UNCONFIRMED_COOLING_OFF_HOURS: i64 = phone_change_unconfirmed_cooling_off_hours();
+    pub static ref PHONE_COUNTRIES: Vec<country::Id> = phone_countries();
+    pub static ref PHONE_DEFAULT_COUNTRY: country::Id = phone_default_country();
+    pub static ref ONE_TIME_CODE_TTL_MINUTES: i64 = one_time_code_ttl_minutes();
 );
 
 fn set_address() -> String {
@@ -55,6 +77,21 @@ fn session_expiration_time() -> i64 {
     .expect("Failed to parse 'SESSION_EXPIRATION_TIME' as a valid i64 value.")
 }
 
+/// Master key the session cookie is signed and encrypted with. It must be
+/// random and at least 32 bytes long, since anyone holding it can forge a
+/// session for any user.
+fn session_key() -> String {
+    dotenv::dotenv().ok();
+    let key = env::var("SESSION_KEY")
+        .expect("Environment variable 'SESSION_KEY' is required but not set.");
+
+    if key.len() < 32 {
+        panic!("Environment variable 'SESSION_KEY' must be at least 32 bytes long.");
+    }
+
+    key
+}
+
 fn whatsapp_access_token() -> String {
     dotenv::dotenv().ok();
     env::var("WHATSAPP_ACCESS_TOKEN")
@@ -67,6 +104,7 @@ fn whatsapp_phone_number_id() -> String {
         .expect("Environment variable 'WHATSAPP_PHONE_NUMBER_ID' is required but not set.")
 }
 
+#[allow(dead_code)]
 fn whatsapp_business_account_id() -> String {
     dotenv::dotenv().ok();
     env::var("WHATSAPP_BUSINESS_ACCOUNT_ID")
@@ -77,4 +115,125 @@ fn whatsapp_phone_number() -> String {
     dotenv::dotenv().ok();
     env::var("WHATSAPP_PHONE_NUMBER")
         .expect("Environment variable 'WHATSAPP_PHONE_NUMBER' is required but not set.")
+}
+
+fn deposit_bidding_multiplier() -> Decimal {
+    dotenv::dotenv().ok();
+    env::var("DEPOSIT_BIDDING_MULTIPLIER")
+        .unwrap_or_else(|_| "10".to_string())
+        .parse::<Decimal>()
+        .expect("Failed to parse 'DEPOSIT_BIDDING_MULTIPLIER' as a valid decimal value.")
+}
+
+fn payout_provider() -> String {
+    dotenv::dotenv().ok();
+    env::var("PAYOUT_PROVIDER").unwrap_or_else(|_| "mock".to_string())
+}
+
+fn receipt_signing_key() -> String {
+    dotenv::dotenv().ok();
+    env::var("RECEIPT_SIGNING_KEY").unwrap_or_else(|_| set_secret())
+}
+
+fn bid_retraction_window_seconds() -> i64 {
+    dotenv::dotenv().ok();
+    env::var("BID_RETRACTION_WINDOW_SECONDS")
+        .unwrap_or_else(|_| "60".to_string())
+        .parse::<i64>()
+        .expect("Failed to parse 'BID_RETRACTION_WINDOW_SECONDS' as a valid i64 value.")
+}
+
+fn bid_retraction_close_lockout_seconds() -> i64 {
+    dotenv::dotenv().ok();
+    env::var("BID_RETRACTION_CLOSE_LOCKOUT_SECONDS")
+        .unwrap_or_else(|_| "300".to_string())
+        .parse::<i64>()
+        .expect("Failed to parse 'BID_RETRACTION_CLOSE_LOCKOUT_SECONDS' as a valid i64 value.")
+}
+
+fn kyc_document_dir() -> String {
+    dotenv::dotenv().ok();
+    env::var("KYC_DOCUMENT_DIR").unwrap_or_else(|_| "uploads/kyc".to_string())
+}
+
+fn kyc_max_document_bytes() -> usize {
+    dotenv::dotenv().ok();
+    env::var("KYC_MAX_DOCUMENT_BYTES")
+        .unwrap_or_else(|_| "5242880".to_string())
+        .parse::<usize>()
+        .expect("Failed to parse 'KYC_MAX_DOCUMENT_BYTES' as a valid usize value.")
+}
+
+/// Email notifications are only sent when an SMTP relay is configured.
+fn smtp_host() -> Option<String> {
+    dotenv::dotenv().ok();
+    env::var("SMTP_HOST").ok().filter(|host| !host.is_empty())
+}
+
+fn smtp_username() -> String {
+    dotenv::dotenv().ok();
+    env::var("SMTP_USERNAME").unwrap_or_default()
+}
+
+fn smtp_password() -> String {
+    dotenv::dotenv().ok();
+    env::var("SMTP_PASSWORD").unwrap_or_default()
+}
+
+fn email_from() -> String {
+    dotenv::dotenv().ok();
+    env::var("EMAIL_FROM").unwrap_or_else(|_| "Auctions <no-reply@localhost>".to_string())
+}
+
+fn phone_change_code_ttl_minutes() -> i64 {
+    dotenv::dotenv().ok();
+    env::var("PHONE_CHANGE_CODE_TTL_MINUTES")
+        .unwrap_or_else(|_| "10".to_string())
+        .parse::<i64>()
+        .expect("Failed to parse 'PHONE_CHANGE_CODE_TTL_MINUTES' as a valid i64 value.")
+}
+
+/// How long a confirmed number change waits before it takes effect, giving
+/// the holder of the old number time to cancel it.
+fn phone_change_cooling_off_hours() -> i64 {
+    dotenv::dotenv().ok();
+    env::var("PHONE_CHANGE_COOLING_OFF_HOURS")
+        .unwrap_or_else(|_| "24".to_string())
+        .parse::<i64>()
+        .expect("Failed to parse 'PHONE_CHANGE_COOLING_OFF_HOURS' as a valid i64 value.")
+}
+
+/// The longer wait used when the old number could not confirm the change.
+fn phone_change_unconfirmed_cooling_off_hours() -> i64 {
+    dotenv::dotenv().ok();
+    env::var("PHONE_CHANGE_UNCONFIRMED_COOLING_OFF_HOURS")
+        .unwrap_or_else(|_| "168".to_string())
+        .parse::<i64>()
+        .expect("Failed to parse 'PHONE_CHANGE_UNCONFIRMED_COOLING_OFF_HOURS' as a valid i64 value.")
+}
+
+/// Countries whose mobile numbers can hold an account.
+fn phone_countries() -> Vec<country::Id> {
+    dotenv::dotenv().ok();
+    parse_countries(&env::var("PHONE_COUNTRIES").unwrap_or_else(|_| "KE,UG,TZ".to_string()))
+        .expect("Failed to parse 'PHONE_COUNTRIES' as a list of country codes.")
+}
+
+/// The country a number written without its country code belongs to.
+fn phone_default_country() -> country::Id {
+    dotenv::dotenv().ok();
+    env::var("PHONE_DEFAULT_COUNTRY")
+        .unwrap_or_else(|_| "KE".to_string())
+        .to_uppercase()
+        .parse::<country::Id>()
+        .expect("Failed to parse 'PHONE_DEFAULT_COUNTRY' as a country code.")
+}
+
+/// How long a one-time code sent to a number stays valid.
+fn one_time_code_ttl_minutes() -> i64 {
+    dotenv::dotenv().ok();
+    env::var("ONE_TIME_CODE_TTL_MINUTES")
+        .unwrap_or_else(|_| "10".to_string())
+        .parse::<i64>()
+        .expect("Failed to parse 'ONE_TIME_CODE_TTL_MINUTES' as a valid i64 value.")
 }
\ No newline at end of file
@@ -0,0 +1,161 @@
+use std::env;
+
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+
+use super::{api_response::ApiResponse, constants, json_response::response, phone::msisdn};
+
+/// A payment to a single recipient's mobile money account.
+pub struct DisbursementRequest {
+    /// Our reference, unique per payout, so retries can be recognised.
+    pub reference: String,
+    pub phone_number: String,
+    pub amount: Decimal,
+    pub remarks: String,
+}
+
+pub struct DisbursementReceipt {
+    pub provider_reference: String,
+}
+
+/// Sends money out to consignors. The provider is picked with the
+/// `PAYOUT_PROVIDER` environment variable.
+#[async_trait]
+pub trait DisbursementProvider: Send + Sync {
+    async fn disburse(&self, request: &DisbursementRequest) -> Result<DisbursementReceipt, String>;
+}
+
+pub fn disbursement_provider() -> Result<Box<dyn DisbursementProvider>, ApiResponse> {
+    match constants::PAYOUT_PROVIDER.as_str() {
+        "mock" => Ok(Box::new(MockDisbursementProvider)),
+        "mpesa" => Ok(Box::new(MpesaB2cProvider::from_env()?)),
+        provider => Err(ApiResponse::new(500, response(json!({
+            "error": format!("Unknown payout provider '{}'", provider)
+        })))),
+    }
+}
+
+/// Accepts every payout without moving any money, for local development.
+/// Recipients without a phone number are rejected so failures can be tried out.
+pub struct MockDisbursementProvider;
+
+#[async_trait]
+impl DisbursementProvider for MockDisbursementProvider {
+    async fn disburse(&self, request: &DisbursementRequest) -> Result<DisbursementReceipt, String> {
+        if request.phone_number.trim().is_empty() {
+            return Err("Recipient has no phone number".to_string());
+        }
+
+        Ok(DisbursementReceipt {
+            provider_reference: format!("MOCK-{}", request.reference),
+        })
+    }
+}
+
+/// M-Pesa Daraja business-to-customer payments. A successful response only
+/// means Safaricom accepted the request for processing.
+pub struct MpesaB2cProvider {
+    base_url: String,
+    consumer_key: String,
+    consumer_secret: String,
+    initiator_name: String,
+    security_credential: String,
+    short_code: String,
+    result_url: String,
+    timeout_url: String,
+}
+
+impl MpesaB2cProvider {
+    pub fn from_env() -> Result<Self, ApiResponse> {
+        dotenv::dotenv().ok();
+
+        let var = |name: &str| {
+            env::var(name).map_err(|_| ApiResponse::new(500, response(json!({
+                "error": format!("Environment variable '{}' is required for M-Pesa payouts but not set.", name)
+            }))))
+        };
+
+        Ok(MpesaB2cProvider {
+            base_url: env::var("MPESA_BASE_URL").unwrap_or_else(|_| "https://sandbox.safaricom.co.ke".to_string()),
+            consumer_key: var("MPESA_CONSUMER_KEY")?,
+            consumer_secret: var("MPESA_CONSUMER_SECRET")?,
+            initiator_name: var("MPESA_INITIATOR_NAME")?,
+            security_credential: var("MPESA_SECURITY_CREDENTIAL")?,
+            short_code: var("MPESA_SHORT_CODE")?,
+            result_url: var("MPESA_RESULT_URL")?,
+            timeout_url: var("MPESA_TIMEOUT_URL")?,
+        })
+    }
+
+    async fn access_token(&self, client: &reqwest::Client) -> Result<String, String> {
+        let body: Value = client
+            .get(format!("{}/oauth/v1/generate?grant_type=client_credentials", self.base_url))
+            .basic_auth(&self.consumer_key, Some(&self.consumer_secret))
+            .send()
+            .await
+            .map_err(|err| err.to_string())?
+            .json()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        body["access_token"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "M-Pesa did not return an access token".to_string())
+    }
+}
+
+#[async_trait]
+impl DisbursementProvider for MpesaB2cProvider {
+    async fn disburse(&self, request: &DisbursementRequest) -> Result<DisbursementReceipt, String> {
+        // M-Pesa only moves whole shillings, which payouts are created in
+        if request.amount.fract() != Decimal::ZERO {
+            return Err(format!("M-Pesa payouts must be whole shillings, got {}", request.amount));
+        }
+
+        let client = reqwest::Client::new();
+        let token = self.access_token(&client).await?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        let body = json!({
+            "OriginatorConversationID": request.reference,
+            "InitiatorName": self.initiator_name,
+            "SecurityCredential": self.security_credential,
+            "CommandID": "BusinessPayment",
+            "Amount": request.amount.trunc().to_string(),
+            "PartyA": self.short_code,
+            "PartyB": msisdn(&request.phone_number)?,
+            "Remarks": request.remarks,
+            "QueueTimeOutURL": self.timeout_url,
+            "ResultURL": self.result_url,
+            "Occasion": request.reference,
+        });
+
+        let body: Value = client
+            .post(format!("{}/mpesa/b2c/v3/paymentrequest", self.base_url))
+            .bearer_auth(token)
+            .headers(headers)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|err| err.to_string())?
+            .json()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        match (body["ResponseCode"].as_str(), body["ConversationID"].as_str()) {
+            (Some("0"), Some(conversation_id)) => Ok(DisbursementReceipt {
+                provider_reference: conversation_id.to_string(),
+            }),
+            _ => Err(body["errorMessage"]
+                .as_str()
+                .or(body["ResponseDescription"].as_str())
+                .unwrap_or("M-Pesa rejected the payout")
+                .to_string()),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use actix_web::web;
+use lettre::{transport::smtp::authentication::Credentials, Message, SmtpTransport, Transport};
+use serde_json::json;
+
+use super::{api_response::ApiResponse, constants, json_response::response};
+
+pub async fn send_email(
+    to: &str,
+    subject: &str,
+    body: &str,
+) -> Result<(), ApiResponse> {
+    let Some(smtp_host) = constants::SMTP_HOST.clone() else {
+        return Err(ApiResponse::new(500, response(
+            json!({
+                "error": "Email is not configured".to_string()
+            })
+        )));
+    };
+
+    let email = Message::builder()
+        .from(constants::EMAIL_FROM.parse().map_err(|err: lettre::address::AddressError| {
+            ApiResponse::new(500, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?)
+        .to(to.parse().map_err(|err: lettre::address::AddressError| {
+            ApiResponse::new(400, response(
+                json!({
+                    "error": err.to_string()
+                })
+            ))
+        })?)
+        .subject(subject)
+        .body(body.to_string())
+        .map_err(|err| ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        )))?;
+
+    let mailer = SmtpTransport::relay(&smtp_host)
+        .map_err(|err| ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        )))?
+        .credentials(Credentials::new(constants::SMTP_USERNAME.to_string(), constants::SMTP_PASSWORD.to_string()))
+        .build();
+
+    // The SMTP transport blocks, so it runs off the async workers
+    web::block(move || mailer.send(&email))
+        .await
+        .map_err(|err| ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        )))?
+        .map_err(|err| ApiResponse::new(500, response(
+            json!({
+                "error": err.to_string()
+            })
+        )))?;
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+/// Events are dropped for subscribers that fall this far behind.
+const LIVE_FEED_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone)]
+pub struct LiveEvent {
+    pub auction_id: i32,
+    pub payload: Value,
+}
+
+/// Fans auction events out to every connected WebSocket client. Cloning
+/// gives another handle on the same channel.
+#[derive(Clone)]
+pub struct LiveFeed {
+    sender: broadcast::Sender<LiveEvent>,
+}
+
+impl LiveFeed {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(LIVE_FEED_CAPACITY);
+
+        Self { sender }
+    }
+
+    /// Sends an event to everyone watching the auction. Nobody watching is
+    /// not an error.
+    pub fn publish(&self, auction_id: i32, payload: Value) {
+        let _ = self.sender.send(LiveEvent { auction_id, payload });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<LiveEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for LiveFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,11 @@
 pub mod api_response;
 pub mod app_state;
 pub mod constants;
+pub mod disbursement;
+pub mod email;
 pub mod json_response;
+pub mod live_feed;
+pub mod pdf;
+pub mod phone;
+pub mod session;
 pub mod whatsapp;
\ No newline at end of file
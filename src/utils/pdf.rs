@@ -0,0 +1,45 @@
+use printpdf::{BuiltinFont, Mm, PdfDocument};
+use serde_json::json;
+
+use super::{api_response::ApiResponse, json_response::response};
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 15.0;
+const LINE_HEIGHT: f32 = 4.5;
+const FONT_SIZE: f32 = 9.0;
+
+/// Renders pre-formatted text lines onto A4 pages. A monospaced font is used
+/// so that column layouts built with padding line up in the PDF.
+pub fn render_text_pdf(title: &str, lines: &[String]) -> Result<Vec<u8>, ApiResponse> {
+    let (document, first_page, first_layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+
+    let font = document
+        .add_builtin_font(BuiltinFont::Courier)
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?;
+
+    let lines_per_page = ((PAGE_HEIGHT - 2.0 * MARGIN) / LINE_HEIGHT) as usize;
+
+    for (page_number, page_lines) in lines.chunks(lines_per_page.max(1)).enumerate() {
+        let layer = if page_number == 0 {
+            document.get_page(first_page).get_layer(first_layer)
+        } else {
+            let (page, layer) = document.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+            document.get_page(page).get_layer(layer)
+        };
+
+        for (index, line) in page_lines.iter().enumerate() {
+            let y = PAGE_HEIGHT - MARGIN - (index as f32 * LINE_HEIGHT);
+            layer.use_text(line.as_str(), FONT_SIZE, Mm(MARGIN), Mm(y), &font);
+        }
+    }
+
+    document
+        .save_to_bytes()
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))
+}
+
+/// Cuts a value down to fit a fixed-width column.
+pub fn truncate(value: &str, max_chars: usize) -> String {
+    value.chars().take(max_chars).collect()
+}
@@ -0,0 +1,46 @@
+use phonenumber::{country, metadata::DATABASE, Mode, Type};
+
+use super::constants;
+
+/// Parses a phone number written internationally (+256 7xx...) or in the
+/// local form of the default country (07xx...) and returns it in E.164, the
+/// form accounts are keyed by. Only mobile numbers from the supported
+/// countries are accepted, since every account is reached on WhatsApp.
+pub fn normalize_phone_number(phone_number: &str) -> Result<String, String> {
+    let number = phonenumber::parse(Some(*constants::PHONE_DEFAULT_COUNTRY), phone_number.trim())
+        .map_err(|_| "Phone number is not valid.".to_string())?;
+
+    if !number.is_valid() {
+        return Err("Phone number is not valid.".to_string());
+    }
+
+    match number.country().id() {
+        Some(id) if constants::PHONE_COUNTRIES.contains(&id) => {}
+        _ => return Err("Phone numbers from this country are not supported".to_string()),
+    }
+
+    if !matches!(number.number_type(&DATABASE), Type::Mobile | Type::FixedLineOrMobile) {
+        return Err("Enter a mobile phone number".to_string());
+    }
+
+    Ok(number.format().mode(Mode::E164).to_string())
+}
+
+/// The international number as digits only, country code first, which is
+/// how WhatsApp and M-Pesa address a subscriber.
+pub fn msisdn(phone_number: &str) -> Result<String, String> {
+    let number = phonenumber::parse(Some(*constants::PHONE_DEFAULT_COUNTRY), phone_number.trim())
+        .map_err(|err| format!("{} is not a phone number: {}", phone_number, err))?;
+
+    Ok(format!("{}{}", number.code().value(), number.national()))
+}
+
+/// Reads a comma separated list of ISO country codes such as "KE,UG,TZ".
+pub fn parse_countries(countries: &str) -> Result<Vec<country::Id>, String> {
+    countries
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| id.to_uppercase().parse::<country::Id>().map_err(|_| format!("Unknown country code '{}'", id)))
+        .collect()
+}
@@ -0,0 +1,22 @@
+use actix_session::Session;
+use serde_json::json;
+
+use super::{api_response::ApiResponse, json_response::response};
+
+const USER_ID_KEY: &str = "user_id";
+
+/// Remembers the signed-in user in the session cookie.
+pub fn sign_in(session: &Session, user_id: i32) -> Result<(), ApiResponse> {
+    session.renew();
+    session
+        .insert(USER_ID_KEY, user_id)
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))
+}
+
+/// The signed-in user, or 401 when the session has none.
+pub fn session_user_id(session: &Session) -> Result<i32, ApiResponse> {
+    session
+        .get::<i32>(USER_ID_KEY)
+        .map_err(|err| ApiResponse::new(500, response(json!({ "error": err.to_string() }))))?
+        .ok_or_else(|| ApiResponse::new(401, response(json!({ "error": "Sign in to continue" }))))
+}
@@ -1,12 +1,18 @@
 use serde_json::json;
 use reqwest::{header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE}, Response};
 
-use super::{api_response::ApiResponse, constants, json_response::response};
+use super::{api_response::ApiResponse, constants, json_response::response, phone::msisdn};
 
+/// Sends a text message to a phone number given in E.164 or in local form.
 pub async fn send_whatsapp_message(
     phone_number: &str,
     message: &str,
 ) -> Result<Response, ApiResponse> {  
+    let recipient = msisdn(phone_number).map_err(|err| ApiResponse::new(400, response(
+        json!({
+            "error": err
+        })
+    )))?;
     let whatsapp_phone_number_id = constants::WHATSAPP_PHONE_NUMBER_ID.to_string();  
     let url = &format!("https://graph.facebook.com/v22.0/{}/messages", whatsapp_phone_number_id);
     let mut headers = HeaderMap::new();
@@ -19,7 +25,7 @@ pub async fn send_whatsapp_message(
 
     let body = json!({
         "messaging_product": "whatsapp",
-        "to": phone_number,
+        "to": recipient,
         "type": "text",
         "text": {
             "body": message